@@ -0,0 +1,100 @@
+use forward_mode::dual_numbers::Dual;
+use forward_mode::naive_dual_numbers::NaiveDual;
+use reverse_mode::tape::{Tape, Var};
+
+use crate::{Backend, Function};
+
+/// Число входов, дифференцируемых за один прямой проход в [`Forward::vjp`].
+const CHUNK: usize = 8;
+
+/// Прямой режим на дуальных числах `forward_mode`.
+///
+/// `jvp` — один проход с [`NaiveDual`], `vjp` собирает якобиан по столбцам
+/// блоками по [`CHUNK`] входов с [`Dual`], то есть за `⌈n / CHUNK⌉` проходов.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Forward;
+
+impl Backend for Forward {
+    fn jvp<F: Function>(function: &F, x: &[f64], tangent: &[f64]) -> Vec<f64> {
+        let inputs: Vec<NaiveDual> = x
+            .iter()
+            .zip(tangent)
+            .map(|(&value, &derivative)| NaiveDual::new(value, derivative))
+            .collect();
+        function
+            .eval(&inputs)
+            .iter()
+            .map(NaiveDual::derivative)
+            .collect()
+    }
+
+    fn vjp<F: Function>(function: &F, x: &[f64], cotangent: &[f64]) -> Vec<f64> {
+        let mut result = vec![0.0; x.len()];
+        for start in (0..x.len()).step_by(CHUNK) {
+            let inputs: Vec<Dual<CHUNK>> = x
+                .iter()
+                .enumerate()
+                .map(|(index, &value)| {
+                    if (start..start + CHUNK).contains(&index) {
+                        Dual::variable(index - start, value)
+                    } else {
+                        Dual::constant(value)
+                    }
+                })
+                .collect();
+            let outputs = function.eval(&inputs);
+            for (slot, sum) in result[start..].iter_mut().take(CHUNK).enumerate() {
+                *sum = outputs
+                    .iter()
+                    .zip(cotangent)
+                    .map(|(output, weight)| weight * output.derivatives()[slot])
+                    .sum();
+            }
+        }
+        result
+    }
+}
+
+/// Обратный режим на ленте `reverse_mode`.
+///
+/// `vjp` — один обратный проход, засеянный во всех выходах, `jvp` собирает
+/// якобиан по строкам, то есть за `m` обратных проходов по одной ленте.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Reverse;
+
+impl Backend for Reverse {
+    fn jvp<F: Function>(function: &F, x: &[f64], tangent: &[f64]) -> Vec<f64> {
+        let tape = Tape::new();
+        let (inputs, outputs) = record(&tape, function, x);
+        outputs
+            .iter()
+            .map(|&output| {
+                let grads = tape.gradient(output);
+                inputs
+                    .iter()
+                    .zip(tangent)
+                    .map(|(&input, direction)| grads.wrt(input) * direction)
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn vjp<F: Function>(function: &F, x: &[f64], cotangent: &[f64]) -> Vec<f64> {
+        let tape = Tape::new();
+        let (inputs, outputs) = record(&tape, function, x);
+        let seeds: Vec<(Var<'_>, f64)> =
+            outputs.into_iter().zip(cotangent.iter().copied()).collect();
+        let grads = tape.reverse_many(&seeds);
+        inputs.iter().map(|&input| grads.wrt(input)).collect()
+    }
+}
+
+fn record<'t, F: Function>(
+    tape: &'t Tape,
+    function: &F,
+    x: &[f64],
+) -> (Vec<Var<'t>>, Vec<Var<'t>>) {
+    let inputs: Vec<Var<'t>> = x.iter().map(|&value| tape.var(value)).collect();
+    let outputs = function.eval(&inputs);
+    (inputs, outputs)
+}
@@ -0,0 +1,38 @@
+use std::autodiff::{autodiff_forward, autodiff_reverse};
+
+use crate::{Backend, Function};
+
+/// Бэкенд на `std::autodiff` (Enzyme), включается фичей `enzyme`.
+///
+/// Enzyme дифференцирует мономорфизированный `eval::<f64>`, поэтому функция
+/// проходит через обычные `f64` без дуальных чисел и ленты.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Enzyme;
+
+#[autodiff_forward(eval_forward, Const, Dual, Dual)]
+fn eval_into_forward<F: Function>(function: &F, x: &[f64], out: &mut [f64]) {
+    out.copy_from_slice(&function.eval(x));
+}
+
+#[autodiff_reverse(eval_reverse, Const, Duplicated, Duplicated)]
+fn eval_into_reverse<F: Function>(function: &F, x: &[f64], out: &mut [f64]) {
+    out.copy_from_slice(&function.eval(x));
+}
+
+impl Backend for Enzyme {
+    fn jvp<F: Function>(function: &F, x: &[f64], tangent: &[f64]) -> Vec<f64> {
+        let mut out = vec![0.0; function.outputs()];
+        let mut d_out = vec![0.0; function.outputs()];
+        eval_forward(function, x, tangent, &mut out, &mut d_out);
+        d_out
+    }
+
+    fn vjp<F: Function>(function: &F, x: &[f64], cotangent: &[f64]) -> Vec<f64> {
+        let mut d_x = vec![0.0; x.len()];
+        let mut out = vec![0.0; function.outputs()];
+        // Тень выхода — засеянные сопряжённые; Enzyme обнуляет её после прохода.
+        let mut d_out = cotangent.to_vec();
+        eval_reverse(function, x, &mut d_x, &mut out, &mut d_out);
+        d_x
+    }
+}
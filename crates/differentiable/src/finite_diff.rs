@@ -0,0 +1,307 @@
+use crate::{Backend, Function};
+
+/// Разностная схема.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    /// `(f(x + h) - f(x)) / h`, ошибка `O(h)`.
+    Forward,
+    /// `(f(x) - f(x - h)) / h`, ошибка `O(h)`.
+    Backward,
+    /// `(f(x + h) - f(x - h)) / 2h`, ошибка `O(h²)`.
+    Central,
+}
+
+impl Scheme {
+    /// Порядок погрешности отбрасывания.
+    #[must_use]
+    pub const fn order(self) -> i32 {
+        match self {
+            Self::Forward | Self::Backward => 1,
+            Self::Central => 2,
+        }
+    }
+}
+
+/// Оценка производной и её абсолютной погрешности.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+/// Численное дифференцирование конечными разностями.
+///
+/// Шаг по умолчанию уравновешивает погрешность отбрасывания и ошибку
+/// округления: `ε^(1/(p(L+1)+1)) · max(|x|, 1)`, где `p` — порядок схемы, а
+/// `L` — число уровней Ричардсона (без экстраполяции это `√ε` для односторонних
+/// схем и `∛ε` для центральной). С `richardson(L)` результаты на шагах
+/// `h, h/2, …, h/2^L` экстраполируются по Ричардсону. Погрешность оценивается
+/// по расхождению с соседним приближением таблицы.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FiniteDiff {
+    scheme: Scheme,
+    step: Option<f64>,
+    levels: usize,
+}
+
+impl Default for FiniteDiff {
+    fn default() -> Self {
+        Self::new(Scheme::Central)
+    }
+}
+
+impl FiniteDiff {
+    #[must_use]
+    pub const fn new(scheme: Scheme) -> Self {
+        Self {
+            scheme,
+            step: None,
+            levels: 0,
+        }
+    }
+
+    /// Фиксированный начальный шаг вместо автоматического.
+    #[must_use]
+    pub const fn step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Число уровней экстраполяции Ричардсона.
+    #[must_use]
+    pub const fn richardson(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Производная скалярной функции одной переменной.
+    pub fn derivative(&self, f: impl Fn(f64) -> f64, x: f64) -> Estimate {
+        let step = self.initial_step(x.abs());
+        self.extrapolate(|t| vec![f(x + t)], step)[0]
+    }
+
+    /// Производная по направлению `J(x) · direction` для `f: ℝⁿ → ℝᵐ`.
+    pub fn directional(
+        &self,
+        f: impl Fn(&[f64]) -> Vec<f64>,
+        x: &[f64],
+        direction: &[f64],
+    ) -> Vec<Estimate> {
+        let scale = x.iter().fold(0.0_f64, |max, value| max.max(value.abs()));
+        let length = direction
+            .iter()
+            .fold(0.0_f64, |max, value| max.max(value.abs()));
+        if length == 0.0 {
+            let zero = Estimate {
+                value: 0.0,
+                error: 0.0,
+            };
+            return vec![zero; f(x).len()];
+        }
+        let step = self.initial_step(scale) / length;
+        let mut shifted = x.to_vec();
+        self.extrapolate(
+            |t| {
+                for ((point, &origin), &component) in shifted.iter_mut().zip(x).zip(direction) {
+                    *point = component.mul_add(t, origin);
+                }
+                f(&shifted)
+            },
+            step,
+        )
+    }
+
+    /// Градиент скалярной функции `f: ℝⁿ → ℝ`.
+    pub fn gradient(&self, f: impl Fn(&[f64]) -> f64, x: &[f64]) -> Vec<Estimate> {
+        self.jacobian(|point| vec![f(point)], x)
+            .into_iter()
+            .map(|mut row| row.remove(0))
+            .collect()
+    }
+
+    /// Якобиан `f: ℝⁿ → ℝᵐ` по столбцам: `result[j][i] = ∂fᵢ/∂xⱼ`.
+    pub fn jacobian(&self, f: impl Fn(&[f64]) -> Vec<f64>, x: &[f64]) -> Vec<Vec<Estimate>> {
+        let mut point = x.to_vec();
+        (0..x.len())
+            .map(|column| {
+                let origin = x[column];
+                let step = self.initial_step(origin.abs());
+                self.extrapolate(
+                    |t| {
+                        point[column] = origin + t;
+                        let value = f(&point);
+                        point[column] = origin;
+                        value
+                    },
+                    step,
+                )
+            })
+            .collect()
+    }
+
+    fn initial_step(&self, scale: f64) -> f64 {
+        self.step.unwrap_or_else(|| {
+            let levels = u32::try_from(self.levels).unwrap_or(u32::MAX);
+            let power = self.scheme.order().unsigned_abs() * (levels + 1) + 1;
+            f64::EPSILON.powf(1.0 / f64::from(power)) * scale.max(1.0)
+        })
+    }
+
+    /// Разностное отношение для `g(t)` в нуле с шагом `step`.
+    fn difference(&self, g: &mut impl FnMut(f64) -> Vec<f64>, step: f64) -> Vec<f64> {
+        let (lower, upper) = match self.scheme {
+            Scheme::Forward => (0.0, step),
+            Scheme::Backward => (-step, 0.0),
+            Scheme::Central => (-step, step),
+        };
+        let (low, high) = (g(lower), g(upper));
+        high.iter()
+            .zip(&low)
+            .map(|(high, low)| (high - low) / (upper - lower))
+            .collect()
+    }
+
+    /// Таблица Ричардсона по шагам `step / 2^k`. Погрешность — расхождение
+    /// двух последних приближений; без экстраполяции результат сравнивается с
+    /// экстраполированным по шагам `h` и `h/2`.
+    fn extrapolate(&self, mut g: impl FnMut(f64) -> Vec<f64>, step: f64) -> Vec<Estimate> {
+        let depth = self.levels.max(1);
+        let ratio = 2.0_f64.powi(self.scheme.order());
+        let mut table: Vec<Vec<Vec<f64>>> = Vec::with_capacity(depth + 1);
+        let mut step = step;
+        for level in 0..=depth {
+            let mut row = vec![self.difference(&mut g, step)];
+            let mut factor = 1.0;
+            for column in 1..=level {
+                factor *= ratio;
+                let next = row[column - 1]
+                    .iter()
+                    .zip(&table[level - 1][column - 1])
+                    .map(|(fine, coarse)| fine + (fine - coarse) / (factor - 1.0))
+                    .collect();
+                row.push(next);
+            }
+            table.push(row);
+            step /= 2.0;
+        }
+
+        let best = &table[self.levels][self.levels];
+        let reference = if self.levels == 0 {
+            &table[1][1]
+        } else {
+            &table[depth][depth - 1]
+        };
+        best.iter()
+            .zip(reference)
+            .map(|(&value, other)| Estimate {
+                value,
+                error: (value - other).abs(),
+            })
+            .collect()
+    }
+}
+
+/// Запасной бэкенд на центральных разностях с двумя уровнями Ричардсона.
+///
+/// Нужен для функций, которые нельзя записать через [`crate::Scalar`], и как
+/// независимая проверка остальных бэкендов; точность порядка `1e-10`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FiniteDifference;
+
+impl FiniteDifference {
+    const METHOD: FiniteDiff = FiniteDiff::new(Scheme::Central).richardson(2);
+}
+
+impl Backend for FiniteDifference {
+    fn jvp<F: Function>(function: &F, x: &[f64], tangent: &[f64]) -> Vec<f64> {
+        Self::METHOD
+            .directional(|point| function.eval(point), x, tangent)
+            .iter()
+            .map(|estimate| estimate.value)
+            .collect()
+    }
+
+    fn vjp<F: Function>(function: &F, x: &[f64], cotangent: &[f64]) -> Vec<f64> {
+        Self::METHOD
+            .jacobian(|point| function.eval(point), x)
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .zip(cotangent)
+                    .map(|(estimate, weight)| estimate.value * weight)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FiniteDiff, Scheme};
+
+    #[test]
+    fn central_differences_beat_one_sided() {
+        let exact = 1.3_f64.cos();
+        let error =
+            |scheme| (FiniteDiff::new(scheme).derivative(f64::sin, 1.3).value - exact).abs();
+        let (forward, backward, central) = (
+            error(Scheme::Forward),
+            error(Scheme::Backward),
+            error(Scheme::Central),
+        );
+        assert!(forward < 1e-7 && backward < 1e-7);
+        assert!(central < 1e-9);
+        assert!(central < forward.min(backward));
+    }
+
+    #[test]
+    fn richardson_improves_coarse_steps() {
+        let f = |x: f64| (2.0 * x).exp() / x.mul_add(x, 1.0);
+        let x = 0.4_f64;
+        let exact = f(x) * (2.0 - 2.0 * x / x.mul_add(x, 1.0));
+
+        let plain = FiniteDiff::new(Scheme::Central).step(0.1).derivative(f, x);
+        let extrapolated = FiniteDiff::new(Scheme::Central)
+            .step(0.1)
+            .richardson(3)
+            .derivative(f, x);
+        assert!((extrapolated.value - exact).abs() < 1e-3 * (plain.value - exact).abs());
+        assert!((extrapolated.value - exact).abs() < 1e-8);
+    }
+
+    #[test]
+    fn error_estimate_bounds_actual_error() {
+        for scheme in [Scheme::Forward, Scheme::Backward, Scheme::Central] {
+            for levels in [0, 2] {
+                let estimate = FiniteDiff::new(scheme)
+                    .step(0.05)
+                    .richardson(levels)
+                    .derivative(f64::exp, 0.7);
+                let actual = (estimate.value - 0.7_f64.exp()).abs();
+                assert!(
+                    actual <= 2.0f64.mul_add(estimate.error, 1e-12),
+                    "{scheme:?}, {levels}: {actual} > {}",
+                    estimate.error
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn jacobian_and_gradient_match_analytic() {
+        let f = |x: &[f64]| vec![x[0] * x[1], x[0].sin() + x[1].powi(3)];
+        let x = [0.3, -1.2];
+        let jacobian = FiniteDiff::default().richardson(2).jacobian(f, &x);
+        let expected = [[x[1], x[0].cos()], [x[0], 3.0 * x[1] * x[1]]];
+        for (column, exact) in jacobian.iter().zip(expected) {
+            for (estimate, exact) in column.iter().zip(exact) {
+                assert!((estimate.value - exact).abs() < 1e-10);
+            }
+        }
+
+        let gradient = FiniteDiff::default().gradient(|x| x[0] * x[1], &x);
+        assert!((gradient[0].value - x[1]).abs() < 1e-9);
+        assert!((gradient[1].value - x[0]).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::finite_diff::{FiniteDiff, Scheme};
+
+/// Проверка градиента `f: ℝⁿ → ℝ` с настройками по умолчанию.
+///
+/// Для тестов ручных производных: `check_gradient(f, grad_f, &x).assert_ok()`
+/// падает с таблицей расхождений по каждой компоненте.
+pub fn check_gradient(
+    f: impl Fn(&[f64]) -> f64,
+    grad_f: impl Fn(&[f64]) -> Vec<f64>,
+    x: &[f64],
+) -> GradientReport {
+    GradientCheck::default().check(f, grad_f, x)
+}
+
+/// Сравнение аналитического градиента с конечными разностями.
+///
+/// Проверяется сама точка `x` и ещё `samples - 1` случайных точек в кубе
+/// `x ± radius · max(|xᵢ|, 1)`; генератор детерминирован (`seed`), поэтому
+/// упавший тест воспроизводится. Компонента считается расхождением, если
+/// `|analytic - numeric| > abs + rel · max(|analytic|, |numeric|) + error`, где
+/// `error` — оценка погрешности самих конечных разностей.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientCheck {
+    samples: usize,
+    radius: f64,
+    seed: u64,
+    abs_tolerance: f64,
+    rel_tolerance: f64,
+    method: FiniteDiff,
+}
+
+impl Default for GradientCheck {
+    fn default() -> Self {
+        Self {
+            samples: 5,
+            radius: 0.1,
+            seed: 0,
+            abs_tolerance: 1e-8,
+            rel_tolerance: 1e-6,
+            method: FiniteDiff::new(Scheme::Central).richardson(2),
+        }
+    }
+}
+
+impl GradientCheck {
+    /// Число проверяемых точек, включая саму `x`.
+    #[must_use]
+    pub const fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Относительный радиус окрестности для случайных точек.
+    #[must_use]
+    pub const fn radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    #[must_use]
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Абсолютный и относительный допуски.
+    #[must_use]
+    pub const fn tolerance(mut self, abs: f64, rel: f64) -> Self {
+        self.abs_tolerance = abs;
+        self.rel_tolerance = rel;
+        self
+    }
+
+    /// Схема конечных разностей для эталона.
+    #[must_use]
+    pub const fn method(mut self, method: FiniteDiff) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn check(
+        &self,
+        f: impl Fn(&[f64]) -> f64,
+        grad_f: impl Fn(&[f64]) -> Vec<f64>,
+        x: &[f64],
+    ) -> GradientReport {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let points = (0..self.samples.max(1))
+            .map(|sample| {
+                let point: Vec<f64> = if sample == 0 {
+                    x.to_vec()
+                } else {
+                    x.iter()
+                        .map(|&value| {
+                            let offset = rng.random_range(-self.radius..=self.radius);
+                            offset.mul_add(value.abs().max(1.0), value)
+                        })
+                        .collect()
+                };
+                self.check_point(&f, &grad_f, point)
+            })
+            .collect();
+        GradientReport { points }
+    }
+
+    fn check_point(
+        &self,
+        f: &impl Fn(&[f64]) -> f64,
+        grad_f: &impl Fn(&[f64]) -> Vec<f64>,
+        point: Vec<f64>,
+    ) -> PointReport {
+        let analytic = grad_f(&point);
+        assert_eq!(
+            analytic.len(),
+            point.len(),
+            "Gradient has {} components for {} inputs.",
+            analytic.len(),
+            point.len()
+        );
+        let numeric = self.method.gradient(f, &point);
+        let components = analytic
+            .into_iter()
+            .zip(numeric)
+            .enumerate()
+            .map(|(index, (analytic, numeric))| {
+                let abs_error = (analytic - numeric.value).abs();
+                let scale = analytic.abs().max(numeric.value.abs());
+                let rel_error = if scale == 0.0 { 0.0 } else { abs_error / scale };
+                let allowed = self
+                    .rel_tolerance
+                    .mul_add(scale, self.abs_tolerance + numeric.error);
+                ComponentError {
+                    index,
+                    analytic,
+                    numeric: numeric.value,
+                    numeric_error: numeric.error,
+                    abs_error,
+                    rel_error,
+                    mismatch: abs_error.is_nan() || abs_error > allowed,
+                }
+            })
+            .collect();
+        PointReport { point, components }
+    }
+}
+
+/// Сравнение одной компоненты градиента.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComponentError {
+    pub index: usize,
+    pub analytic: f64,
+    pub numeric: f64,
+    /// Оценка погрешности конечных разностей.
+    pub numeric_error: f64,
+    pub abs_error: f64,
+    pub rel_error: f64,
+    /// Расхождение больше допуска (или `NaN` в одной из оценок).
+    pub mismatch: bool,
+}
+
+/// Результаты в одной точке.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointReport {
+    pub point: Vec<f64>,
+    pub components: Vec<ComponentError>,
+}
+
+/// Отчёт [`GradientCheck::check`] по всем точкам.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientReport {
+    pub points: Vec<PointReport>,
+}
+
+impl GradientReport {
+    /// Все компоненты во всех точках в пределах допуска.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.mismatches().next().is_none()
+    }
+
+    /// Расхождения вместе с точками, где они найдены.
+    pub fn mismatches(&self) -> impl Iterator<Item = (&[f64], &ComponentError)> {
+        self.points.iter().flat_map(|report| {
+            report
+                .components
+                .iter()
+                .filter(|component| component.mismatch)
+                .map(|component| (report.point.as_slice(), component))
+        })
+    }
+
+    #[must_use]
+    pub fn max_abs_error(&self) -> f64 {
+        self.components()
+            .fold(0.0, |max, component| max.max(component.abs_error))
+    }
+
+    #[must_use]
+    pub fn max_rel_error(&self) -> f64 {
+        self.components()
+            .fold(0.0, |max, component| max.max(component.rel_error))
+    }
+
+    /// Проверка в тестах: печатает полный отчёт при расхождении.
+    ///
+    /// # Panics
+    /// Если хотя бы одна компонента вне допуска.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "Gradient check failed:\n{self}");
+    }
+
+    fn components(&self) -> impl Iterator<Item = &ComponentError> {
+        self.points.iter().flat_map(|report| &report.components)
+    }
+}
+
+impl fmt::Display for GradientReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.points {
+            writeln!(f, "x = {:?}", report.point)?;
+            writeln!(
+                f,
+                "{:>5} | {:>14} | {:>14} | {:>10} | {:>10} |",
+                "i", "аналитический", "разностный", "абс.", "отн."
+            )?;
+            for component in &report.components {
+                writeln!(
+                    f,
+                    "{:>5} | {:>14.6e} | {:>14.6e} | {:>10.2e} | {:>10.2e} | {}",
+                    component.index,
+                    component.analytic,
+                    component.numeric,
+                    component.abs_error,
+                    component.rel_error,
+                    if component.mismatch { "✗" } else { "" }
+                )?;
+            }
+        }
+        write!(
+            f,
+            "максимум: абс. {:.2e}, отн. {:.2e}",
+            self.max_abs_error(),
+            self.max_rel_error()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GradientCheck, check_gradient};
+
+    fn f(x: &[f64]) -> f64 {
+        x[0].sin().mul_add(x[1], (x[0] * x[2]).exp())
+    }
+
+    fn grad(x: &[f64]) -> Vec<f64> {
+        let exp = (x[0] * x[2]).exp();
+        vec![x[0].cos().mul_add(x[1], x[2] * exp), x[0].sin(), x[0] * exp]
+    }
+
+    #[test]
+    fn correct_gradient_passes_at_all_points() {
+        let report = check_gradient(f, grad, &[0.4, -1.1, 0.8]);
+        report.assert_ok();
+        assert_eq!(report.points.len(), 5);
+        assert!(report.max_rel_error() < 1e-9);
+    }
+
+    #[test]
+    fn wrong_component_is_flagged() {
+        let wrong = |x: &[f64]| {
+            let mut gradient = grad(x);
+            gradient[2] *= 1.001;
+            gradient
+        };
+        let report = GradientCheck::default()
+            .samples(3)
+            .check(f, wrong, &[0.4, -1.1, 0.8]);
+        assert!(!report.is_ok());
+        assert!(
+            report
+                .mismatches()
+                .all(|(_, component)| component.index == 2)
+        );
+        assert_eq!(report.mismatches().count(), 3);
+        assert!(report.to_string().contains('✗'));
+    }
+}
@@ -0,0 +1,215 @@
+#![cfg_attr(feature = "enzyme", feature(autodiff))]
+
+pub mod backends;
+pub mod complex_step;
+#[cfg(feature = "enzyme")]
+pub mod enzyme;
+pub mod finite_diff;
+pub mod gradcheck;
+pub mod scalar;
+
+use std::marker::PhantomData;
+
+pub use backends::{Forward, Reverse};
+pub use complex_step::ComplexStep;
+#[cfg(feature = "enzyme")]
+pub use enzyme::Enzyme;
+pub use finite_diff::FiniteDifference;
+pub use gradcheck::check_gradient;
+pub use scalar::Scalar;
+
+/// Бэкенд по умолчанию: Enzyme при включённой фиче `enzyme`, иначе лента.
+#[cfg(feature = "enzyme")]
+pub type DefaultBackend = Enzyme;
+/// Бэкенд по умолчанию: Enzyme при включённой фиче `enzyme`, иначе лента.
+#[cfg(not(feature = "enzyme"))]
+pub type DefaultBackend = Reverse;
+
+/// Пользовательская функция `ℝⁿ → ℝᵐ`, записанная один раз для любого [`Scalar`].
+pub trait Function {
+    /// Число выходов `m`.
+    fn outputs(&self) -> usize {
+        1
+    }
+
+    fn eval<S: Scalar>(&self, x: &[S]) -> Vec<S>;
+}
+
+/// Единый интерфейс дифференцирования, не зависящий от бэкенда.
+pub trait Differentiable {
+    /// Значение функции.
+    fn value(&self, x: &[f64]) -> Vec<f64>;
+
+    /// Производная по направлению: `J(x) · tangent`.
+    fn jvp(&self, x: &[f64], tangent: &[f64]) -> Vec<f64>;
+
+    /// Произведение вектора на якобиан: `cotangentᵀ · J(x)`.
+    fn vjp(&self, x: &[f64], cotangent: &[f64]) -> Vec<f64>;
+
+    /// Градиент скалярной функции.
+    fn gradient(&self, x: &[f64]) -> Vec<f64> {
+        self.vjp(x, &[1.0])
+    }
+}
+
+/// Способ вычисления `jvp` и `vjp` для любой [`Function`].
+pub trait Backend {
+    fn jvp<F: Function>(function: &F, x: &[f64], tangent: &[f64]) -> Vec<f64>;
+    fn vjp<F: Function>(function: &F, x: &[f64], cotangent: &[f64]) -> Vec<f64>;
+}
+
+/// Функция вместе с выбранным бэкендом: `Ad::<_, Forward>::new(f)`.
+#[derive(Clone, Copy, Debug)]
+pub struct Ad<F, B = DefaultBackend> {
+    function: F,
+    backend: PhantomData<B>,
+}
+
+impl<F: Function, B: Backend> Ad<F, B> {
+    pub const fn new(function: F) -> Self {
+        Self {
+            function,
+            backend: PhantomData,
+        }
+    }
+
+    pub const fn function(&self) -> &F {
+        &self.function
+    }
+}
+
+impl<F: Function, B: Backend> Differentiable for Ad<F, B> {
+    fn value(&self, x: &[f64]) -> Vec<f64> {
+        self.function.eval(x)
+    }
+
+    fn jvp(&self, x: &[f64], tangent: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            x.len(),
+            tangent.len(),
+            "Tangent has {} components for {} inputs.",
+            tangent.len(),
+            x.len()
+        );
+        B::jvp(&self.function, x, tangent)
+    }
+
+    fn vjp(&self, x: &[f64], cotangent: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            self.function.outputs(),
+            cotangent.len(),
+            "Cotangent has {} components for {} outputs.",
+            cotangent.len(),
+            self.function.outputs()
+        );
+        B::vjp(&self.function, x, cotangent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Ad, Backend, ComplexStep, Differentiable, FiniteDifference, Forward, Function, Reverse,
+        Scalar,
+    };
+
+    /// `f(x, y, z) = [x·y + sin(x)·z, exp(y / z) - x²]`.
+    struct Example;
+
+    impl Function for Example {
+        fn outputs(&self) -> usize {
+            2
+        }
+
+        fn eval<S: Scalar>(&self, x: &[S]) -> Vec<S> {
+            let [x, y, z] = [x[0], x[1], x[2]];
+            vec![x * y + x.sin() * z, (y / z).exp() - x.powi(2)]
+        }
+    }
+
+    /// Якобиан `Example`, записанный вручную.
+    fn jacobian(point: &[f64]) -> [[f64; 3]; 2] {
+        let [x, y, z] = [point[0], point[1], point[2]];
+        let exp = (y / z).exp();
+        [
+            [x.cos().mul_add(z, y), x, x.sin()],
+            [-2.0 * x, exp / z, -exp * y / z.powi(2)],
+        ]
+    }
+
+    fn check_backend<B: Backend>(tolerance: f64) {
+        let ad = Ad::<_, B>::new(Example);
+        let x = [0.7, 1.3, 2.1];
+        let jacobian = jacobian(&x);
+
+        let tangent = [0.5, -1.0, 2.0];
+        let jvp = ad.jvp(&x, &tangent);
+        for (row, value) in jacobian.iter().zip(&jvp) {
+            let expected: f64 = row.iter().zip(&tangent).map(|(j, t)| j * t).sum();
+            assert!((value - expected).abs() < tolerance);
+        }
+
+        let cotangent = [1.5, -0.25];
+        let vjp = ad.vjp(&x, &cotangent);
+        for (col, value) in vjp.iter().enumerate() {
+            let expected = cotangent[0].mul_add(jacobian[0][col], cotangent[1] * jacobian[1][col]);
+            assert!((value - expected).abs() < tolerance);
+        }
+
+        let value = ad.value(&x);
+        assert!((value[1] - ((1.3f64 / 2.1).exp() - 0.49)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn forward_backend_matches_hand_written_jacobian() {
+        check_backend::<Forward>(1e-12);
+    }
+
+    #[test]
+    fn reverse_backend_matches_hand_written_jacobian() {
+        check_backend::<Reverse>(1e-12);
+    }
+
+    #[test]
+    fn finite_difference_backend_matches_hand_written_jacobian() {
+        check_backend::<FiniteDifference>(1e-9);
+    }
+
+    #[test]
+    fn complex_step_backend_matches_hand_written_jacobian() {
+        check_backend::<ComplexStep>(1e-12);
+    }
+
+    #[cfg(feature = "enzyme")]
+    #[test]
+    fn enzyme_backend_matches_hand_written_jacobian() {
+        check_backend::<super::Enzyme>(1e-12);
+    }
+
+    #[test]
+    fn gradient_is_backend_independent() {
+        struct Rosenbrock;
+
+        impl Function for Rosenbrock {
+            fn eval<S: Scalar>(&self, x: &[S]) -> Vec<S> {
+                let sum = x
+                    .windows(2)
+                    .map(|pair| {
+                        let step = pair[1] - pair[0].powi(2);
+                        let offset = -pair[0] + 1.0;
+                        step * step * 100.0 + offset * offset
+                    })
+                    .reduce(|sum, term| sum + term);
+                vec![sum.unwrap()]
+            }
+        }
+
+        // Больше входов, чем помещается в один блок `Dual` прямого режима.
+        let x: Vec<f64> = (0..19).map(|i| f64::from(i).mul_add(0.1, -0.8)).collect();
+        let forward = Ad::<_, Forward>::new(Rosenbrock).gradient(&x);
+        let reverse = Ad::<_, Reverse>::new(Rosenbrock).gradient(&x);
+        for (lhs, rhs) in forward.iter().zip(&reverse) {
+            assert!((lhs - rhs).abs() < 1e-10 * rhs.abs().max(1.0));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use forward_mode::dual_numbers::Dual;
+use forward_mode::naive_dual_numbers::NaiveDual;
+use num_complex::Complex64;
+use reverse_mode::tape::Var;
+
+/// Число, над которым записывается пользовательская функция.
+///
+/// Реализован для `f64` (обычное вычисление), дуальных чисел `forward_mode`,
+/// переменных ленты `reverse_mode` и `Complex64` для комплексного шага, поэтому одна и та же обобщённая функция
+/// дифференцируется любым бэкендом. Константы смешиваются через операции с `f64`.
+pub trait Scalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + Add<f64, Output = Self>
+    + Sub<f64, Output = Self>
+    + Mul<f64, Output = Self>
+    + Div<f64, Output = Self>
+{
+    /// Значение прямого прохода.
+    fn value(&self) -> f64;
+
+    #[must_use]
+    fn sin(self) -> Self;
+    #[must_use]
+    fn cos(self) -> Self;
+    #[must_use]
+    fn tan(self) -> Self;
+    #[must_use]
+    fn exp(self) -> Self;
+    #[must_use]
+    fn ln(self) -> Self;
+    /// `ln(1 + x)`. По умолчанию `(x + 1).ln()`: производная точная, но значение
+    /// теряет точность при малых `x`; `f64` и `Complex64` считают его точно.
+    #[must_use]
+    fn ln_1p(self) -> Self {
+        (self + 1.0).ln()
+    }
+    #[must_use]
+    fn sqrt(self) -> Self;
+    #[must_use]
+    fn powi(self, exponent: i32) -> Self;
+    #[must_use]
+    fn powf(self, exponent: f64) -> Self;
+}
+
+macro_rules! forward_elementary {
+    ($ty:ty) => {
+        fn sin(self) -> Self {
+            <$ty>::sin(self)
+        }
+
+        fn cos(self) -> Self {
+            <$ty>::cos(self)
+        }
+
+        fn tan(self) -> Self {
+            <$ty>::tan(self)
+        }
+
+        fn exp(self) -> Self {
+            <$ty>::exp(self)
+        }
+
+        fn ln(self) -> Self {
+            <$ty>::ln(self)
+        }
+
+        fn sqrt(self) -> Self {
+            <$ty>::sqrt(self)
+        }
+
+        fn powi(self, exponent: i32) -> Self {
+            <$ty>::powi(self, exponent)
+        }
+
+        fn powf(self, exponent: f64) -> Self {
+            <$ty>::powf(self, exponent)
+        }
+    };
+}
+
+impl Scalar for f64 {
+    fn value(&self) -> f64 {
+        *self
+    }
+
+    fn ln_1p(self) -> Self {
+        Self::ln_1p(self)
+    }
+
+    forward_elementary!(f64);
+}
+
+impl Scalar for NaiveDual {
+    fn value(&self) -> f64 {
+        Self::value(self)
+    }
+
+    forward_elementary!(NaiveDual);
+}
+
+impl<const N: usize> Scalar for Dual<N> {
+    fn value(&self) -> f64 {
+        Self::value(self)
+    }
+
+    forward_elementary!(Dual<N>);
+}
+
+impl Scalar for Var<'_> {
+    fn value(&self) -> f64 {
+        Self::value(self)
+    }
+
+    forward_elementary!(Var<'_>);
+}
+
+/// Голоморфное продолжение элементарных функций. Ветвления по [`Scalar::value`]
+/// видят только вещественную часть, поэтому сравнения тоже работают.
+impl Scalar for Complex64 {
+    fn value(&self) -> f64 {
+        self.re
+    }
+
+    fn sin(self) -> Self {
+        Self::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        Self::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        Self::tan(self)
+    }
+
+    fn exp(self) -> Self {
+        Self::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        Self::ln(self)
+    }
+
+    /// `|1 + z|² = 1 + (2 + a)·a + b²`, поэтому вещественная часть не теряет
+    /// точность при малых `z`.
+    fn ln_1p(self) -> Self {
+        let Self { re, im } = self;
+        Self::new(
+            0.5 * im.mul_add(im, (2.0 + re) * re).ln_1p(),
+            im.atan2(1.0 + re),
+        )
+    }
+
+    fn sqrt(self) -> Self {
+        Self::sqrt(self)
+    }
+
+    fn powi(self, exponent: i32) -> Self {
+        Self::powi(&self, exponent)
+    }
+
+    fn powf(self, exponent: f64) -> Self {
+        Self::powf(self, exponent)
+    }
+}
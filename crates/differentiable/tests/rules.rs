@@ -0,0 +1,207 @@
+//! Свойства правил дифференцирования: для каждой элементарной функции и
+//! арифметической операции производные `NaiveDual`, `Dual<N>` и ленты
+//! `reverse_mode` совпадают между собой и с конечными разностями на случайных
+//! входах из области определения. При падении proptest сужает вход до
+//! минимального контрпримера.
+
+use differentiable::Scalar;
+use differentiable::finite_diff::FiniteDiff;
+use forward_mode::dual_numbers::{Dual, variables};
+use forward_mode::naive_dual_numbers::NaiveDual;
+use proptest::prelude::*;
+use reverse_mode::tape::Tape;
+
+#[derive(Clone, Copy, Debug)]
+enum Unary {
+    Neg,
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Ln1p,
+    Sqrt,
+    Powi(i32),
+    Powf(f64),
+}
+
+impl Unary {
+    fn apply<S: Scalar>(self, x: S) -> S {
+        match self {
+            Self::Neg => -x,
+            Self::Sin => x.sin(),
+            Self::Cos => x.cos(),
+            Self::Tan => x.tan(),
+            Self::Exp => x.exp(),
+            Self::Ln => x.ln(),
+            Self::Ln1p => x.ln_1p(),
+            Self::Sqrt => x.sqrt(),
+            Self::Powi(exponent) => x.powi(exponent),
+            Self::Powf(exponent) => x.powf(exponent),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Binary {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Операции с константой `f64` справа.
+    AddConst(f64),
+    SubConst(f64),
+    MulConst(f64),
+    DivConst(f64),
+}
+
+impl Binary {
+    fn apply<S: Scalar>(self, x: S, y: S) -> S {
+        match self {
+            Self::Add => x + y,
+            Self::Sub => x - y,
+            Self::Mul => x * y,
+            Self::Div => x / y,
+            Self::AddConst(c) => x + c + y,
+            Self::SubConst(c) => x - c + y,
+            Self::MulConst(c) => x * c + y,
+            Self::DivConst(c) => x / c + y,
+        }
+    }
+}
+
+/// Значение по модулю от `0.1` до `10` с любым знаком: вдали от нуля для
+/// делителей и отрицательных степеней.
+fn away_from_zero() -> impl Strategy<Value = f64> {
+    (0.1..10.0_f64, any::<bool>()).prop_map(|(x, negative)| if negative { -x } else { x })
+}
+
+/// Функция вместе с точкой из её области определения.
+fn unary() -> impl Strategy<Value = (Unary, f64)> {
+    prop_oneof![
+        (-10.0..10.0).prop_map(|x| (Unary::Neg, x)),
+        (-10.0..10.0).prop_map(|x| (Unary::Sin, x)),
+        (-10.0..10.0).prop_map(|x| (Unary::Cos, x)),
+        (-1.4..1.4).prop_map(|x| (Unary::Tan, x)),
+        (-20.0..20.0).prop_map(|x| (Unary::Exp, x)),
+        (1e-3..1e3).prop_map(|x| (Unary::Ln, x)),
+        (-0.9..10.0).prop_map(|x| (Unary::Ln1p, x)),
+        (1e-3..1e3).prop_map(|x| (Unary::Sqrt, x)),
+        (-4..=4, away_from_zero()).prop_map(|(n, x)| (Unary::Powi(n), x)),
+        (-3.0..3.0, 0.1..10.0).prop_map(|(p, x)| (Unary::Powf(p), x)),
+    ]
+}
+
+/// Функции, определённые на всей прямой, для композиций.
+fn smooth() -> impl Strategy<Value = Unary> {
+    prop_oneof![
+        Just(Unary::Neg),
+        Just(Unary::Sin),
+        Just(Unary::Cos),
+        Just(Unary::Exp),
+        (0..=4).prop_map(Unary::Powi),
+    ]
+}
+
+fn binary() -> impl Strategy<Value = (Binary, f64, f64)> {
+    let any = -10.0..10.0;
+    prop_oneof![
+        (any.clone(), any.clone()).prop_map(|(x, y)| (Binary::Add, x, y)),
+        (any.clone(), any.clone()).prop_map(|(x, y)| (Binary::Sub, x, y)),
+        (any.clone(), any.clone()).prop_map(|(x, y)| (Binary::Mul, x, y)),
+        (any.clone(), away_from_zero()).prop_map(|(x, y)| (Binary::Div, x, y)),
+        (any.clone(), any.clone(), any.clone()).prop_map(|(c, x, y)| (Binary::AddConst(c), x, y)),
+        (any.clone(), any.clone(), any.clone()).prop_map(|(c, x, y)| (Binary::SubConst(c), x, y)),
+        (any.clone(), any.clone(), any.clone()).prop_map(|(c, x, y)| (Binary::MulConst(c), x, y)),
+        (away_from_zero(), any.clone(), any).prop_map(|(c, x, y)| (Binary::DivConst(c), x, y)),
+    ]
+}
+
+/// Производные бэкендов одного правила совпадают почти до последнего бита.
+fn assert_agree(lhs: f64, rhs: f64, what: &str) -> Result<(), TestCaseError> {
+    prop_assert!(
+        (lhs - rhs).abs() <= 1e-12 * lhs.abs().max(rhs.abs()).max(1.0),
+        "{what}: {lhs} vs {rhs}"
+    );
+    Ok(())
+}
+
+/// Конечные разности сверяются с допуском на их собственную погрешность.
+fn assert_matches_fd(exact: f64, estimate: f64, error: f64) -> Result<(), TestCaseError> {
+    let allowed = 1e-6f64.mul_add(exact.abs().max(1.0), 2.0 * error);
+    prop_assert!(
+        (exact - estimate).abs() <= allowed,
+        "AD {exact} vs FD {estimate} ± {error}"
+    );
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn unary_rules((op, x) in unary()) {
+        let naive = op.apply(NaiveDual::variable(x)).derivative();
+        let forward = op.apply(Dual::<1>::variable(0, x)).derivatives()[0];
+        let tape = Tape::new();
+        let input = tape.var(x);
+        let reverse = tape.gradient(op.apply(input)).wrt(input);
+
+        assert_agree(naive, forward, "naive vs forward")?;
+        assert_agree(naive, reverse, "naive vs reverse")?;
+
+        let estimate = FiniteDiff::default().richardson(2).derivative(|x| op.apply(x), x);
+        assert_matches_fd(naive, estimate.value, estimate.error)?;
+    }
+
+    #[test]
+    fn binary_rules((op, x, y) in binary()) {
+        let naive = [
+            op.apply(NaiveDual::variable(x), NaiveDual::constant(y)).derivative(),
+            op.apply(NaiveDual::constant(x), NaiveDual::variable(y)).derivative(),
+        ];
+        let [dx, dy] = variables([x, y]);
+        let forward = *op.apply(dx, dy).derivatives();
+        let tape = Tape::new();
+        let (u, v) = (tape.var(x), tape.var(y));
+        let grads = tape.gradient(op.apply(u, v));
+        let reverse = [grads.wrt(u), grads.wrt(v)];
+
+        let estimates = FiniteDiff::default()
+            .richardson(2)
+            .gradient(|p| op.apply(p[0], p[1]), &[x, y]);
+        for index in 0..2 {
+            assert_agree(naive[index], forward[index], "naive vs forward")?;
+            assert_agree(naive[index], reverse[index], "naive vs reverse")?;
+            let estimate = estimates[index];
+            assert_matches_fd(naive[index], estimate.value, estimate.error)?;
+        }
+    }
+
+    /// Композиция проверяет цепное правило через всю цепочку узлов.
+    #[test]
+    fn chain_rule_through_compositions(
+        outer in smooth(),
+        inner in smooth(),
+        x in -3.0..3.0,
+        scale in 0.05..0.5,
+    ) {
+        let f = |x: f64| outer.apply(inner.apply(x).mul_add(scale, x));
+        let naive = {
+            let x = NaiveDual::variable(x);
+            outer.apply(inner.apply(x) * scale + x).derivative()
+        };
+        let forward = {
+            let x = Dual::<1>::variable(0, x);
+            outer.apply(inner.apply(x) * scale + x).derivatives()[0]
+        };
+        let tape = Tape::new();
+        let input = tape.var(x);
+        let reverse = tape
+            .gradient(outer.apply(inner.apply(input) * scale + input))
+            .wrt(input);
+
+        assert_agree(naive, forward, "naive vs forward")?;
+        assert_agree(naive, reverse, "naive vs reverse")?;
+        let estimate = FiniteDiff::default().richardson(2).derivative(f, x);
+        assert_matches_fd(naive, estimate.value, estimate.error)?;
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    // Тест `tests/c_abi.rs` собирает C-программу через `cc` вне build-скрипта,
+    // и ему нужна целевая платформа.
+    let target = std::env::var("TARGET").expect("cargo sets TARGET for build scripts");
+    println!("cargo:rustc-env=FAKE_CBLAS_TARGET={target}");
+    println!("cargo:rerun-if-changed=build.rs");
+}
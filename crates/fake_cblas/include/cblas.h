@@ -0,0 +1,162 @@
+/*
+ * Заголовок fake_cblas, совместимый с cblas.h эталонного CBLAS (кроме
+ * cblas_xerbla, см. ниже).
+ *
+ * Поддерживается вручную: при добавлении функции в Rust-библиотеку её
+ * прототип добавляется сюда же. Тест tests/c_abi.rs собирает C-программу с
+ * этим заголовком и линкует её со статической библиотекой.
+ */
+#ifndef FAKE_CBLAS_H
+#define FAKE_CBLAS_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum CBLAS_LAYOUT { CblasRowMajor = 101, CblasColMajor = 102 } CBLAS_LAYOUT;
+typedef enum CBLAS_TRANSPOSE {
+    CblasNoTrans = 111,
+    CblasTrans = 112,
+    CblasConjTrans = 113
+} CBLAS_TRANSPOSE;
+typedef enum CBLAS_UPLO { CblasUpper = 121, CblasLower = 122 } CBLAS_UPLO;
+typedef enum CBLAS_DIAG { CblasNonUnit = 131, CblasUnit = 132 } CBLAS_DIAG;
+typedef enum CBLAS_SIDE { CblasLeft = 141, CblasRight = 142 } CBLAS_SIDE;
+
+/* Старое имя раскладки из CBLAS до версии 3.8. */
+#define CBLAS_ORDER CBLAS_LAYOUT
+
+#define CBLAS_INDEX size_t
+
+/*
+ * Число потоков: начальное значение берётся из FAKE_CBLAS_NUM_THREADS, 1 —
+ * последовательное выполнение, n <= 0 — все потоки пула.
+ */
+void fake_cblas_set_num_threads(int n);
+int fake_cblas_get_num_threads(void);
+
+/*
+ * Неверные аргументы: функция ничего не вычисляет и передаёт номер параметра
+ * (с единицы, считая layout) и своё имя обработчику. По умолчанию это
+ * fake_cblas_default_xerbla, печатающий сообщение в stderr. Эталонный
+ * вариативный cblas_xerbla не экспортируется: обработчик заменяется не
+ * символом при линковке, а вызовом fake_cblas_set_xerbla; он возвращает
+ * предыдущий обработчик, NULL восстанавливает обработчик по умолчанию.
+ */
+typedef void (*fake_cblas_xerbla_handler)(int p, const char *rout);
+void fake_cblas_default_xerbla(int p, const char *rout);
+fake_cblas_xerbla_handler fake_cblas_set_xerbla(fake_cblas_xerbla_handler handler);
+
+/* Level 1 */
+
+double cblas_ddot(const int N, const double *X, const int incX, const double *Y, const int incY);
+void cblas_daxpy(const int N, const double alpha, const double *X, const int incX, double *Y,
+                 const int incY);
+void cblas_dscal(const int N, const double alpha, double *X, const int incX);
+void cblas_dcopy(const int N, const double *X, const int incX, double *Y, const int incY);
+void cblas_dswap(const int N, double *X, const int incX, double *Y, const int incY);
+double cblas_dnrm2(const int N, const double *X, const int incX);
+double cblas_dasum(const int N, const double *X, const int incX);
+CBLAS_INDEX cblas_idamax(const int N, const double *X, const int incX);
+void cblas_drot(const int N, double *X, const int incX, double *Y, const int incY, const double c,
+                const double s);
+void cblas_drotg(double *a, double *b, double *c, double *s);
+
+float cblas_sdot(const int N, const float *X, const int incX, const float *Y, const int incY);
+void cblas_saxpy(const int N, const float alpha, const float *X, const int incX, float *Y,
+                 const int incY);
+void cblas_sscal(const int N, const float alpha, float *X, const int incX);
+void cblas_scopy(const int N, const float *X, const int incX, float *Y, const int incY);
+void cblas_sswap(const int N, float *X, const int incX, float *Y, const int incY);
+float cblas_snrm2(const int N, const float *X, const int incX);
+float cblas_sasum(const int N, const float *X, const int incX);
+CBLAS_INDEX cblas_isamax(const int N, const float *X, const int incX);
+void cblas_srot(const int N, float *X, const int incX, float *Y, const int incY, const float c,
+                const float s);
+void cblas_srotg(float *a, float *b, float *c, float *s);
+
+/* Level 2 */
+
+void cblas_dgemv(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, const int M, const int N,
+                 const double alpha, const double *A, const int lda, const double *X,
+                 const int incX, const double beta, double *Y, const int incY);
+void cblas_dger(CBLAS_LAYOUT layout, const int M, const int N, const double alpha,
+                const double *X, const int incX, const double *Y, const int incY, double *A,
+                const int lda);
+void cblas_dsymv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, const int N, const double alpha,
+                 const double *A, const int lda, const double *X, const int incX,
+                 const double beta, double *Y, const int incY);
+void cblas_dtrmv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA, CBLAS_DIAG Diag,
+                 const int N, const double *A, const int lda, double *X, const int incX);
+void cblas_dtrsv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA, CBLAS_DIAG Diag,
+                 const int N, const double *A, const int lda, double *X, const int incX);
+
+void cblas_sgemv(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, const int M, const int N,
+                 const float alpha, const float *A, const int lda, const float *X,
+                 const int incX, const float beta, float *Y, const int incY);
+void cblas_sger(CBLAS_LAYOUT layout, const int M, const int N, const float alpha, const float *X,
+                const int incX, const float *Y, const int incY, float *A, const int lda);
+void cblas_ssymv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, const int N, const float alpha,
+                 const float *A, const int lda, const float *X, const int incX, const float beta,
+                 float *Y, const int incY);
+void cblas_strmv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA, CBLAS_DIAG Diag,
+                 const int N, const float *A, const int lda, float *X, const int incX);
+void cblas_strsv(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA, CBLAS_DIAG Diag,
+                 const int N, const float *A, const int lda, float *X, const int incX);
+
+/* Level 3 */
+
+void cblas_dgemm(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, CBLAS_TRANSPOSE TransB,
+                 const int M, const int N, const int K, const double alpha,
+                 const double *A, const int lda, const double *B, const int ldb,
+                 const double beta, double *C, const int ldc);
+void cblas_sgemm(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, CBLAS_TRANSPOSE TransB,
+                 const int M, const int N, const int K, const float alpha, const float *A,
+                 const int lda, const float *B, const int ldb, const float beta, float *C,
+                 const int ldc);
+/* Комплексные элементы — пары (re, im), alpha и beta передаются по указателю. */
+void cblas_cgemm(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, CBLAS_TRANSPOSE TransB,
+                 const int M, const int N, const int K, const void *alpha, const void *A,
+                 const int lda, const void *B, const int ldb, const void *beta, void *C,
+                 const int ldc);
+void cblas_zgemm(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA, CBLAS_TRANSPOSE TransB,
+                 const int M, const int N, const int K, const void *alpha, const void *A,
+                 const int lda, const void *B, const int ldb, const void *beta, void *C,
+                 const int ldc);
+void cblas_dsymm(CBLAS_LAYOUT layout, CBLAS_SIDE Side, CBLAS_UPLO Uplo, const int M, const int N,
+                 const double alpha, const double *A, const int lda, const double *B,
+                 const int ldb, const double beta, double *C, const int ldc);
+void cblas_dsyrk(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE Trans, const int N,
+                 const int K, const double alpha, const double *A, const int lda,
+                 const double beta, double *C, const int ldc);
+void cblas_dsyr2k(CBLAS_LAYOUT layout, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE Trans, const int N,
+                  const int K, const double alpha, const double *A, const int lda,
+                  const double *B, const int ldb, const double beta, double *C, const int ldc);
+void cblas_dtrmm(CBLAS_LAYOUT layout, CBLAS_SIDE Side, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA,
+                 CBLAS_DIAG Diag, const int M, const int N, const double alpha, const double *A,
+                 const int lda, double *B, const int ldb);
+void cblas_dtrsm(CBLAS_LAYOUT layout, CBLAS_SIDE Side, CBLAS_UPLO Uplo, CBLAS_TRANSPOSE TransA,
+                 CBLAS_DIAG Diag, const int M, const int N, const double alpha, const double *A,
+                 const int lda, double *B, const int ldb);
+
+/* Пакетный dgemm в соглашениях MKL. */
+void cblas_dgemm_batch_strided(CBLAS_LAYOUT layout, CBLAS_TRANSPOSE TransA,
+                               CBLAS_TRANSPOSE TransB, const int M, const int N, const int K,
+                               const double alpha, const double *A, const int lda,
+                               const int stridea, const double *B, const int ldb,
+                               const int strideb, const double beta, double *C, const int ldc,
+                               const int stridec, const int batch_size);
+void cblas_dgemm_batch(CBLAS_LAYOUT layout, const CBLAS_TRANSPOSE *TransA_Array,
+                       const CBLAS_TRANSPOSE *TransB_Array, const int *M_Array,
+                       const int *N_Array, const int *K_Array, const double *alpha_Array,
+                       const double **A_Array, const int *lda_Array, const double **B_Array,
+                       const int *ldb_Array, const double *beta_Array, double **C_Array,
+                       const int *ldc_Array, const int group_count, const int *group_size);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FAKE_CBLAS_H */
@@ -0,0 +1,568 @@
+//! Ручные правила дифференцирования GEMM, работающие без Enzyme.
+//!
+//! Для `C = alpha·op(A)·op(B) + beta·C₀` и сопряжённой `C̄ = ∂L/∂C`:
+//!
+//! - `op(A)̄ = alpha·C̄·op(B)ᵀ`, `op(B)̄ = alpha·op(A)ᵀ·C̄`;
+//! - `ᾱ = ⟨C̄, op(A)·op(B)⟩`, `β̄ = ⟨C̄, C₀⟩`, `C̄₀ = beta·C̄`.
+//!
+//! Для `Trans` градиент хранимой матрицы — транспонированный градиент `op(X)`.
+//! Все произведения считаются снова через [`cblas_dgemm`] в той же раскладке и с
+//! теми же ведущими размерами, поэтому градиенты и касательные имеют форму
+//! входных буферов, а элементы-заполнители между строками или столбцами в них
+//! равны нулю.
+
+use core::ffi::c_int;
+
+use crate::level3::check_gemm;
+use crate::{CBLAS_LAYOUT, CBLAS_TRANSPOSE, cblas_dgemm, dim};
+
+/// Аргументы одного вызова [`cblas_dgemm`] с буферами-срезами; `c` — исходное
+/// содержимое `C₀`.
+#[derive(Clone, Copy, Debug)]
+pub struct Gemm<'a> {
+    pub layout: CBLAS_LAYOUT,
+    pub transa: CBLAS_TRANSPOSE,
+    pub transb: CBLAS_TRANSPOSE,
+    pub m: c_int,
+    pub n: c_int,
+    pub k: c_int,
+    pub alpha: f64,
+    pub a: &'a [f64],
+    pub lda: c_int,
+    pub b: &'a [f64],
+    pub ldb: c_int,
+    pub beta: f64,
+    pub c: &'a [f64],
+    pub ldc: c_int,
+}
+
+/// Градиенты `L` по всем входам GEMM; `a`, `b`, `c` имеют форму буферов `A`,
+/// `B`, `C₀`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GemmGradient {
+    pub a: Vec<f64>,
+    pub b: Vec<f64>,
+    pub alpha: f64,
+    pub beta: f64,
+    pub c: Vec<f64>,
+}
+
+/// Касательные ко входам GEMM для [`gemm_jvp`]; буферы имеют форму `A`, `B`, `C₀`.
+#[derive(Clone, Copy, Debug)]
+pub struct GemmTangent<'a> {
+    pub a: &'a [f64],
+    pub b: &'a [f64],
+    pub alpha: f64,
+    pub beta: f64,
+    pub c: &'a [f64],
+}
+
+/// Число элементов буфера под матрицу `rows × cols` с ведущим размером `ld`.
+fn stored_len(layout: CBLAS_LAYOUT, rows: usize, cols: usize, ld: usize) -> usize {
+    if rows == 0 || cols == 0 {
+        0
+    } else if layout == CBLAS_LAYOUT::RowMajor {
+        (rows - 1) * ld + cols
+    } else {
+        (cols - 1) * ld + rows
+    }
+}
+
+/// Обратное транспонирование для вещественных матриц.
+fn flip(trans: CBLAS_TRANSPOSE) -> CBLAS_TRANSPOSE {
+    if trans == CBLAS_TRANSPOSE::NoTrans {
+        CBLAS_TRANSPOSE::Trans
+    } else {
+        CBLAS_TRANSPOSE::NoTrans
+    }
+}
+
+impl<'a> Gemm<'a> {
+    /// `ColMajor` без транспонирования и с плотно упакованными матрицами, как в
+    /// демонстрации `main.rs`.
+    ///
+    /// # Panics
+    /// Если размеры не помещаются в `c_int`.
+    #[allow(clippy::too_many_arguments)]
+    #[must_use]
+    pub fn col_major(
+        m: usize,
+        n: usize,
+        k: usize,
+        alpha: f64,
+        a: &'a [f64],
+        b: &'a [f64],
+        beta: f64,
+        c: &'a [f64],
+    ) -> Self {
+        let [m, n, k] =
+            [m, n, k].map(|size| c_int::try_from(size).expect("dimension fits in c_int"));
+        Self {
+            layout: CBLAS_LAYOUT::ColMajor,
+            transa: CBLAS_TRANSPOSE::NoTrans,
+            transb: CBLAS_TRANSPOSE::NoTrans,
+            m,
+            n,
+            k,
+            alpha,
+            a,
+            lda: m.max(1),
+            b,
+            ldb: k.max(1),
+            beta,
+            c,
+            ldc: m.max(1),
+        }
+    }
+
+    /// Результат `C = alpha·op(A)·op(B) + beta·C₀`.
+    ///
+    /// # Panics
+    /// Если аргументы не прошли бы проверку `cblas_dgemm` или буферы короче
+    /// описанных матриц.
+    #[must_use]
+    pub fn forward(&self) -> Vec<f64> {
+        self.validate();
+        let mut c = self.c.to_vec();
+        unsafe { self.call(self.alpha, self.a, self.b, self.beta, &mut c) };
+        c
+    }
+
+    /// Аргументы корректны, а буферы покрывают матрицы — после этого вызовы
+    /// `cblas_dgemm` с буферами той же формы безопасны.
+    #[track_caller]
+    fn validate(&self) {
+        let Self {
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            lda,
+            ldb,
+            ldc,
+            ..
+        } = *self;
+        if let Some(position) = check_gemm(layout, transa, transb, m, n, k, lda, ldb, ldc) {
+            panic!("GEMM argument {position} is invalid");
+        }
+        let [m, n, k, lda, ldb, ldc] = [m, n, k, lda, ldb, ldc].map(dim);
+        let (a_rows, a_cols) = if transa == CBLAS_TRANSPOSE::NoTrans {
+            (m, k)
+        } else {
+            (k, m)
+        };
+        let (b_rows, b_cols) = if transb == CBLAS_TRANSPOSE::NoTrans {
+            (k, n)
+        } else {
+            (n, k)
+        };
+        assert!(
+            self.a.len() >= stored_len(layout, a_rows, a_cols, lda),
+            "A buffer is shorter than the matrix"
+        );
+        assert!(
+            self.b.len() >= stored_len(layout, b_rows, b_cols, ldb),
+            "B buffer is shorter than the matrix"
+        );
+        assert!(
+            self.c.len() >= stored_len(layout, m, n, ldc),
+            "C buffer is shorter than the matrix"
+        );
+    }
+
+    /// Индексы элементов `C` в буфере без заполнителей.
+    fn c_indices(&self) -> impl Iterator<Item = usize> + use<> {
+        let (layout, ld) = (self.layout, dim(self.ldc));
+        let [m, n] = [self.m, self.n].map(dim);
+        (0..m).flat_map(move |row| {
+            (0..n).map(move |col| {
+                if layout == CBLAS_LAYOUT::RowMajor {
+                    row * ld + col
+                } else {
+                    row + col * ld
+                }
+            })
+        })
+    }
+
+    /// `c := alpha·op(a)·op(b) + beta·c` с раскладкой и размерами этого вызова.
+    ///
+    /// # Safety
+    /// [`Self::validate`] пройдена, а `a`, `b`, `c` не короче `self.a`,
+    /// `self.b`, `self.c`.
+    unsafe fn call(&self, alpha: f64, a: &[f64], b: &[f64], beta: f64, c: &mut [f64]) {
+        unsafe {
+            cblas_dgemm(
+                self.layout,
+                self.transa,
+                self.transb,
+                self.m,
+                self.n,
+                self.k,
+                alpha,
+                a.as_ptr(),
+                self.lda,
+                b.as_ptr(),
+                self.ldb,
+                beta,
+                c.as_mut_ptr(),
+                self.ldc,
+            );
+        }
+    }
+}
+
+/// Обратный проход GEMM: по `dc = ∂L/∂C` (форма буфера `C`) возвращает
+/// градиенты по `A`, `B`, `alpha`, `beta` и `C₀`.
+///
+/// # Panics
+/// Если аргументы `gemm` некорректны или `dc` не совпадает по длине с `gemm.c`.
+#[must_use]
+pub fn gemm_vjp(gemm: &Gemm<'_>, dc: &[f64]) -> GemmGradient {
+    gemm.validate();
+    assert_eq!(
+        dc.len(),
+        gemm.c.len(),
+        "dC must have the shape of the C buffer"
+    );
+    let Gemm {
+        layout,
+        transa,
+        transb,
+        m,
+        n,
+        k,
+        alpha,
+        a,
+        lda,
+        b,
+        ldb,
+        beta,
+        c,
+        ldc,
+    } = *gemm;
+    let (no, tr) = (CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans);
+
+    // Формы совпадают с исходными матрицами, поэтому проверки `validate`
+    // покрывают и эти вызовы.
+    let mut da = vec![0.0; a.len()];
+    let mut db = vec![0.0; b.len()];
+    unsafe {
+        if transa == no {
+            // Ā = alpha·C̄·op(B)ᵀ
+            cblas_dgemm(
+                layout,
+                no,
+                flip(transb),
+                m,
+                k,
+                n,
+                alpha,
+                dc.as_ptr(),
+                ldc,
+                b.as_ptr(),
+                ldb,
+                0.0,
+                da.as_mut_ptr(),
+                lda,
+            );
+        } else {
+            // Ā = (alpha·C̄·op(B)ᵀ)ᵀ = alpha·op(B)·C̄ᵀ
+            cblas_dgemm(
+                layout,
+                transb,
+                tr,
+                k,
+                m,
+                n,
+                alpha,
+                b.as_ptr(),
+                ldb,
+                dc.as_ptr(),
+                ldc,
+                0.0,
+                da.as_mut_ptr(),
+                lda,
+            );
+        }
+        if transb == no {
+            // B̄ = alpha·op(A)ᵀ·C̄
+            cblas_dgemm(
+                layout,
+                flip(transa),
+                no,
+                k,
+                n,
+                m,
+                alpha,
+                a.as_ptr(),
+                lda,
+                dc.as_ptr(),
+                ldc,
+                0.0,
+                db.as_mut_ptr(),
+                ldb,
+            );
+        } else {
+            // B̄ = (alpha·op(A)ᵀ·C̄)ᵀ = alpha·C̄ᵀ·op(A)
+            cblas_dgemm(
+                layout,
+                tr,
+                transa,
+                n,
+                k,
+                m,
+                alpha,
+                dc.as_ptr(),
+                ldc,
+                a.as_ptr(),
+                lda,
+                0.0,
+                db.as_mut_ptr(),
+                ldb,
+            );
+        }
+    }
+
+    let mut product = vec![0.0; c.len()];
+    unsafe { gemm.call(1.0, a, b, 0.0, &mut product) };
+    let mut dc_in = vec![0.0; c.len()];
+    let (mut d_alpha, mut d_beta) = (0.0, 0.0);
+    for index in gemm.c_indices() {
+        d_alpha += dc[index] * product[index];
+        d_beta += dc[index] * c[index];
+        dc_in[index] = beta * dc[index];
+    }
+
+    GemmGradient {
+        a: da,
+        b: db,
+        alpha: d_alpha,
+        beta: d_beta,
+        c: dc_in,
+    }
+}
+
+/// Прямой проход производной GEMM: касательная к `C` (форма буфера `C`)
+///
+/// `Ċ = alphȧ·op(A)·op(B) + alpha·op(Ȧ)·op(B) + alpha·op(A)·op(Ḃ) + betȧ·C₀ + beta·Ċ₀`.
+///
+/// # Panics
+/// Если аргументы `gemm` некорректны или буферы `tangent` не совпадают по длине
+/// с буферами `gemm`.
+#[must_use]
+pub fn gemm_jvp(gemm: &Gemm<'_>, tangent: &GemmTangent<'_>) -> Vec<f64> {
+    gemm.validate();
+    assert_eq!(
+        tangent.a.len(),
+        gemm.a.len(),
+        "tangent of A must have the shape of A"
+    );
+    assert_eq!(
+        tangent.b.len(),
+        gemm.b.len(),
+        "tangent of B must have the shape of B"
+    );
+    assert_eq!(
+        tangent.c.len(),
+        gemm.c.len(),
+        "tangent of C must have the shape of C"
+    );
+
+    let mut dc = vec![0.0; gemm.c.len()];
+    for index in gemm.c_indices() {
+        dc[index] = tangent
+            .beta
+            .mul_add(gemm.c[index], gemm.beta * tangent.c[index]);
+    }
+    unsafe {
+        gemm.call(tangent.alpha, gemm.a, gemm.b, 1.0, &mut dc);
+        gemm.call(gemm.alpha, tangent.a, gemm.b, 1.0, &mut dc);
+        gemm.call(gemm.alpha, gemm.a, tangent.b, 1.0, &mut dc);
+    }
+    dc
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_int;
+
+    use differentiable::check_gradient;
+    use differentiable::finite_diff::FiniteDiff;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Случайный GEMM, как в `main.rs`, но с любой раскладкой,
+    /// транспонированиями и ведущими размерами с запасом. Возвращает аргументы
+    /// и вектор параметров `[alpha, beta, A…, B…, C₀…]`.
+    #[allow(clippy::type_complexity)]
+    fn random_gemm(
+        rng: &mut StdRng,
+        layout: CBLAS_LAYOUT,
+        transa: CBLAS_TRANSPOSE,
+        transb: CBLAS_TRANSPOSE,
+    ) -> ([c_int; 6], Vec<f64>, [usize; 3]) {
+        let m = rng.random_range(1..=4);
+        let n = rng.random_range(1..=4);
+        let k = rng.random_range(1..=4);
+        let shape = |trans, rows, cols| {
+            if trans == CBLAS_TRANSPOSE::NoTrans {
+                (rows, cols)
+            } else {
+                (cols, rows)
+            }
+        };
+        let ld = |(rows, cols): (usize, usize)| {
+            let length = if layout == CBLAS_LAYOUT::RowMajor {
+                cols
+            } else {
+                rows
+            };
+            (length + 1, stored_len(layout, rows, cols, length + 1))
+        };
+        let (lda, a_len) = ld(shape(transa, m, k));
+        let (ldb, b_len) = ld(shape(transb, k, n));
+        let (ldc, c_len) = ld((m, n));
+
+        let mut params = vec![rng.random_range(-2.0..=2.0), rng.random_range(-1.0..=1.0)];
+        params.extend((0..a_len + b_len + c_len).map(|_| rng.random_range(-5.0..=5.0)));
+        let dims = [m, n, k, lda, ldb, ldc].map(|value| value as c_int);
+        (dims, params, [a_len, b_len, c_len])
+    }
+
+    fn gemm_at<'a>(
+        layout: CBLAS_LAYOUT,
+        transa: CBLAS_TRANSPOSE,
+        transb: CBLAS_TRANSPOSE,
+        [m, n, k, lda, ldb, ldc]: [c_int; 6],
+        [a_len, b_len, _]: [usize; 3],
+        params: &'a [f64],
+    ) -> Gemm<'a> {
+        let (a, rest) = params[2..].split_at(a_len);
+        let (b, c) = rest.split_at(b_len);
+        Gemm {
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            alpha: params[0],
+            a,
+            lda,
+            b,
+            ldb,
+            beta: params[1],
+            c,
+            ldc,
+        }
+    }
+
+    fn combinations() -> impl Iterator<Item = (CBLAS_LAYOUT, CBLAS_TRANSPOSE, CBLAS_TRANSPOSE)> {
+        let layouts = [CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor];
+        let transposes = [CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans];
+        layouts.into_iter().flat_map(move |layout| {
+            transposes.into_iter().flat_map(move |transa| {
+                transposes
+                    .into_iter()
+                    .map(move |transb| (layout, transa, transb))
+            })
+        })
+    }
+
+    /// `L = ⟨W, C⟩` со случайными весами `W` (нулевыми на заполнителях).
+    #[test]
+    fn vjp_passes_gradient_check_for_layouts_and_transposes() {
+        let mut rng = StdRng::seed_from_u64(47);
+        for (layout, transa, transb) in combinations() {
+            let (dims, point, lens) = random_gemm(&mut rng, layout, transa, transb);
+            let probe = gemm_at(layout, transa, transb, dims, lens, &point);
+            let mut weights = vec![0.0; lens[2]];
+            for index in probe.c_indices() {
+                weights[index] = rng.random_range(-1.0..=1.0);
+            }
+
+            let loss = |p: &[f64]| {
+                let output = gemm_at(layout, transa, transb, dims, lens, p).forward();
+                output.iter().zip(&weights).map(|(c, w)| c * w).sum()
+            };
+            let gradient = |p: &[f64]| {
+                let g = gemm_vjp(&gemm_at(layout, transa, transb, dims, lens, p), &weights);
+                [vec![g.alpha, g.beta], g.a, g.b, g.c].concat()
+            };
+            let report = check_gradient(loss, gradient, &point);
+            assert!(report.is_ok(), "{layout:?} {transa:?} {transb:?}\n{report}");
+        }
+    }
+
+    #[test]
+    fn jvp_matches_directional_finite_differences_and_vjp() {
+        let mut rng = StdRng::seed_from_u64(48);
+        for (layout, transa, transb) in combinations() {
+            let (dims, point, lens) = random_gemm(&mut rng, layout, transa, transb);
+            let gemm = gemm_at(layout, transa, transb, dims, lens, &point);
+            let direction: Vec<f64> = (0..point.len())
+                .map(|_| rng.random_range(-1.0..=1.0))
+                .collect();
+            let (ta, rest) = direction[2..].split_at(lens[0]);
+            let (tb, tc) = rest.split_at(lens[1]);
+            let tangent = GemmTangent {
+                a: ta,
+                b: tb,
+                alpha: direction[0],
+                beta: direction[1],
+                c: tc,
+            };
+            let jvp = gemm_jvp(&gemm, &tangent);
+            let what = format!("{layout:?} {transa:?} {transb:?}");
+
+            let estimates = FiniteDiff::default().richardson(2).directional(
+                |p| gemm_at(layout, transa, transb, dims, lens, p).forward(),
+                &point,
+                &direction,
+            );
+            for index in gemm.c_indices() {
+                let estimate = estimates[index];
+                assert!(
+                    (jvp[index] - estimate.value).abs()
+                        <= 1e-6f64.mul_add(jvp[index].abs().max(1.0), 2.0 * estimate.error),
+                    "{what}: JVP {} vs FD {} ± {}",
+                    jvp[index],
+                    estimate.value,
+                    estimate.error
+                );
+            }
+
+            // ⟨W, J·v⟩ = ⟨Jᵀ·W, v⟩.
+            let weights: Vec<f64> = (0..lens[2]).map(|_| rng.random_range(-1.0..=1.0)).collect();
+            let g = gemm_vjp(&gemm, &weights);
+            let lhs: f64 = gemm
+                .c_indices()
+                .map(|index| weights[index] * jvp[index])
+                .sum();
+            let rhs: f64 = [vec![g.alpha, g.beta], g.a, g.b, g.c]
+                .concat()
+                .iter()
+                .zip(&direction)
+                .map(|(g, v)| g * v)
+                .sum();
+            assert!(
+                (lhs - rhs).abs() <= 1e-10 * lhs.abs().max(1.0),
+                "{what}: {lhs} vs {rhs}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "GEMM argument 9 is invalid")]
+    fn invalid_arguments_panic_instead_of_reading_out_of_bounds() {
+        let data = [1.0; 4];
+        let mut gemm = Gemm::col_major(2, 2, 2, 1.0, &data, &data, 0.0, &data);
+        gemm.lda = 1;
+        let _ = gemm.forward();
+    }
+}
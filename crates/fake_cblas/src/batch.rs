@@ -0,0 +1,646 @@
+//! Пакетное умножение матриц: много независимых `dgemm` за один вызов.
+//!
+//! Интерфейс повторяет расширения MKL (и аналогичные `cublas*Batched`):
+//! - [`cblas_dgemm_batch_strided`] — матрицы одной формы лежат в буферах с
+//!   постоянным шагом между соседними элементами пакета;
+//! - [`cblas_dgemm_batch`] — массивы указателей, разбитые на группы с общими
+//!   параметрами.
+//!
+//! Элементы пакета распределяются по потокам `rayon`, каждое умножение внутри
+//! идёт последовательно; число потоков задаётся так же, как для остальных ядер
+//! (см. [`crate::threads`]). Пакет из одного элемента считается как обычный
+//! `dgemm` с параллелизмом внутри умножения.
+
+use core::ffi::c_int;
+
+use faer::Par;
+use rayon::prelude::*;
+
+use crate::level3::{check_gemm, gemm};
+use crate::{CBLAS_LAYOUT, CBLAS_TRANSPOSE, dim, par, xerbla};
+
+/// Сырой указатель, который можно передать в потоки `rayon`: элементы пакета
+/// по контракту вызывающей стороны не пересекаются.
+#[derive(Clone, Copy)]
+struct Shared<T>(*mut T);
+
+unsafe impl<T> Send for Shared<T> {}
+unsafe impl<T> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    // Метод, а не поле: замыкание должно захватить всю обёртку, а не `*mut T`.
+    const fn get(self) -> *mut T {
+        self.0
+    }
+}
+
+/// Вызывает `run(index, par)` для каждого элемента пакета из `count`. При
+/// последовательном `par` или одном элементе — по порядку с этим `par`, иначе
+/// элементы делятся между `par.degree()` потоками и считаются с `Par::Seq`.
+fn for_each_entry(count: usize, par: Par, run: impl Fn(usize, Par) + Send + Sync) {
+    let threads = par.degree();
+    if threads <= 1 || count <= 1 {
+        (0..count).for_each(|index| run(index, par));
+    } else {
+        (0..count)
+            .into_par_iter()
+            .with_min_len(count.div_ceil(threads))
+            .for_each(|index| run(index, Par::Seq));
+    }
+}
+
+/// Срез из указателя C; при нулевой длине указатель не читается и может быть
+/// нулевым.
+///
+/// # Safety
+/// При `len > 0` указатель валиден для `len` элементов.
+unsafe fn group_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(ptr, len) }
+    }
+}
+
+/// Номер первого неверного параметра `cblas_dgemm_batch_strided` или `None`.
+///
+/// Шаги `A` и `B` только неотрицательны (нулевой шаг — одна матрица на весь
+/// пакет), а шаг `C` не меньше размера хранимой `C`, чтобы результаты не
+/// перекрывались.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_gemm_batch_strided(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    lda: c_int,
+    stridea: c_int,
+    ldb: c_int,
+    strideb: c_int,
+    ldc: c_int,
+    stridec: c_int,
+    batch_size: c_int,
+) -> Option<c_int> {
+    if let Some(position) = check_gemm(layout, transa, transb, m, n, k, lda, ldb, ldc) {
+        // После `lda` в этой сигнатуре идёт `stridea`, после `ldb` — `strideb`.
+        return Some(match position {
+            11 => 12,
+            14 => 16,
+            position => position,
+        });
+    }
+    let c_len = if m == 0 || n == 0 {
+        0
+    } else if layout == CBLAS_LAYOUT::RowMajor {
+        i64::from(ldc) * i64::from(m)
+    } else {
+        i64::from(ldc) * i64::from(n)
+    };
+    if stridea < 0 {
+        Some(10)
+    } else if strideb < 0 {
+        Some(13)
+    } else if i64::from(stridec) < c_len {
+        Some(17)
+    } else if batch_size < 0 {
+        Some(18)
+    } else {
+        None
+    }
+}
+
+/// Пакет из `batch_size` умножений `C_i := alpha·op(A_i)·op(B_i) + beta·C_i`,
+/// где `A_i = a + i·stridea`, `B_i = b + i·strideb`, `C_i = c + i·stridec`.
+/// Все параметры, кроме адресов, общие; порядок аргументов как в MKL.
+///
+/// Результат совпадает с циклом `cblas_dgemm` по элементам пакета. При
+/// неверном аргументе ни одна `C_i` не меняется; ошибки — см. [`crate::xerbla`].
+///
+/// # Safety
+/// Каждая тройка `A_i`, `B_i`, `C_i` удовлетворяет требованиям
+/// [`cblas_dgemm`](crate::cblas_dgemm); `C_i` не пересекаются ни друг с
+/// другом, ни с `A_j`, `B_j`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_dgemm_batch_strided(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    stridea: c_int,
+    b: *const f64,
+    ldb: c_int,
+    strideb: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+    stridec: c_int,
+    batch_size: c_int,
+) {
+    if let Some(position) = check_gemm_batch_strided(
+        layout, transa, transb, m, n, k, lda, stridea, ldb, strideb, ldc, stridec, batch_size,
+    ) {
+        xerbla("cblas_dgemm_batch_strided", position);
+        return;
+    }
+    let [stridea, strideb, stridec] = [stridea, strideb, stridec].map(dim);
+    let (a, b, c) = (Shared(a.cast_mut()), Shared(b.cast_mut()), Shared(c));
+
+    for_each_entry(dim(batch_size), par(), |index, par| unsafe {
+        gemm(
+            "cblas_dgemm_batch_strided",
+            par,
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            alpha,
+            a.get().add(index * stridea),
+            lda,
+            b.get().add(index * strideb),
+            ldb,
+            beta,
+            c.get().add(index * stridec),
+            ldc,
+        );
+    });
+}
+
+/// Пакет умножений по массивам указателей в групповом формате MKL.
+///
+/// Группа `g` состоит из `group_size[g]` умножений с общими `transa[g]`,
+/// `transb[g]`, `m[g]`, `n[g]`, `k[g]`, `alpha[g]`, `lda[g]`, `ldb[g]`,
+/// `beta[g]` и `ldc[g]`; массивы `a`, `b`, `c` содержат по указателю на каждое
+/// умножение подряд по группам. Все группы проверяются до начала вычислений,
+/// и при неверном параметре ни одна матрица не меняется; для полей группы
+/// сообщается номер массива. Ошибки — см. [`crate::xerbla`].
+///
+/// # Safety
+/// Массивы параметров содержат `group_count` элементов, массивы указателей —
+/// сумму `group_size`. Каждое умножение удовлетворяет требованиям
+/// [`cblas_dgemm`](crate::cblas_dgemm); матрицы `C` не пересекаются ни друг с
+/// другом, ни с `A` и `B`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_dgemm_batch(
+    layout: CBLAS_LAYOUT,
+    transa: *const CBLAS_TRANSPOSE,
+    transb: *const CBLAS_TRANSPOSE,
+    m: *const c_int,
+    n: *const c_int,
+    k: *const c_int,
+    alpha: *const f64,
+    a: *const *const f64,
+    lda: *const c_int,
+    b: *const *const f64,
+    ldb: *const c_int,
+    beta: *const f64,
+    c: *const *mut f64,
+    ldc: *const c_int,
+    group_count: c_int,
+    group_size: *const c_int,
+) {
+    if group_count < 0 {
+        xerbla("cblas_dgemm_batch", 15);
+        return;
+    }
+    let groups = dim(group_count);
+    let (transa, transb) = unsafe { (group_slice(transa, groups), group_slice(transb, groups)) };
+    let (m, n, k) = unsafe {
+        (
+            group_slice(m, groups),
+            group_slice(n, groups),
+            group_slice(k, groups),
+        )
+    };
+    let (lda, ldb, ldc) = unsafe {
+        (
+            group_slice(lda, groups),
+            group_slice(ldb, groups),
+            group_slice(ldc, groups),
+        )
+    };
+    let (alpha, beta) = unsafe { (group_slice(alpha, groups), group_slice(beta, groups)) };
+    let group_size = unsafe { group_slice(group_size, groups) };
+
+    for g in 0..groups {
+        // Номера совпадают с `cblas_dgemm`: массивы стоят на тех же местах.
+        let position = check_gemm(
+            layout, transa[g], transb[g], m[g], n[g], k[g], lda[g], ldb[g], ldc[g],
+        )
+        .or((group_size[g] < 0).then_some(16));
+        if let Some(position) = position {
+            xerbla("cblas_dgemm_batch", position);
+            return;
+        }
+    }
+
+    // Группа каждого умножения в общем порядке массивов указателей.
+    let entries: Vec<usize> = group_size
+        .iter()
+        .enumerate()
+        .flat_map(|(g, &size)| core::iter::repeat_n(g, dim(size)))
+        .collect();
+    let (a, b, c) = unsafe {
+        (
+            group_slice(a, entries.len()),
+            group_slice(b, entries.len()),
+            group_slice(c, entries.len()),
+        )
+    };
+    let (a, b, c): (Vec<_>, Vec<_>, Vec<_>) = (
+        a.iter().map(|&ptr| Shared(ptr.cast_mut())).collect(),
+        b.iter().map(|&ptr| Shared(ptr.cast_mut())).collect(),
+        c.iter().map(|&ptr| Shared(ptr)).collect(),
+    );
+
+    for_each_entry(entries.len(), par(), |index, par| {
+        let g = entries[index];
+        unsafe {
+            gemm(
+                "cblas_dgemm_batch",
+                par,
+                layout,
+                transa[g],
+                transb[g],
+                m[g],
+                n[g],
+                k[g],
+                alpha[g],
+                a[index].get(),
+                lda[g],
+                b[index].get(),
+                ldb[g],
+                beta[g],
+                c[index].get(),
+                ldc[g],
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_int;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use faer::Par;
+
+    use super::*;
+    use crate::xerbla::{UNTOUCHED, assert_rejected};
+    use crate::{cblas_dgemm, min_ld};
+
+    const LAYOUTS: [CBLAS_LAYOUT; 2] = [CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor];
+    const TRANSPOSES: [CBLAS_TRANSPOSE; 2] = [CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans];
+
+    fn filled(len: usize, seed: f64) -> Vec<f64> {
+        (0..len)
+            .map(|i| (i as f64).mul_add(seed, 0.3).sin())
+            .collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        for (i, (x, y)) in actual.iter().zip(expected).enumerate() {
+            assert!((x - y).abs() < 1e-12, "element {i}: {x} vs {y}");
+        }
+    }
+
+    #[test]
+    fn parallel_runner_visits_every_entry_once() {
+        let visits: Vec<AtomicUsize> = (0..37).map(|_| AtomicUsize::new(0)).collect();
+        for par in [Par::Seq, Par::rayon(4)] {
+            for_each_entry(visits.len(), par, |index, inner| {
+                assert!(matches!(inner, Par::Seq));
+                visits[index].fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert!(
+            visits
+                .iter()
+                .all(|count| count.load(Ordering::Relaxed) == 2)
+        );
+    }
+
+    #[test]
+    fn strided_batch_matches_loop_over_dgemm() {
+        let (m, n, k, batch): (c_int, c_int, c_int, usize) = (3, 4, 5, 6);
+        let (alpha, beta) = (1.5, -0.5);
+        for layout in LAYOUTS {
+            for transa in TRANSPOSES {
+                for transb in TRANSPOSES {
+                    // Хранимые размеры A и B; у A ведущий размер с запасом,
+                    // B одна на весь пакет (нулевой шаг), между C есть зазор.
+                    let (a_rows, a_cols) = if transa == CBLAS_TRANSPOSE::NoTrans {
+                        (m, k)
+                    } else {
+                        (k, m)
+                    };
+                    let (b_rows, b_cols) = if transb == CBLAS_TRANSPOSE::NoTrans {
+                        (k, n)
+                    } else {
+                        (n, k)
+                    };
+                    let lda = min_ld(layout, a_rows, a_cols) + 1;
+                    let ldb = min_ld(layout, b_rows, b_cols);
+                    let ldc = min_ld(layout, m, n);
+                    let stridea = dim(lda) * dim(a_rows.max(a_cols));
+                    let strideb = 0;
+                    let stridec = dim(ldc) * dim(m.max(n)) + 3;
+                    let a = filled(stridea * batch, 0.7);
+                    let b = filled(dim(ldb) * dim(b_rows.max(b_cols)), 1.3);
+                    let mut c = filled(stridec * batch, 0.4);
+                    let mut expected = c.clone();
+
+                    for i in 0..batch {
+                        unsafe {
+                            cblas_dgemm(
+                                layout,
+                                transa,
+                                transb,
+                                m,
+                                n,
+                                k,
+                                alpha,
+                                a[i * stridea..].as_ptr(),
+                                lda,
+                                b.as_ptr(),
+                                ldb,
+                                beta,
+                                expected[i * stridec..].as_mut_ptr(),
+                                ldc,
+                            );
+                        }
+                    }
+                    unsafe {
+                        cblas_dgemm_batch_strided(
+                            layout,
+                            transa,
+                            transb,
+                            m,
+                            n,
+                            k,
+                            alpha,
+                            a.as_ptr(),
+                            lda,
+                            c_int::try_from(stridea).unwrap(),
+                            b.as_ptr(),
+                            ldb,
+                            strideb,
+                            beta,
+                            c.as_mut_ptr(),
+                            ldc,
+                            c_int::try_from(stridec).unwrap(),
+                            c_int::try_from(batch).unwrap(),
+                        );
+                    }
+                    assert_close(&c, &expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pointer_batch_matches_loop_over_dgemm() {
+        // Две группы разной формы и с разными операциями.
+        let transa = [CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans];
+        let transb = [CBLAS_TRANSPOSE::Trans, CBLAS_TRANSPOSE::NoTrans];
+        let (m, n, k): ([c_int; 2], [c_int; 2], [c_int; 2]) = ([2, 4], [3, 1], [4, 2]);
+        let (lda, ldb, ldc): ([c_int; 2], [c_int; 2], [c_int; 2]) = ([2, 2], [3, 2], [2, 5]);
+        let (alpha, beta) = ([2.0, -1.0], [0.0, 0.5]);
+        let group_size: [c_int; 2] = [3, 2];
+
+        // Длины буферов A, B, C в каждой группе и группа каждого умножения.
+        let lens = [(8, 12, 6), (8, 2, 5)];
+        let groups = [0, 0, 0, 1, 1];
+        let buffers = |part: fn((usize, usize, usize)) -> usize, seed: f64| -> Vec<Vec<f64>> {
+            groups
+                .iter()
+                .enumerate()
+                .map(|(i, &g)| filled(part(lens[g]), seed + i as f64))
+                .collect()
+        };
+        let a = buffers(|lens| lens.0, 0.3);
+        let b = buffers(|lens| lens.1, 1.1);
+        let mut c = buffers(|lens| lens.2, 0.9);
+        let mut expected = c.clone();
+
+        for (i, &g) in groups.iter().enumerate() {
+            unsafe {
+                cblas_dgemm(
+                    CBLAS_LAYOUT::ColMajor,
+                    transa[g],
+                    transb[g],
+                    m[g],
+                    n[g],
+                    k[g],
+                    alpha[g],
+                    a[i].as_ptr(),
+                    lda[g],
+                    b[i].as_ptr(),
+                    ldb[g],
+                    beta[g],
+                    expected[i].as_mut_ptr(),
+                    ldc[g],
+                );
+            }
+        }
+        let a_ptrs: Vec<*const f64> = a.iter().map(|a| a.as_ptr()).collect();
+        let b_ptrs: Vec<*const f64> = b.iter().map(|b| b.as_ptr()).collect();
+        let c_ptrs: Vec<*mut f64> = c.iter_mut().map(|c| c.as_mut_ptr()).collect();
+        unsafe {
+            cblas_dgemm_batch(
+                CBLAS_LAYOUT::ColMajor,
+                transa.as_ptr(),
+                transb.as_ptr(),
+                m.as_ptr(),
+                n.as_ptr(),
+                k.as_ptr(),
+                alpha.as_ptr(),
+                a_ptrs.as_ptr(),
+                lda.as_ptr(),
+                b_ptrs.as_ptr(),
+                ldb.as_ptr(),
+                beta.as_ptr(),
+                c_ptrs.as_ptr(),
+                ldc.as_ptr(),
+                2,
+                group_size.as_ptr(),
+            );
+        }
+        for (actual, expected) in c.iter().zip(&expected) {
+            assert_close(actual, expected);
+        }
+    }
+
+    #[test]
+    fn invalid_batch_arguments_are_reported_by_position() {
+        let check = |lda, stridea, ldb, strideb, ldc, stridec, batch| {
+            check_gemm_batch_strided(
+                CBLAS_LAYOUT::ColMajor,
+                CBLAS_TRANSPOSE::NoTrans,
+                CBLAS_TRANSPOSE::NoTrans,
+                2,
+                3,
+                4,
+                lda,
+                stridea,
+                ldb,
+                strideb,
+                ldc,
+                stridec,
+                batch,
+            )
+        };
+        assert_eq!(check(2, 8, 4, 12, 2, 6, 5), None);
+        assert_eq!(check(1, 8, 4, 12, 2, 6, 5), Some(9));
+        assert_eq!(check(2, -1, 4, 12, 2, 6, 5), Some(10));
+        assert_eq!(check(2, 8, 3, 12, 2, 6, 5), Some(12));
+        assert_eq!(check(2, 8, 4, -1, 2, 6, 5), Some(13));
+        assert_eq!(check(2, 8, 4, 12, 1, 6, 5), Some(16));
+        assert_eq!(check(2, 8, 4, 12, 2, 5, 5), Some(17));
+        assert_eq!(check(2, 8, 4, 12, 2, 6, -1), Some(18));
+
+        // Ошибка во второй группе не даёт изменить первую.
+        let mut c0 = [1.0; 4];
+        let mut c1 = [1.0; 4];
+        let a = [1.0; 4];
+        let c_ptrs = [c0.as_mut_ptr(), c1.as_mut_ptr()];
+        let a_ptrs = [a.as_ptr(); 2];
+        let trans = [CBLAS_TRANSPOSE::NoTrans; 2];
+        let dims: [c_int; 2] = [2, 2];
+        let lds: [c_int; 2] = [2, 2];
+        let bad_group_size: [c_int; 2] = [1, -1];
+        unsafe {
+            cblas_dgemm_batch(
+                CBLAS_LAYOUT::ColMajor,
+                trans.as_ptr(),
+                trans.as_ptr(),
+                dims.as_ptr(),
+                dims.as_ptr(),
+                dims.as_ptr(),
+                [1.0; 2].as_ptr(),
+                a_ptrs.as_ptr(),
+                lds.as_ptr(),
+                a_ptrs.as_ptr(),
+                lds.as_ptr(),
+                [0.0; 2].as_ptr(),
+                c_ptrs.as_ptr(),
+                lds.as_ptr(),
+                2,
+                bad_group_size.as_ptr(),
+            );
+        }
+        assert_eq!((c0, c1), ([1.0; 4], [1.0; 4]));
+    }
+
+    #[test]
+    fn every_invalid_argument_reaches_xerbla() {
+        let input = [1.0; 16];
+        let (layout, trans) = (CBLAS_LAYOUT, CBLAS_TRANSPOSE);
+
+        // [layout, transa, transb, m, n, k, lda, stridea, ldb, strideb, ldc,
+        //  stridec, batch_size]
+        let strided_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, -1, 6),
+            (6, 1, 9),
+            (7, -1, 10),
+            (8, 1, 12),
+            (9, -1, 13),
+            (10, 1, 16),
+            (11, 3, 17),
+            (12, -1, 18),
+        ];
+        assert_rejected(
+            "cblas_dgemm_batch_strided",
+            [102, 111, 111, 2, 2, 2, 2, 4, 2, 4, 2, 4, 2],
+            &strided_cases,
+            |[l, ta, tb, m, n, k, lda, sa, ldb, sb, ldc, sc, batch]| {
+                let mut c = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dgemm_batch_strided(
+                        layout(l),
+                        trans(ta),
+                        trans(tb),
+                        m,
+                        n,
+                        k,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        sa,
+                        input.as_ptr(),
+                        ldb,
+                        sb,
+                        0.0,
+                        c.as_mut_ptr(),
+                        ldc,
+                        sc,
+                        batch,
+                    );
+                }
+                c
+            },
+        );
+
+        // Одна группа из двух умножений: [layout, transa, transb, m, n, k,
+        // lda, ldb, ldc, group_count, group_size].
+        let pointer_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, -1, 6),
+            (6, 1, 9),
+            (7, 1, 11),
+            (8, 1, 14),
+            (9, -1, 15),
+            (10, -1, 16),
+        ];
+        assert_rejected(
+            "cblas_dgemm_batch",
+            [102, 111, 111, 2, 2, 2, 2, 2, 2, 1, 2],
+            &pointer_cases,
+            |[l, ta, tb, m, n, k, lda, ldb, ldc, groups, size]| {
+                let mut c = vec![UNTOUCHED; 8];
+                let inputs = [input.as_ptr(); 2];
+                let outputs = [c.as_mut_ptr(), c[4..].as_mut_ptr()];
+                unsafe {
+                    cblas_dgemm_batch(
+                        layout(l),
+                        &trans(ta),
+                        &trans(tb),
+                        &m,
+                        &n,
+                        &k,
+                        &1.0,
+                        inputs.as_ptr(),
+                        &lda,
+                        inputs.as_ptr(),
+                        &ldb,
+                        &0.0,
+                        outputs.as_ptr(),
+                        &ldc,
+                        groups,
+                        &size,
+                    );
+                }
+                c
+            },
+        );
+    }
+}
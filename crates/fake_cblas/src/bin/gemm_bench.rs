@@ -0,0 +1,113 @@
+//! Пропускная способность `cblas_dgemm` в зависимости от размера и числа
+//! потоков в сравнении с наивным тройным циклом.
+//!
+//! ```bash
+//! cargo run -p fake_cblas --release --bin gemm_bench [размеры...]
+//! ```
+
+use core::ffi::c_int;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fake_cblas::{CBLAS_LAYOUT, CBLAS_TRANSPOSE, cblas_dgemm, fake_cblas_set_num_threads};
+
+/// Размеры по умолчанию, если они не заданы аргументами.
+const SIZES: [usize; 5] = [64, 128, 256, 512, 1024];
+/// Числа потоков; `0` — все потоки пула.
+const THREADS: [c_int; 4] = [1, 2, 4, 0];
+/// Наивный цикл дольше этого размера не запускается.
+const NAIVE_LIMIT: usize = 512;
+/// Минимальное суммарное время замеров одной конфигурации.
+const BUDGET: Duration = Duration::from_millis(300);
+
+/// Лучшее время одного запуска `run` среди повторов в пределах [`BUDGET`].
+fn best_time(mut run: impl FnMut()) -> Duration {
+    let started = Instant::now();
+    let mut best = Duration::MAX;
+    while started.elapsed() < BUDGET || best == Duration::MAX {
+        let start = Instant::now();
+        run();
+        best = best.min(start.elapsed());
+    }
+    best
+}
+
+fn gflops(n: usize, time: Duration) -> f64 {
+    2.0 * (n as f64).powi(3) / time.as_secs_f64() / 1e9
+}
+
+/// `C := A·B` тройным циклом в `ColMajor`.
+fn naive_gemm(n: usize, a: &[f64], b: &[f64], c: &mut [f64]) {
+    for col in 0..n {
+        for row in 0..n {
+            let mut accum = 0.0;
+            for p in 0..n {
+                accum += a[row + p * n] * b[p + col * n];
+            }
+            c[row + col * n] = accum;
+        }
+    }
+}
+
+fn main() {
+    let sizes: Vec<usize> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("sizes must be positive integers"))
+        .collect();
+    let sizes = if sizes.is_empty() {
+        SIZES.to_vec()
+    } else {
+        sizes
+    };
+
+    print!("{:>6} {:>10}", "n", "naive");
+    for threads in THREADS {
+        let label = if threads == 0 {
+            "все".to_owned()
+        } else {
+            threads.to_string()
+        };
+        print!(" {:>10}", format!("faer×{label}"));
+    }
+    println!("   (GFLOP/s)");
+
+    for n in sizes {
+        let a: Vec<f64> = (0..n * n).map(|i| (i as f64).sin()).collect();
+        let b: Vec<f64> = (0..n * n).map(|i| (i as f64).cos()).collect();
+        let mut c = vec![0.0; n * n];
+
+        print!("{n:>6}");
+        if n <= NAIVE_LIMIT {
+            let time = best_time(|| naive_gemm(n, black_box(&a), black_box(&b), &mut c));
+            print!(" {:>10.2}", gflops(n, time));
+        } else {
+            print!(" {:>10}", "—");
+        }
+
+        let size = c_int::try_from(n).expect("size fits in c_int");
+        for threads in THREADS {
+            fake_cblas_set_num_threads(threads);
+            let time = best_time(|| unsafe {
+                cblas_dgemm(
+                    CBLAS_LAYOUT::ColMajor,
+                    CBLAS_TRANSPOSE::NoTrans,
+                    CBLAS_TRANSPOSE::NoTrans,
+                    size,
+                    size,
+                    size,
+                    1.0,
+                    black_box(a.as_ptr()),
+                    size,
+                    black_box(b.as_ptr()),
+                    size,
+                    0.0,
+                    c.as_mut_ptr(),
+                    size,
+                );
+            });
+            print!(" {:>10.2}", gflops(n, time));
+        }
+        println!();
+        black_box(&c);
+    }
+}
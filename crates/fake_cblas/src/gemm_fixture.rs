@@ -0,0 +1,31 @@
+//! Общая задача для проверок градиента GEMM в тестах библиотеки и
+//! демонстрации `main.rs`: `L = sum(C)` как функция от
+//! `[alpha, beta, A..., B...]` при фиксированной `C₀` (`ColMajor`, без
+//! транспонирования, `A` — `M × K`, `B` — `K × N`).
+
+pub(crate) const M: usize = 3;
+pub(crate) const N: usize = 2;
+pub(crate) const K: usize = 4;
+
+/// Исходная `C₀`.
+pub(crate) fn c0() -> Vec<f64> {
+    (0..M * N).map(|i| (i as f64).mul_add(0.5, -1.0)).collect()
+}
+
+/// Точка, в которой проверяется градиент.
+pub(crate) fn point() -> Vec<f64> {
+    (0..2 + M * K + K * N)
+        .map(|i| ((i as f64) * 0.7).sin())
+        .collect()
+}
+
+/// Разбирает вектор параметров на `(alpha, beta, A, B)`.
+pub(crate) fn split(p: &[f64]) -> (f64, f64, &[f64], &[f64]) {
+    let (a, b) = p[2..].split_at(M * K);
+    (p[0], p[1], a, b)
+}
+
+/// Собирает градиент в том же порядке, что и параметры.
+pub(crate) fn pack(alpha: f64, beta: f64, a: &[f64], b: &[f64]) -> Vec<f64> {
+    [&[alpha, beta], a, b].concat()
+}
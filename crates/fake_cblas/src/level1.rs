@@ -0,0 +1,463 @@
+//! BLAS уровня 1: операции вектор–вектор для `f64` (`d*`) и `f32` (`s*`).
+//!
+//! Вектор из `n` элементов с шагом `inc` занимает `1 + (n - 1)·|inc|` элементов
+//! буфера. При отрицательном шаге элементы обходятся с конца: `i`-й элемент
+//! лежит по смещению `(n - 1 - i)·|inc|`, как в эталонном BLAS. Шаг `0` для
+//! двухвекторных операций означает повторение первого элемента. Одновекторные
+//! `scal`, `nrm2`, `asum` и `i*amax`, как и эталон, при `inc <= 0` ничего не
+//! делают и возвращают ноль.
+
+use core::ffi::c_int;
+
+/// Смещение `i`-го элемента вектора длины `n` с шагом `inc`.
+#[inline]
+pub(crate) const fn offset(i: usize, n: usize, inc: c_int) -> usize {
+    let step = inc.unsigned_abs() as usize;
+    if inc < 0 {
+        (n - 1 - i) * step
+    } else {
+        i * step
+    }
+}
+
+/// Длина вектора; неположительная означает пустой вектор.
+#[inline]
+pub(crate) const fn len(n: c_int) -> usize {
+    if n > 0 { n.unsigned_abs() as usize } else { 0 }
+}
+
+macro_rules! level1 {
+    (
+        $t:ty,
+        $dot:ident,
+        $axpy:ident,
+        $scal:ident,
+        $copy:ident,
+        $swap:ident,
+        $nrm2:ident,
+        $asum:ident,
+        $iamax:ident,
+        $rot:ident,
+        $rotg:ident
+    ) => {
+        #[doc = concat!("`xᵀy` для `", stringify!($t), "`; сумма накапливается в той же точности.")]
+        ///
+        /// # Safety
+        /// `x` и `y` указывают на векторы длины `n` с шагами `inc_x`, `inc_y`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $dot(
+            n: c_int,
+            x: *const $t,
+            inc_x: c_int,
+            y: *const $t,
+            inc_y: c_int,
+        ) -> $t {
+            let n = len(n);
+            (0..n)
+                .map(|i| unsafe { *x.add(offset(i, n, inc_x)) * *y.add(offset(i, n, inc_y)) })
+                .fold(0.0, |sum, term| sum + term)
+        }
+
+        #[doc = concat!("`y := alpha·x + y` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` и `y` указывают на векторы длины `n`, `y` не пересекается с `x`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::float_cmp)]
+        pub unsafe extern "C" fn $axpy(
+            n: c_int,
+            alpha: $t,
+            x: *const $t,
+            inc_x: c_int,
+            y: *mut $t,
+            inc_y: c_int,
+        ) {
+            if alpha == 0.0 {
+                return;
+            }
+            let n = len(n);
+            for i in 0..n {
+                unsafe { *y.add(offset(i, n, inc_y)) += alpha * *x.add(offset(i, n, inc_x)) };
+            }
+        }
+
+        #[doc = concat!("`x := alpha·x` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` указывает на вектор длины `n` с шагом `inc_x`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $scal(n: c_int, alpha: $t, x: *mut $t, inc_x: c_int) {
+            if inc_x <= 0 {
+                return;
+            }
+            let n = len(n);
+            for i in 0..n {
+                unsafe { *x.add(offset(i, n, inc_x)) *= alpha };
+            }
+        }
+
+        #[doc = concat!("`y := x` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` и `y` указывают на непересекающиеся векторы длины `n`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $copy(
+            n: c_int,
+            x: *const $t,
+            inc_x: c_int,
+            y: *mut $t,
+            inc_y: c_int,
+        ) {
+            let n = len(n);
+            for i in 0..n {
+                unsafe { *y.add(offset(i, n, inc_y)) = *x.add(offset(i, n, inc_x)) };
+            }
+        }
+
+        #[doc = concat!("Обмен `x ↔ y` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` и `y` указывают на непересекающиеся векторы длины `n`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $swap(
+            n: c_int,
+            x: *mut $t,
+            inc_x: c_int,
+            y: *mut $t,
+            inc_y: c_int,
+        ) {
+            let n = len(n);
+            for i in 0..n {
+                unsafe { std::ptr::swap(x.add(offset(i, n, inc_x)), y.add(offset(i, n, inc_y))) };
+            }
+        }
+
+        #[doc = concat!("Евклидова норма `‖x‖₂` для `", stringify!($t), "`.")]
+        ///
+        /// Считается через масштаб и сумму квадратов, поэтому не переполняется
+        /// и не теряет точность для очень больших и очень малых элементов.
+        ///
+        /// # Safety
+        /// `x` указывает на вектор длины `n` с шагом `inc_x`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $nrm2(n: c_int, x: *const $t, inc_x: c_int) -> $t {
+            if inc_x <= 0 {
+                return 0.0;
+            }
+            let n = len(n);
+            let (mut scale, mut ssq): ($t, $t) = (0.0, 1.0);
+            for i in 0..n {
+                let value = unsafe { *x.add(offset(i, n, inc_x)) }.abs();
+                if value == 0.0 {
+                    continue;
+                }
+                if scale < value {
+                    ssq = (scale / value).powi(2).mul_add(ssq, 1.0);
+                    scale = value;
+                } else {
+                    ssq += (value / scale).powi(2);
+                }
+            }
+            scale * ssq.sqrt()
+        }
+
+        #[doc = concat!("Сумма модулей `Σ|xᵢ|` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` указывает на вектор длины `n` с шагом `inc_x`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $asum(n: c_int, x: *const $t, inc_x: c_int) -> $t {
+            if inc_x <= 0 {
+                return 0.0;
+            }
+            let n = len(n);
+            (0..n)
+                .map(|i| unsafe { *x.add(offset(i, n, inc_x)) }.abs())
+                .fold(0.0, |sum, term| sum + term)
+        }
+
+        #[doc = concat!("Индекс (с нуля) первого элемента с наибольшим `|xᵢ|` для `", stringify!($t), "`.")]
+        ///
+        /// Для пустого вектора или `inc_x <= 0` возвращает `0`, как эталонный CBLAS.
+        ///
+        /// # Safety
+        /// `x` указывает на вектор длины `n` с шагом `inc_x`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $iamax(n: c_int, x: *const $t, inc_x: c_int) -> usize {
+            if inc_x <= 0 {
+                return 0;
+            }
+            let n = len(n);
+            let mut best = (0, <$t>::NEG_INFINITY);
+            for i in 0..n {
+                let value = unsafe { *x.add(offset(i, n, inc_x)) }.abs();
+                if value > best.1 {
+                    best = (i, value);
+                }
+            }
+            best.0
+        }
+
+        #[doc = concat!("Плоский поворот `(x, y) := (c·x + s·y, c·y - s·x)` для `", stringify!($t), "`.")]
+        ///
+        /// # Safety
+        /// `x` и `y` указывают на непересекающиеся векторы длины `n`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $rot(
+            n: c_int,
+            x: *mut $t,
+            inc_x: c_int,
+            y: *mut $t,
+            inc_y: c_int,
+            c: $t,
+            s: $t,
+        ) {
+            let n = len(n);
+            for i in 0..n {
+                unsafe {
+                    let x = &mut *x.add(offset(i, n, inc_x));
+                    let y = &mut *y.add(offset(i, n, inc_y));
+                    (*x, *y) = (c.mul_add(*x, s * *y), c.mul_add(*y, -s * *x));
+                }
+            }
+        }
+
+        #[doc = concat!("Построение поворота Гивенса для `", stringify!($t), "`.")]
+        ///
+        /// По `(a, b)` находит `c`, `s` и `r` с `c·a + s·b = r`, `-s·a + c·b = 0`,
+        /// `c² + s² = 1`. На выходе `a := r`, `b := z` — компактная запись
+        /// поворота, из которой эталонный BLAS восстанавливает `c` и `s`.
+        ///
+        /// # Safety
+        /// Все четыре указателя валидны и не пересекаются.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $rotg(a: *mut $t, b: *mut $t, c: *mut $t, s: *mut $t) {
+            unsafe {
+                let (x, y) = (*a, *b);
+                let roe = if x.abs() > y.abs() { x } else { y };
+                let scale = x.abs() + y.abs();
+                let (cos, sin, r, z);
+                if scale == 0.0 {
+                    (cos, sin, r, z) = (1.0, 0.0, 0.0, 0.0);
+                } else {
+                    r = (scale * (x / scale).hypot(y / scale)).copysign(roe);
+                    cos = x / r;
+                    sin = y / r;
+                    z = if x.abs() > y.abs() {
+                        sin
+                    } else if cos != 0.0 {
+                        1.0 / cos
+                    } else {
+                        1.0
+                    };
+                }
+                (*a, *b, *c, *s) = (r, z, cos, sin);
+            }
+        }
+    };
+}
+
+level1!(
+    f64,
+    cblas_ddot,
+    cblas_daxpy,
+    cblas_dscal,
+    cblas_dcopy,
+    cblas_dswap,
+    cblas_dnrm2,
+    cblas_dasum,
+    cblas_idamax,
+    cblas_drot,
+    cblas_drotg
+);
+
+level1!(
+    f32,
+    cblas_sdot,
+    cblas_saxpy,
+    cblas_sscal,
+    cblas_scopy,
+    cblas_sswap,
+    cblas_snrm2,
+    cblas_sasum,
+    cblas_isamax,
+    cblas_srot,
+    cblas_srotg
+);
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_int;
+
+    use super::*;
+
+    /// Логический вектор из буфера — наивное определение шага без `offset`.
+    fn gather(buffer: &[f64], n: usize, inc: c_int) -> Vec<f64> {
+        let mut values: Vec<f64> = buffer
+            .iter()
+            .step_by(inc.unsigned_abs() as usize)
+            .take(n)
+            .copied()
+            .collect();
+        if inc < 0 {
+            values.reverse();
+        }
+        values
+    }
+
+    fn buffer(n: usize, inc: c_int, seed: f64) -> Vec<f64> {
+        let len = 1 + (n - 1) * inc.unsigned_abs() as usize;
+        (0..len)
+            .map(|i| (i as f64).mul_add(seed, 0.4).sin() * 3.0)
+            .collect()
+    }
+
+    const N: usize = 5;
+    const INCREMENTS: [(c_int, c_int); 4] = [(1, 1), (2, -1), (-3, 2), (-1, -2)];
+
+    #[test]
+    fn dot_axpy_copy_swap_rot_match_naive_for_all_increment_signs() {
+        let n = N as c_int;
+        for (inc_x, inc_y) in INCREMENTS {
+            let x = buffer(N, inc_x, 0.9);
+            let y = buffer(N, inc_y, 1.7);
+            let (xs, ys) = (gather(&x, N, inc_x), gather(&y, N, inc_y));
+
+            let dot = unsafe { cblas_ddot(n, x.as_ptr(), inc_x, y.as_ptr(), inc_y) };
+            let expected: f64 = xs.iter().zip(&ys).map(|(a, b)| a * b).sum();
+            assert!((dot - expected).abs() < 1e-12, "dot {inc_x} {inc_y}");
+
+            let mut out = y.clone();
+            unsafe { cblas_daxpy(n, -0.5, x.as_ptr(), inc_x, out.as_mut_ptr(), inc_y) };
+            for (i, value) in gather(&out, N, inc_y).iter().enumerate() {
+                assert!((value - (-0.5f64).mul_add(xs[i], ys[i])).abs() < 1e-12);
+            }
+
+            let mut out = vec![0.0; y.len()];
+            unsafe { cblas_dcopy(n, x.as_ptr(), inc_x, out.as_mut_ptr(), inc_y) };
+            assert_eq!(gather(&out, N, inc_y), xs);
+
+            let (mut sx, mut sy) = (x.clone(), y.clone());
+            unsafe { cblas_dswap(n, sx.as_mut_ptr(), inc_x, sy.as_mut_ptr(), inc_y) };
+            assert_eq!(
+                (gather(&sx, N, inc_x), gather(&sy, N, inc_y)),
+                (ys.clone(), xs.clone())
+            );
+
+            let (c, s) = (0.6, 0.8);
+            let (mut rx, mut ry) = (x.clone(), y.clone());
+            unsafe { cblas_drot(n, rx.as_mut_ptr(), inc_x, ry.as_mut_ptr(), inc_y, c, s) };
+            let (rxs, rys) = (gather(&rx, N, inc_x), gather(&ry, N, inc_y));
+            for i in 0..N {
+                assert!((rxs[i] - (c * xs[i] + s * ys[i])).abs() < 1e-12);
+                assert!((rys[i] - (c * ys[i] - s * xs[i])).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn negative_increment_walks_from_the_end() {
+        // y в обратном порядке: [6, 5, 4].
+        let x = [1.0, 2.0, 3.0];
+        let y = [4.0, -1.0, 5.0, -1.0, 6.0];
+        assert_eq!(
+            unsafe { cblas_ddot(3, x.as_ptr(), 1, y.as_ptr(), -2) },
+            28.0
+        );
+    }
+
+    #[test]
+    fn single_vector_routines_match_naive() {
+        let n = N as c_int;
+        let x = buffer(N, 2, 0.9);
+        let xs = gather(&x, N, 2);
+
+        let norm = unsafe { cblas_dnrm2(n, x.as_ptr(), 2) };
+        assert!((norm - xs.iter().map(|v| v * v).sum::<f64>().sqrt()).abs() < 1e-12);
+        let asum = unsafe { cblas_dasum(n, x.as_ptr(), 2) };
+        assert!((asum - xs.iter().map(|v| v.abs()).sum::<f64>()).abs() < 1e-12);
+        let index = unsafe { cblas_idamax(n, x.as_ptr(), 2) };
+        let expected = (0..N).fold(0, |best, i| {
+            if xs[i].abs() > xs[best].abs() {
+                i
+            } else {
+                best
+            }
+        });
+        assert_eq!(index, expected);
+
+        let mut scaled = x.clone();
+        unsafe { cblas_dscal(n, 2.5, scaled.as_mut_ptr(), 2) };
+        for (value, original) in gather(&scaled, N, 2).iter().zip(&xs) {
+            assert_eq!(*value, 2.5 * original);
+        }
+        // Элементы между шагами не трогаются.
+        assert_eq!(scaled[1], x[1]);
+
+        // Неположительный шаг: ничего не делается.
+        assert_eq!(unsafe { cblas_dnrm2(n, x.as_ptr(), -1) }, 0.0);
+        assert_eq!(unsafe { cblas_idamax(0, x.as_ptr(), 1) }, 0);
+    }
+
+    #[test]
+    fn nrm2_does_not_overflow_or_underflow() {
+        let big = [3e300, 4e300];
+        assert!((unsafe { cblas_dnrm2(2, big.as_ptr(), 1) } / 5e300 - 1.0).abs() < 1e-15);
+        let tiny = [3e-300, 4e-300];
+        assert!((unsafe { cblas_dnrm2(2, tiny.as_ptr(), 1) } / 5e-300 - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn rotg_zeroes_second_component() {
+        for (a0, b0) in [(3.0, 4.0), (-5.0, 2.0), (0.0, -2.0), (1.0, 0.0), (0.0, 0.0)] {
+            let (mut a, mut b, mut c, mut s) = (a0, b0, 0.0, 0.0);
+            unsafe { cblas_drotg(&mut a, &mut b, &mut c, &mut s) };
+            assert!((c.mul_add(a0, s * b0) - a).abs() < 1e-12);
+            assert!(c.mul_add(b0, -s * a0).abs() < 1e-12);
+            assert!((c.mul_add(c, s * s) - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn single_precision_variants_match_double() {
+        let x64 = buffer(N, -2, 0.9);
+        let y64 = buffer(N, 1, 1.7);
+        let x: Vec<f32> = x64.iter().map(|&v| v as f32).collect();
+        let mut y: Vec<f32> = y64.iter().map(|&v| v as f32).collect();
+        let n = N as c_int;
+        unsafe {
+            let dot = cblas_sdot(n, x.as_ptr(), -2, y.as_ptr(), 1);
+            assert!(
+                (f64::from(dot) - cblas_ddot(n, x64.as_ptr(), -2, y64.as_ptr(), 1)).abs() < 1e-4
+            );
+            assert!(
+                (f64::from(cblas_snrm2(n, x.as_ptr(), 2)) - cblas_dnrm2(n, x64.as_ptr(), 2)).abs()
+                    < 1e-5
+            );
+            assert!(
+                (f64::from(cblas_sasum(n, x.as_ptr(), 2)) - cblas_dasum(n, x64.as_ptr(), 2)).abs()
+                    < 1e-5
+            );
+            assert_eq!(
+                cblas_isamax(n, x.as_ptr(), 2),
+                cblas_idamax(n, x64.as_ptr(), 2)
+            );
+
+            cblas_saxpy(n, 2.0, x.as_ptr(), -2, y.as_mut_ptr(), 1);
+            cblas_sscal(n, 0.5, y.as_mut_ptr(), 1);
+            let mut y_copy = vec![0.0_f32; N];
+            cblas_scopy(n, y.as_ptr(), 1, y_copy.as_mut_ptr(), 1);
+            cblas_sswap(n, y.as_mut_ptr(), 1, y_copy.as_mut_ptr(), 1);
+            cblas_srot(n, y.as_mut_ptr(), 1, y_copy.as_mut_ptr(), 1, 1.0, 0.0);
+            let xs = gather(&x64, N, -2);
+            for i in 0..N {
+                let expected = 2.0f64.mul_add(xs[i], y64[i]) * 0.5;
+                assert!((f64::from(y[i]) - expected).abs() < 1e-5);
+            }
+
+            let (mut a, mut b, mut c, mut s) = (3.0_f32, 4.0_f32, 0.0, 0.0);
+            cblas_srotg(&mut a, &mut b, &mut c, &mut s);
+            assert!((a - 5.0).abs() < 1e-6 && (c - 0.6).abs() < 1e-6 && (s - 0.8).abs() < 1e-6);
+        }
+    }
+}
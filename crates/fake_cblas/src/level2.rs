@@ -0,0 +1,1116 @@
+//! BLAS уровня 2: операции матрица–вектор для `f64` (`d*`) и `f32` (`s*`).
+//!
+//! Матрицы задаются раскладкой и ведущим размером, как в уровне 3, векторы —
+//! длиной и шагом, как в уровне 1 (отрицательный шаг обходит буфер с конца).
+//! Нулевой шаг вектора — ошибка аргумента, как в эталонном BLAS. Вычисления
+//! выполняет `faer`; симметричная и треугольные матрицы читаются только из
+//! хранимого треугольника.
+
+use core::ffi::c_int;
+
+use faer::linalg::matmul::matmul;
+use faer::linalg::matmul::triangular::{self, BlockStructure};
+use faer::prelude::ReborrowMut;
+use faer::{Accum, Mat};
+
+use crate::{
+    CBLAS_DIAG, CBLAS_LAYOUT, CBLAS_TRANSPOSE, CBLAS_UPLO, Real, dim, matmut, matref, min_ld, par,
+    scale_matrix, solve_triangular, triangle, vecmut, vecref, xerbla,
+};
+
+/// Номер первого неверного параметра `cblas_?gemv` или `None`.
+pub(crate) fn check_gemv(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    lda: c_int,
+    inc_x: c_int,
+    inc_y: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        Some(1)
+    } else if trans.is_transposed().is_none() {
+        Some(2)
+    } else if m < 0 {
+        Some(3)
+    } else if n < 0 {
+        Some(4)
+    } else if lda < min_ld(layout, m, n) {
+        Some(7)
+    } else if inc_x == 0 {
+        Some(9)
+    } else if inc_y == 0 {
+        Some(12)
+    } else {
+        None
+    }
+}
+
+/// Номер первого неверного параметра `cblas_?ger` или `None`.
+pub(crate) fn check_ger(
+    layout: CBLAS_LAYOUT,
+    m: c_int,
+    n: c_int,
+    inc_x: c_int,
+    inc_y: c_int,
+    lda: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        Some(1)
+    } else if m < 0 {
+        Some(2)
+    } else if n < 0 {
+        Some(3)
+    } else if inc_x == 0 {
+        Some(6)
+    } else if inc_y == 0 {
+        Some(8)
+    } else if lda < min_ld(layout, m, n) {
+        Some(10)
+    } else {
+        None
+    }
+}
+
+/// Номер первого неверного параметра `cblas_?symv` или `None`.
+pub(crate) fn check_symv(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    n: c_int,
+    lda: c_int,
+    inc_x: c_int,
+    inc_y: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        Some(1)
+    } else if uplo.is_lower().is_none() {
+        Some(2)
+    } else if n < 0 {
+        Some(3)
+    } else if lda < n.max(1) {
+        Some(6)
+    } else if inc_x == 0 {
+        Some(8)
+    } else if inc_y == 0 {
+        Some(11)
+    } else {
+        None
+    }
+}
+
+/// Номер первого неверного параметра `cblas_?trmv` и `cblas_?trsv` (у них
+/// одинаковые сигнатуры) или `None`.
+pub(crate) fn check_triangular(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    n: c_int,
+    lda: c_int,
+    inc_x: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        Some(1)
+    } else if uplo.is_lower().is_none() {
+        Some(2)
+    } else if trans.is_transposed().is_none() {
+        Some(3)
+    } else if diag.is_unit().is_none() {
+        Some(4)
+    } else if n < 0 {
+        Some(5)
+    } else if lda < n.max(1) {
+        Some(7)
+    } else if inc_x == 0 {
+        Some(9)
+    } else {
+        None
+    }
+}
+
+/// `y := alpha·op(A)·x + beta·y`, `A` размера `m × n`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn gemv<T: Real>(
+    routine: &str,
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    alpha: T,
+    a: *const T,
+    lda: c_int,
+    x: *const T,
+    inc_x: c_int,
+    beta: T,
+    y: *mut T,
+    inc_y: c_int,
+) {
+    if let Some(position) = check_gemv(layout, trans, m, n, lda, inc_x, inc_y) {
+        xerbla(routine, position);
+        return;
+    }
+    let [m, n, lda] = [m, n, lda].map(dim);
+    if m == 0 || n == 0 || (alpha == T::ZERO && beta == T::ONE) {
+        return;
+    }
+
+    let a_view = unsafe { matref(a, layout, m, n, lda) };
+    let a_view = if trans != CBLAS_TRANSPOSE::NoTrans {
+        a_view.transpose()
+    } else {
+        a_view
+    };
+    let mut y_view = unsafe { vecmut(y, a_view.nrows(), inc_y) };
+    scale_matrix(y_view.rb_mut(), beta);
+    if alpha == T::ZERO {
+        return;
+    }
+    let x_view = unsafe { vecref(x, a_view.ncols(), inc_x) };
+    matmul(y_view, Accum::Add, a_view, x_view, alpha, par());
+}
+
+/// `A := alpha·x·yᵀ + A`, `A` размера `m × n`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn ger<T: Real>(
+    routine: &str,
+    layout: CBLAS_LAYOUT,
+    m: c_int,
+    n: c_int,
+    alpha: T,
+    x: *const T,
+    inc_x: c_int,
+    y: *const T,
+    inc_y: c_int,
+    a: *mut T,
+    lda: c_int,
+) {
+    if let Some(position) = check_ger(layout, m, n, inc_x, inc_y, lda) {
+        xerbla(routine, position);
+        return;
+    }
+    let [m, n, lda] = [m, n, lda].map(dim);
+    if m == 0 || n == 0 || alpha == T::ZERO {
+        return;
+    }
+
+    let a_view = unsafe { matmut(a, layout, m, n, lda) };
+    let x_view = unsafe { vecref(x, m, inc_x) };
+    let y_view = unsafe { vecref(y, n, inc_y) };
+    matmul(a_view, Accum::Add, x_view, y_view.transpose(), alpha, par());
+}
+
+/// `y := alpha·A·x + beta·y` для симметричной `A`, хранимой треугольником `uplo`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn symv<T: Real>(
+    routine: &str,
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    n: c_int,
+    alpha: T,
+    a: *const T,
+    lda: c_int,
+    x: *const T,
+    inc_x: c_int,
+    beta: T,
+    y: *mut T,
+    inc_y: c_int,
+) {
+    if let Some(position) = check_symv(layout, uplo, n, lda, inc_x, inc_y) {
+        xerbla(routine, position);
+        return;
+    }
+    let [n, lda] = [n, lda].map(dim);
+    if n == 0 || (alpha == T::ZERO && beta == T::ONE) {
+        return;
+    }
+
+    let mut y_view = unsafe { vecmut(y, n, inc_y) };
+    scale_matrix(y_view.rb_mut(), beta);
+    if alpha == T::ZERO {
+        return;
+    }
+
+    // Хранимый треугольник, приведённый к нижнему: A = L + strict(L)ᵀ.
+    let a_view = unsafe { matref(a, layout, n, n, lda) };
+    let lower = if uplo == CBLAS_UPLO::Lower {
+        a_view
+    } else {
+        a_view.transpose()
+    };
+    let x_view = unsafe { vecref(x, n, inc_x) };
+    triangular::matmul(
+        y_view.rb_mut(),
+        BlockStructure::Rectangular,
+        Accum::Add,
+        lower,
+        BlockStructure::TriangularLower,
+        x_view,
+        BlockStructure::Rectangular,
+        alpha,
+        par(),
+    );
+    triangular::matmul(
+        y_view,
+        BlockStructure::Rectangular,
+        Accum::Add,
+        lower.transpose(),
+        BlockStructure::StrictTriangularUpper,
+        x_view,
+        BlockStructure::Rectangular,
+        alpha,
+        par(),
+    );
+}
+
+/// `x := op(A)·x` для треугольной `A`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn trmv<T: Real>(
+    routine: &str,
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    n: c_int,
+    a: *const T,
+    lda: c_int,
+    x: *mut T,
+    inc_x: c_int,
+) {
+    if let Some(position) = check_triangular(layout, uplo, trans, diag, n, lda, inc_x) {
+        xerbla(routine, position);
+        return;
+    }
+    let [n, lda] = [n, lda].map(dim);
+    if n == 0 {
+        return;
+    }
+
+    let a_view = unsafe { matref(a, layout, n, n, lda) };
+    let structure = triangle(uplo == CBLAS_UPLO::Lower, diag == CBLAS_DIAG::Unit);
+    let (a_view, structure) = if trans != CBLAS_TRANSPOSE::NoTrans {
+        (a_view.transpose(), structure.transpose())
+    } else {
+        (a_view, structure)
+    };
+    // `faer` не умножает на месте, поэтому исходный `x` копируется.
+    let mut x_view = unsafe { vecmut(x, n, inc_x) };
+    let mut input = Mat::<T>::zeros(n, 1);
+    input.copy_from(x_view.as_ref());
+    triangular::matmul(
+        x_view.rb_mut(),
+        BlockStructure::Rectangular,
+        Accum::Replace,
+        a_view,
+        structure,
+        &input,
+        BlockStructure::Rectangular,
+        T::ONE,
+        par(),
+    );
+}
+
+/// Решает `op(A)·z = x` для треугольной `A` и записывает `z` в `x`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn trsv<T: Real>(
+    routine: &str,
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    n: c_int,
+    a: *const T,
+    lda: c_int,
+    x: *mut T,
+    inc_x: c_int,
+) {
+    if let Some(position) = check_triangular(layout, uplo, trans, diag, n, lda, inc_x) {
+        xerbla(routine, position);
+        return;
+    }
+    let [n, lda] = [n, lda].map(dim);
+    if n == 0 {
+        return;
+    }
+
+    let a_view = unsafe { matref(a, layout, n, n, lda) };
+    let transposed = trans != CBLAS_TRANSPOSE::NoTrans;
+    let a_view = if transposed {
+        a_view.transpose()
+    } else {
+        a_view
+    };
+    // Транспонирование переводит нижний треугольник в верхний.
+    let lower = (uplo == CBLAS_UPLO::Lower) != transposed;
+    let x_view = unsafe { vecmut(x, n, inc_x) };
+    solve_triangular(a_view, x_view, lower, diag == CBLAS_DIAG::Unit);
+}
+
+macro_rules! level2 {
+    ($t:ty, $gemv:ident, $ger:ident, $symv:ident, $trmv:ident, $trsv:ident) => {
+        #[doc = concat!("`y := alpha·op(A)·x + beta·y` для `", stringify!($t), "`.")]
+        ///
+        /// `A` хранится как `m × n` с ведущим размером `lda`; при `NoTrans` длина
+        /// `x` равна `n`, а `y` — `m`, при `Trans` наоборот. При `beta == 0`
+        /// старое содержимое `y` не читается.
+        ///
+        /// # Safety
+        /// Указатели покрывают матрицу и векторы указанных размеров, `y` не
+        /// пересекается с `A` и `x`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $gemv(
+            layout: CBLAS_LAYOUT,
+            trans: CBLAS_TRANSPOSE,
+            m: c_int,
+            n: c_int,
+            alpha: $t,
+            a: *const $t,
+            lda: c_int,
+            x: *const $t,
+            inc_x: c_int,
+            beta: $t,
+            y: *mut $t,
+            inc_y: c_int,
+        ) {
+            unsafe {
+                gemv(
+                    stringify!($gemv),
+                    layout,
+                    trans,
+                    m,
+                    n,
+                    alpha,
+                    a,
+                    lda,
+                    x,
+                    inc_x,
+                    beta,
+                    y,
+                    inc_y,
+                );
+            }
+        }
+
+        #[doc = concat!("`A := alpha·x·yᵀ + A` для `", stringify!($t), "`, `A` размера `m × n`.")]
+        ///
+        /// # Safety
+        /// Указатели покрывают матрицу и векторы указанных размеров, `A` не
+        /// пересекается с `x` и `y`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $ger(
+            layout: CBLAS_LAYOUT,
+            m: c_int,
+            n: c_int,
+            alpha: $t,
+            x: *const $t,
+            inc_x: c_int,
+            y: *const $t,
+            inc_y: c_int,
+            a: *mut $t,
+            lda: c_int,
+        ) {
+            unsafe { ger(stringify!($ger), layout, m, n, alpha, x, inc_x, y, inc_y, a, lda) };
+        }
+
+        #[doc = concat!("`y := alpha·A·x + beta·y` для симметричной `A` из `", stringify!($t), "`.")]
+        ///
+        /// Читается только треугольник `uplo`; второй может содержать что угодно.
+        ///
+        /// # Safety
+        /// Указатели покрывают матрицу `n × n` и векторы длины `n`, `y` не
+        /// пересекается с `A` и `x`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $symv(
+            layout: CBLAS_LAYOUT,
+            uplo: CBLAS_UPLO,
+            n: c_int,
+            alpha: $t,
+            a: *const $t,
+            lda: c_int,
+            x: *const $t,
+            inc_x: c_int,
+            beta: $t,
+            y: *mut $t,
+            inc_y: c_int,
+        ) {
+            unsafe {
+                symv(
+                    stringify!($symv),
+                    layout,
+                    uplo,
+                    n,
+                    alpha,
+                    a,
+                    lda,
+                    x,
+                    inc_x,
+                    beta,
+                    y,
+                    inc_y,
+                );
+            }
+        }
+
+        #[doc = concat!("`x := op(A)·x` для треугольной `A` из `", stringify!($t), "`.")]
+        ///
+        /// Читается только треугольник `uplo`, при `Unit` — без диагонали.
+        ///
+        /// # Safety
+        /// Указатели покрывают матрицу `n × n` и вектор длины `n`, `x` не
+        /// пересекается с `A`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $trmv(
+            layout: CBLAS_LAYOUT,
+            uplo: CBLAS_UPLO,
+            trans: CBLAS_TRANSPOSE,
+            diag: CBLAS_DIAG,
+            n: c_int,
+            a: *const $t,
+            lda: c_int,
+            x: *mut $t,
+            inc_x: c_int,
+        ) {
+            unsafe { trmv(stringify!($trmv), layout, uplo, trans, diag, n, a, lda, x, inc_x) };
+        }
+
+        #[doc = concat!("Решение `op(A)·z = x` на месте `x` для треугольной `A` из `", stringify!($t), "`.")]
+        ///
+        /// Как и эталон, вырожденность `A` не проверяется.
+        ///
+        /// # Safety
+        /// Указатели покрывают матрицу `n × n` и вектор длины `n`, `x` не
+        /// пересекается с `A`.
+        #[unsafe(no_mangle)]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $trsv(
+            layout: CBLAS_LAYOUT,
+            uplo: CBLAS_UPLO,
+            trans: CBLAS_TRANSPOSE,
+            diag: CBLAS_DIAG,
+            n: c_int,
+            a: *const $t,
+            lda: c_int,
+            x: *mut $t,
+            inc_x: c_int,
+        ) {
+            unsafe { trsv(stringify!($trsv), layout, uplo, trans, diag, n, a, lda, x, inc_x) };
+        }
+    };
+}
+
+level2!(
+    f64,
+    cblas_dgemv,
+    cblas_dger,
+    cblas_dsymv,
+    cblas_dtrmv,
+    cblas_dtrsv
+);
+level2!(
+    f32,
+    cblas_sgemv,
+    cblas_sger,
+    cblas_ssymv,
+    cblas_strmv,
+    cblas_strsv
+);
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_int;
+
+    use super::*;
+    use crate::level1::offset;
+    use crate::xerbla::{UNTOUCHED, assert_rejected};
+
+    const LAYOUTS: [CBLAS_LAYOUT; 2] = [CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor];
+    const TRANSPOSES: [CBLAS_TRANSPOSE; 2] = [CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans];
+    const UPLOS: [CBLAS_UPLO; 2] = [CBLAS_UPLO::Upper, CBLAS_UPLO::Lower];
+    const DIAGS: [CBLAS_DIAG; 2] = [CBLAS_DIAG::NonUnit, CBLAS_DIAG::Unit];
+    const INCREMENTS: [c_int; 3] = [1, 2, -3];
+
+    /// Индекс элемента `(row, col)` хранимой матрицы.
+    fn at(layout: CBLAS_LAYOUT, ld: usize, row: usize, col: usize) -> usize {
+        if layout == CBLAS_LAYOUT::RowMajor {
+            row * ld + col
+        } else {
+            row + col * ld
+        }
+    }
+
+    fn filled(len: usize, seed: f64) -> Vec<f64> {
+        (0..len)
+            .map(|i| (i as f64).mul_add(seed, 0.3).sin())
+            .collect()
+    }
+
+    /// Буфер вектора длины `n` с шагом `inc`.
+    fn vector(n: usize, inc: c_int, seed: f64) -> Vec<f64> {
+        filled(1 + (n - 1) * inc.unsigned_abs() as usize, seed)
+    }
+
+    fn gather(buffer: &[f64], n: usize, inc: c_int) -> Vec<f64> {
+        (0..n).map(|i| buffer[offset(i, n, inc)]).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], what: &str) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-12 * expected.abs().max(1.0),
+                "{what}: {actual} vs {expected}"
+            );
+        }
+    }
+
+    /// Треугольная матрица с хорошо обусловленной диагональю и мусором
+    /// (`NaN`) вне хранимого треугольника.
+    fn triangular_matrix(layout: CBLAS_LAYOUT, uplo: CBLAS_UPLO, n: usize, ld: usize) -> Vec<f64> {
+        let mut a = vec![f64::NAN; ld * n];
+        for row in 0..n {
+            for col in 0..n {
+                let stored = if uplo == CBLAS_UPLO::Lower {
+                    row >= col
+                } else {
+                    row <= col
+                };
+                if stored {
+                    let value = ((row * n + col) as f64).mul_add(0.37, 0.1).sin();
+                    a[at(layout, ld, row, col)] = if row == col { 2.0 + value } else { value };
+                }
+            }
+        }
+        a
+    }
+
+    /// Плотная `op(A)` для треугольной `A` с учётом `diag`.
+    fn dense_op(
+        a: &[f64],
+        layout: CBLAS_LAYOUT,
+        uplo: CBLAS_UPLO,
+        trans: CBLAS_TRANSPOSE,
+        diag: CBLAS_DIAG,
+        ld: usize,
+    ) -> impl Fn(usize, usize) -> f64 {
+        move |i, j| {
+            let (row, col) = if trans == CBLAS_TRANSPOSE::Trans {
+                (j, i)
+            } else {
+                (i, j)
+            };
+            let stored = if uplo == CBLAS_UPLO::Lower {
+                row >= col
+            } else {
+                row <= col
+            };
+            if row == col && diag == CBLAS_DIAG::Unit {
+                1.0
+            } else if stored {
+                a[at(layout, ld, row, col)]
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn gemv_matches_naive_for_layouts_transposes_and_increments() {
+        let (m, n) = (3, 4);
+        let (alpha, beta) = (1.5, -0.5);
+        for layout in LAYOUTS {
+            for trans in TRANSPOSES {
+                for inc in INCREMENTS {
+                    let ld = if layout == CBLAS_LAYOUT::RowMajor {
+                        n
+                    } else {
+                        m
+                    } + 1;
+                    let a = filled(ld * m.max(n), 0.7);
+                    let (x_len, y_len) = if trans == CBLAS_TRANSPOSE::Trans {
+                        (m, n)
+                    } else {
+                        (n, m)
+                    };
+                    let x = vector(x_len, inc, 1.1);
+                    let mut y = vector(y_len, -inc, 2.3);
+                    let (xs, ys) = (gather(&x, x_len, inc), gather(&y, y_len, -inc));
+                    let expected: Vec<f64> = (0..y_len)
+                        .map(|i| {
+                            let sum: f64 = (0..x_len)
+                                .map(|j| {
+                                    let element = if trans == CBLAS_TRANSPOSE::Trans {
+                                        a[at(layout, ld, j, i)]
+                                    } else {
+                                        a[at(layout, ld, i, j)]
+                                    };
+                                    element * xs[j]
+                                })
+                                .sum();
+                            alpha * sum + beta * ys[i]
+                        })
+                        .collect();
+
+                    unsafe {
+                        cblas_dgemv(
+                            layout,
+                            trans,
+                            m as c_int,
+                            n as c_int,
+                            alpha,
+                            a.as_ptr(),
+                            ld as c_int,
+                            x.as_ptr(),
+                            inc,
+                            beta,
+                            y.as_mut_ptr(),
+                            -inc,
+                        );
+                    }
+                    let what = format!("{layout:?} {trans:?} inc {inc}");
+                    assert_close(&gather(&y, y_len, -inc), &expected, &what);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ger_and_symv_match_naive() {
+        let n = 4;
+        for layout in LAYOUTS {
+            for inc in INCREMENTS {
+                let ld = n + 2;
+                let x = vector(n, inc, 0.9);
+                let y = vector(n, -inc, 1.7);
+                let (xs, ys) = (gather(&x, n, inc), gather(&y, n, -inc));
+
+                let mut a = filled(ld * n, 0.4);
+                let mut expected = a.clone();
+                for row in 0..n {
+                    for col in 0..n {
+                        expected[at(layout, ld, row, col)] += 2.0 * xs[row] * ys[col];
+                    }
+                }
+                unsafe {
+                    cblas_dger(
+                        layout,
+                        n as c_int,
+                        n as c_int,
+                        2.0,
+                        x.as_ptr(),
+                        inc,
+                        y.as_ptr(),
+                        -inc,
+                        a.as_mut_ptr(),
+                        ld as c_int,
+                    );
+                }
+                assert_close(&a, &expected, &format!("ger {layout:?} inc {inc}"));
+
+                for uplo in UPLOS {
+                    let a = triangular_matrix(layout, uplo, n, ld);
+                    let symmetric = |row: usize, col: usize| {
+                        let (row, col) = if (uplo == CBLAS_UPLO::Lower) == (row >= col) {
+                            (row, col)
+                        } else {
+                            (col, row)
+                        };
+                        a[at(layout, ld, row, col)]
+                    };
+                    let expected: Vec<f64> = (0..n)
+                        .map(|i| {
+                            let sum: f64 = (0..n).map(|j| symmetric(i, j) * xs[j]).sum();
+                            0.5f64.mul_add(sum, 3.0 * ys[i])
+                        })
+                        .collect();
+                    let mut y = y.clone();
+                    unsafe {
+                        cblas_dsymv(
+                            layout,
+                            uplo,
+                            n as c_int,
+                            0.5,
+                            a.as_ptr(),
+                            ld as c_int,
+                            x.as_ptr(),
+                            inc,
+                            3.0,
+                            y.as_mut_ptr(),
+                            -inc,
+                        );
+                    }
+                    let what = format!("symv {layout:?} {uplo:?} inc {inc}");
+                    assert_close(&gather(&y, n, -inc), &expected, &what);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trmv_matches_naive_and_trsv_inverts_it() {
+        let n = 5;
+        let ld = n + 1;
+        for layout in LAYOUTS {
+            for uplo in UPLOS {
+                for trans in TRANSPOSES {
+                    for diag in DIAGS {
+                        for inc in INCREMENTS {
+                            let a = triangular_matrix(layout, uplo, n, ld);
+                            let op = dense_op(&a, layout, uplo, trans, diag, ld);
+                            let mut x = vector(n, inc, 1.9);
+                            let original = x.clone();
+                            let xs = gather(&x, n, inc);
+                            let expected: Vec<f64> = (0..n)
+                                .map(|i| (0..n).map(|j| op(i, j) * xs[j]).sum())
+                                .collect();
+                            let what = format!("{layout:?} {uplo:?} {trans:?} {diag:?} inc {inc}");
+
+                            unsafe {
+                                cblas_dtrmv(
+                                    layout,
+                                    uplo,
+                                    trans,
+                                    diag,
+                                    n as c_int,
+                                    a.as_ptr(),
+                                    ld as c_int,
+                                    x.as_mut_ptr(),
+                                    inc,
+                                );
+                            }
+                            assert_close(&gather(&x, n, inc), &expected, &what);
+
+                            unsafe {
+                                cblas_dtrsv(
+                                    layout,
+                                    uplo,
+                                    trans,
+                                    diag,
+                                    n as c_int,
+                                    a.as_ptr(),
+                                    ld as c_int,
+                                    x.as_mut_ptr(),
+                                    inc,
+                                );
+                            }
+                            assert_close(&x, &original, &what);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported_by_position() {
+        let (row, col) = (CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor);
+        let no = CBLAS_TRANSPOSE::NoTrans;
+        let (lower, unit) = (CBLAS_UPLO::Lower, CBLAS_DIAG::Unit);
+        assert_eq!(check_gemv(col, no, 3, 2, 3, 1, 1), None);
+        assert_eq!(check_gemv(col, no, 3, 2, 2, 1, 1), Some(7));
+        assert_eq!(check_gemv(row, no, 3, 2, 2, 1, 1), None);
+        assert_eq!(check_gemv(col, CBLAS_TRANSPOSE(0), 3, 2, 3, 1, 1), Some(2));
+        assert_eq!(check_gemv(col, no, 3, 2, 3, 0, 1), Some(9));
+        assert_eq!(check_gemv(col, no, 3, 2, 3, 1, 0), Some(12));
+        assert_eq!(check_ger(row, 3, 2, 1, 1, 1), Some(10));
+        assert_eq!(check_ger(col, -1, 2, 1, 1, 3), Some(2));
+        assert_eq!(check_symv(col, CBLAS_UPLO(0), 2, 2, 1, 1), Some(2));
+        assert_eq!(check_symv(col, lower, 2, 1, 1, 1), Some(6));
+        assert_eq!(
+            check_triangular(col, lower, no, CBLAS_DIAG(0), 2, 2, 1),
+            Some(4)
+        );
+        assert_eq!(check_triangular(col, lower, no, unit, 2, 2, 0), Some(9));
+        assert_eq!(
+            check_triangular(CBLAS_LAYOUT(0), lower, no, unit, 2, 2, 1),
+            Some(1)
+        );
+
+        // Неверный вызов не трогает выходной вектор.
+        let a = [1.0; 4];
+        let mut x = [5.0; 2];
+        unsafe { cblas_dtrmv(col, lower, no, unit, 2, a.as_ptr(), 1, x.as_mut_ptr(), 1) };
+        assert_eq!(x, [5.0; 2]);
+    }
+
+    #[test]
+    fn every_invalid_argument_reaches_xerbla() {
+        let input = [1.0; 16];
+        let (layout, trans) = (CBLAS_LAYOUT, CBLAS_TRANSPOSE);
+        let (uplo, diag) = (CBLAS_UPLO, CBLAS_DIAG);
+
+        // [layout, trans, m, n, lda, inc_x, inc_y]
+        let gemv_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, -1, 3),
+            (3, -1, 4),
+            (4, 1, 7),
+            (5, 0, 9),
+            (6, 0, 12),
+        ];
+        let gemv_valid = [102, 111, 2, 3, 2, 1, 1];
+        assert_rejected(
+            "cblas_dgemv",
+            gemv_valid,
+            &gemv_cases,
+            |[l, t, m, n, lda, ix, iy]| {
+                let mut y = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dgemv(
+                        layout(l),
+                        trans(t),
+                        m,
+                        n,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        input.as_ptr(),
+                        ix,
+                        0.0,
+                        y.as_mut_ptr(),
+                        iy,
+                    );
+                }
+                y
+            },
+        );
+        let input32 = [1.0_f32; 16];
+        assert_rejected(
+            "cblas_sgemv",
+            gemv_valid,
+            &gemv_cases,
+            |[l, t, m, n, lda, ix, iy]| {
+                let mut y = vec![UNTOUCHED as f32; 16];
+                unsafe {
+                    cblas_sgemv(
+                        layout(l),
+                        trans(t),
+                        m,
+                        n,
+                        1.0,
+                        input32.as_ptr(),
+                        lda,
+                        input32.as_ptr(),
+                        ix,
+                        0.0,
+                        y.as_mut_ptr(),
+                        iy,
+                    );
+                }
+                y.into_iter().map(f64::from).collect()
+            },
+        );
+
+        // [layout, m, n, inc_x, inc_y, lda]
+        let ger_cases = [
+            (0, 0, 1),
+            (1, -1, 2),
+            (2, -1, 3),
+            (3, 0, 6),
+            (4, 0, 8),
+            (5, 1, 10),
+        ];
+        assert_rejected(
+            "cblas_dger",
+            [102, 2, 3, 1, 1, 2],
+            &ger_cases,
+            |[l, m, n, ix, iy, lda]| {
+                let mut a = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dger(
+                        layout(l),
+                        m,
+                        n,
+                        1.0,
+                        input.as_ptr(),
+                        ix,
+                        input.as_ptr(),
+                        iy,
+                        a.as_mut_ptr(),
+                        lda,
+                    );
+                }
+                a
+            },
+        );
+
+        // [layout, uplo, n, lda, inc_x, inc_y]
+        let symv_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, -1, 3),
+            (3, 1, 6),
+            (4, 0, 8),
+            (5, 0, 11),
+        ];
+        assert_rejected(
+            "cblas_dsymv",
+            [102, 121, 2, 2, 1, 1],
+            &symv_cases,
+            |[l, u, n, lda, ix, iy]| {
+                let mut y = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dsymv(
+                        layout(l),
+                        uplo(u),
+                        n,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        input.as_ptr(),
+                        ix,
+                        0.0,
+                        y.as_mut_ptr(),
+                        iy,
+                    );
+                }
+                y
+            },
+        );
+
+        // [layout, uplo, trans, diag, n, lda, inc_x] — общие для trmv и trsv.
+        let triangular_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, 0, 4),
+            (4, -1, 5),
+            (5, 1, 7),
+            (6, 0, 9),
+        ];
+        let triangular_valid = [102, 121, 111, 131, 2, 2, 1];
+        let routines: [(&str, unsafe extern "C" fn(_, _, _, _, _, _, _, _, _)); 2] =
+            [("cblas_dtrmv", cblas_dtrmv), ("cblas_dtrsv", cblas_dtrsv)];
+        for (routine, function) in routines {
+            assert_rejected(
+                routine,
+                triangular_valid,
+                &triangular_cases,
+                |[l, u, t, d, n, lda, ix]| {
+                    let mut x = vec![UNTOUCHED; 16];
+                    unsafe {
+                        function(
+                            layout(l),
+                            uplo(u),
+                            trans(t),
+                            diag(d),
+                            n,
+                            input.as_ptr(),
+                            lda,
+                            x.as_mut_ptr(),
+                            ix,
+                        );
+                    }
+                    x
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn single_precision_variants_match_double() {
+        let n = 3;
+        let a = triangular_matrix(CBLAS_LAYOUT::ColMajor, CBLAS_UPLO::Upper, n, n);
+        let a = a
+            .iter()
+            .map(|v| if v.is_nan() { 0.0 } else { *v })
+            .collect::<Vec<_>>();
+        let a32 = a.iter().map(|&v| v as f32).collect::<Vec<_>>();
+        let x = filled(n, 0.8);
+        let x32 = x.iter().map(|&v| v as f32).collect::<Vec<_>>();
+        let (mut y, mut y32) = (vec![0.0; n], vec![0.0_f32; n]);
+        let (mut z, mut z32) = (x.clone(), x32.clone());
+        let (col, upper, no) = (
+            CBLAS_LAYOUT::ColMajor,
+            CBLAS_UPLO::Upper,
+            CBLAS_TRANSPOSE::NoTrans,
+        );
+        unsafe {
+            cblas_dgemv(
+                col,
+                no,
+                3,
+                3,
+                1.0,
+                a.as_ptr(),
+                3,
+                x.as_ptr(),
+                1,
+                0.0,
+                y.as_mut_ptr(),
+                1,
+            );
+            cblas_sgemv(
+                col,
+                no,
+                3,
+                3,
+                1.0,
+                a32.as_ptr(),
+                3,
+                x32.as_ptr(),
+                1,
+                0.0,
+                y32.as_mut_ptr(),
+                1,
+            );
+            cblas_dsymv(
+                col,
+                upper,
+                3,
+                1.0,
+                a.as_ptr(),
+                3,
+                x.as_ptr(),
+                1,
+                1.0,
+                y.as_mut_ptr(),
+                1,
+            );
+            cblas_ssymv(
+                col,
+                upper,
+                3,
+                1.0,
+                a32.as_ptr(),
+                3,
+                x32.as_ptr(),
+                1,
+                1.0,
+                y32.as_mut_ptr(),
+                1,
+            );
+            cblas_dtrsv(
+                col,
+                upper,
+                no,
+                CBLAS_DIAG::NonUnit,
+                3,
+                a.as_ptr(),
+                3,
+                z.as_mut_ptr(),
+                1,
+            );
+            cblas_strsv(
+                col,
+                upper,
+                no,
+                CBLAS_DIAG::NonUnit,
+                3,
+                a32.as_ptr(),
+                3,
+                z32.as_mut_ptr(),
+                1,
+            );
+        }
+        for (double, single) in y.iter().chain(&z).zip(y32.iter().chain(&z32)) {
+            assert!(
+                (double - f64::from(*single)).abs() < 1e-5,
+                "{double} vs {single}"
+            );
+        }
+    }
+}
@@ -0,0 +1,1890 @@
+//! BLAS уровня 3: операции матрица–матрица.
+//!
+//! Общее умножение `?gemm` есть для всех четырёх типов: `f32`, `f64` и
+//! комплексных `c32`, `c64` (у последних `alpha` и `beta` передаются по
+//! указателю, как в эталонном ABI). Кроме него для `f64` здесь симметричные (`dsymm`, `dsyrk`, `dsyr2k`) и
+//! треугольные (`dtrmm`, `dtrsm`) операции поверх треугольных ядер `faer`:
+//! они читают только хранимый треугольник, а `syrk`/`syr2k` и пишут только в
+//! него.
+
+use core::ffi::c_int;
+
+use faer::linalg::matmul::matmul_with_conj;
+use faer::linalg::matmul::triangular::{self, BlockStructure};
+use faer::mat::MatMut;
+use faer::prelude::ReborrowMut;
+use faer::{Accum, Conj, Par, c32, c64};
+
+use crate::{
+    CBLAS_DIAG, CBLAS_LAYOUT, CBLAS_SIDE, CBLAS_TRANSPOSE, CBLAS_UPLO, Element, dim, matmut,
+    matref, min_ld, par, scale_matrix, solve_triangular, triangle, xerbla,
+};
+
+/// Номер первого неверного параметра `cblas_?gemm` (с единицы, считая
+/// `layout`) или `None`, если аргументы корректны.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_gemm(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    lda: c_int,
+    ldb: c_int,
+    ldc: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        return Some(1);
+    }
+    let Some(a_transposed) = transa.is_transposed() else {
+        return Some(2);
+    };
+    let Some(b_transposed) = transb.is_transposed() else {
+        return Some(3);
+    };
+    // Хранимые размеры A и B до применения `op`.
+    let (a_rows, a_cols) = if a_transposed { (k, m) } else { (m, k) };
+    let (b_rows, b_cols) = if b_transposed { (n, k) } else { (k, n) };
+    if m < 0 {
+        Some(4)
+    } else if n < 0 {
+        Some(5)
+    } else if k < 0 {
+        Some(6)
+    } else if lda < min_ld(layout, a_rows, a_cols) {
+        Some(9)
+    } else if ldb < min_ld(layout, b_rows, b_cols) {
+        Some(11)
+    } else if ldc < min_ld(layout, m, n) {
+        Some(14)
+    } else {
+        None
+    }
+}
+
+/// `C := alpha·op(A)·op(B) + beta·C` для любого типа элементов; `routine` —
+/// имя для сообщения об ошибке, `par` — параллелизм `faer` внутри умножения.
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn gemm<T: Element>(
+    routine: &str,
+    par: Par,
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: T,
+    a: *const T,
+    lda: c_int,
+    b: *const T,
+    ldb: c_int,
+    beta: T,
+    c: *mut T,
+    ldc: c_int,
+) {
+    if let Some(position) = check_gemm(layout, transa, transb, m, n, k, lda, ldb, ldc) {
+        xerbla(routine, position);
+        return;
+    }
+    // После проверки все размеры неотрицательны.
+    let [m, n, k, lda, ldb, ldc] = [m, n, k, lda, ldb, ldc].map(dim);
+    let a_transposed = transa != CBLAS_TRANSPOSE::NoTrans;
+    let b_transposed = transb != CBLAS_TRANSPOSE::NoTrans;
+    if m == 0 || n == 0 {
+        return;
+    }
+
+    let mut c_view = unsafe { matmut(c, layout, m, n, ldc) };
+    if alpha == T::ZERO || k == 0 {
+        scale_matrix(c_view, beta);
+        return;
+    }
+
+    // Виды хранимых матриц, затем `op(X)`; сопряжение `ConjTrans` делает сам
+    // `matmul`, для вещественных типов оно ничего не меняет.
+    let a_view = if a_transposed {
+        unsafe { matref(a, layout, k, m, lda) }.transpose()
+    } else {
+        unsafe { matref(a, layout, m, k, lda) }
+    };
+    let b_view = if b_transposed {
+        unsafe { matref(b, layout, n, k, ldb) }.transpose()
+    } else {
+        unsafe { matref(b, layout, k, n, ldb) }
+    };
+    let conj = |trans| {
+        if trans == CBLAS_TRANSPOSE::ConjTrans {
+            Conj::Yes
+        } else {
+            Conj::No
+        }
+    };
+
+    // C := alpha*A*B + beta*C
+    let accum = if beta == T::ZERO {
+        Accum::Replace
+    } else {
+        scale_matrix(c_view.rb_mut(), beta);
+        Accum::Add
+    };
+
+    matmul_with_conj(
+        c_view,
+        accum,
+        a_view,
+        conj(transa),
+        b_view,
+        conj(transb),
+        alpha,
+        par,
+    );
+}
+
+/// Реализация `cblas_dgemm` с ABI C: вычисляет матричное произведение
+/// `C := alpha * op(A) * op(B) + beta * C` для матриц плотного формата в духе CBLAS.
+///
+/// Аргументы полностью повторяют традиционный интерфейс:
+/// - `layout` указывает раскладку памяти (`RowMajor` или `ColMajor`);
+/// - `transa` и `transb` определяют, применяется ли транспонирование к `A` и `B`;
+/// - `m`, `n`, `k` задают размеры результирующей матрицы и внутреннее измерение;
+/// - `alpha` и `beta` — скаляры, масштабирующие произведение и исходное содержимое `C`;
+/// - `a`, `b`, `c` — указатели на буферы с элементами матриц;
+/// - `lda`, `ldb`, `ldc` — ведущие размеры (число элементов между соседними строками/столбцами).
+///
+/// Для `RowMajor` ведущий размер соответствует длине строки, для `ColMajor` — длине столбца.
+/// Параметры `trans*` задают `op(X)` как идентичность либо транспонирование;
+/// `ConjTrans` для вещественных матриц означает то же, что `Trans`.
+///
+/// Аргументы проверяются по правилам эталонного BLAS (`lda` не меньше длины
+/// хранимого столбца или строки `A` и т. д.); при неверном аргументе `C` не
+/// меняется, ошибки — см. [`crate::xerbla`]. При `beta == 0` старое
+/// содержимое `C` не читается, при `alpha == 0` не читаются `A` и `B`.
+///
+/// # Safety
+/// Вызывающая сторона обязана обеспечить:
+/// - корректные размеры выделенной памяти;
+/// - валидные и выровненные указатели `a`, `b`, `c` на достаточное число элементов `f64`;
+/// - отсутствие aliasing.
+///
+/// Символ помечен `#[unsafe(no_mangle)]`, чтобы его обнаруживал Enzyme.
+#[unsafe(no_mangle)]
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_dgemm(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *const f64,
+    ldb: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+) {
+    unsafe {
+        gemm(
+            "cblas_dgemm",
+            par(),
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            alpha,
+            a,
+            lda,
+            b,
+            ldb,
+            beta,
+            c,
+            ldc,
+        );
+    }
+}
+
+/// [`cblas_dgemm`] для `f32`.
+///
+/// # Safety
+/// Те же требования, что у [`cblas_dgemm`], для элементов `f32`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_sgemm(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: f32,
+    a: *const f32,
+    lda: c_int,
+    b: *const f32,
+    ldb: c_int,
+    beta: f32,
+    c: *mut f32,
+    ldc: c_int,
+) {
+    unsafe {
+        gemm(
+            "cblas_sgemm",
+            par(),
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            alpha,
+            a,
+            lda,
+            b,
+            ldb,
+            beta,
+            c,
+            ldc,
+        );
+    }
+}
+
+/// [`cblas_dgemm`] для комплексных `c32` (`float _Complex` в C). `ConjTrans`
+/// задаёт `op(X) = Xᴴ`; `alpha` и `beta` передаются по указателю.
+///
+/// # Safety
+/// Те же требования, что у [`cblas_dgemm`], для элементов `c32`; `alpha` и
+/// `beta` указывают на валидные значения.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_cgemm(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: *const c32,
+    a: *const c32,
+    lda: c_int,
+    b: *const c32,
+    ldb: c_int,
+    beta: *const c32,
+    c: *mut c32,
+    ldc: c_int,
+) {
+    unsafe {
+        gemm(
+            "cblas_cgemm",
+            par(),
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            *alpha,
+            a,
+            lda,
+            b,
+            ldb,
+            *beta,
+            c,
+            ldc,
+        );
+    }
+}
+
+/// [`cblas_dgemm`] для комплексных `c64` (`double _Complex` в C). `ConjTrans`
+/// задаёт `op(X) = Xᴴ`; `alpha` и `beta` передаются по указателю.
+///
+/// # Safety
+/// Те же требования, что у [`cblas_dgemm`], для элементов `c64`; `alpha` и
+/// `beta` указывают на валидные значения.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn cblas_zgemm(
+    layout: CBLAS_LAYOUT,
+    transa: CBLAS_TRANSPOSE,
+    transb: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: *const c64,
+    a: *const c64,
+    lda: c_int,
+    b: *const c64,
+    ldb: c_int,
+    beta: *const c64,
+    c: *mut c64,
+    ldc: c_int,
+) {
+    unsafe {
+        gemm(
+            "cblas_zgemm",
+            par(),
+            layout,
+            transa,
+            transb,
+            m,
+            n,
+            k,
+            *alpha,
+            a,
+            lda,
+            b,
+            ldb,
+            *beta,
+            c,
+            ldc,
+        );
+    }
+}
+
+/// Номер первого неверного параметра `cblas_dsymm` или `None`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_dsymm(
+    layout: CBLAS_LAYOUT,
+    side: CBLAS_SIDE,
+    uplo: CBLAS_UPLO,
+    m: c_int,
+    n: c_int,
+    lda: c_int,
+    ldb: c_int,
+    ldc: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        return Some(1);
+    }
+    let Some(left) = side.is_left() else {
+        return Some(2);
+    };
+    // Порядок квадратной симметричной A.
+    let ka = if left { m } else { n };
+    if uplo.is_lower().is_none() {
+        Some(3)
+    } else if m < 0 {
+        Some(4)
+    } else if n < 0 {
+        Some(5)
+    } else if lda < ka.max(1) {
+        Some(8)
+    } else if ldb < min_ld(layout, m, n) {
+        Some(10)
+    } else if ldc < min_ld(layout, m, n) {
+        Some(13)
+    } else {
+        None
+    }
+}
+
+/// Хранимые размеры `A` в `syrk`/`syr2k`: `n × k` без транспонирования.
+const fn rank_k_shape(transposed: bool, n: c_int, k: c_int) -> (c_int, c_int) {
+    if transposed { (k, n) } else { (n, k) }
+}
+
+/// Номер первого неверного параметра `cblas_dsyrk` или `None`.
+pub(crate) fn check_dsyrk(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    n: c_int,
+    k: c_int,
+    lda: c_int,
+    ldc: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        return Some(1);
+    }
+    if uplo.is_lower().is_none() {
+        return Some(2);
+    }
+    let Some(transposed) = trans.is_transposed() else {
+        return Some(3);
+    };
+    let (a_rows, a_cols) = rank_k_shape(transposed, n, k);
+    if n < 0 {
+        Some(4)
+    } else if k < 0 {
+        Some(5)
+    } else if lda < min_ld(layout, a_rows, a_cols) {
+        Some(8)
+    } else if ldc < n.max(1) {
+        Some(11)
+    } else {
+        None
+    }
+}
+
+/// Номер первого неверного параметра `cblas_dsyr2k` или `None`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_dsyr2k(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    n: c_int,
+    k: c_int,
+    lda: c_int,
+    ldb: c_int,
+    ldc: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        return Some(1);
+    }
+    if uplo.is_lower().is_none() {
+        return Some(2);
+    }
+    let Some(transposed) = trans.is_transposed() else {
+        return Some(3);
+    };
+    let (rows, cols) = rank_k_shape(transposed, n, k);
+    if n < 0 {
+        Some(4)
+    } else if k < 0 {
+        Some(5)
+    } else if lda < min_ld(layout, rows, cols) {
+        Some(8)
+    } else if ldb < min_ld(layout, rows, cols) {
+        Some(10)
+    } else if ldc < n.max(1) {
+        Some(13)
+    } else {
+        None
+    }
+}
+
+/// Номер первого неверного параметра `cblas_dtrmm` и `cblas_dtrsm` (у них
+/// одинаковые сигнатуры) или `None`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_dtrmm(
+    layout: CBLAS_LAYOUT,
+    side: CBLAS_SIDE,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    m: c_int,
+    n: c_int,
+    lda: c_int,
+    ldb: c_int,
+) -> Option<c_int> {
+    if !layout.is_valid() {
+        return Some(1);
+    }
+    let Some(left) = side.is_left() else {
+        return Some(2);
+    };
+    let ka = if left { m } else { n };
+    if uplo.is_lower().is_none() {
+        Some(3)
+    } else if trans.is_transposed().is_none() {
+        Some(4)
+    } else if diag.is_unit().is_none() {
+        Some(5)
+    } else if m < 0 {
+        Some(6)
+    } else if n < 0 {
+        Some(7)
+    } else if lda < ka.max(1) {
+        Some(10)
+    } else if ldb < min_ld(layout, m, n) {
+        Some(12)
+    } else {
+        None
+    }
+}
+
+/// `C := beta · C` только в треугольнике `lower` квадратной `C`: второй
+/// треугольник `syrk`/`syr2k` не трогают. При `beta == 0` старые значения не
+/// читаются.
+#[allow(clippy::float_cmp)]
+fn scale_triangle(mut c: MatMut<'_, f64>, lower: bool, beta: f64) {
+    if beta == 1.0 {
+        return;
+    }
+    let n = c.nrows();
+    for col in 0..n {
+        let rows = if lower { col..n } else { 0..col + 1 };
+        for row in rows {
+            let elem = c.rb_mut().get_mut(row, col);
+            *elem = if beta == 0.0 { 0.0 } else { beta * *elem };
+        }
+    }
+}
+
+/// `C := alpha·A·B + beta·C` (`Left`) или `C := alpha·B·A + beta·C` (`Right`)
+/// для симметричной `A`, хранимой треугольником `uplo`; `B` и `C` размера
+/// `m × n`, `A` — `m × m` или `n × n`.
+///
+/// Второй треугольник `A` не читается. Аргументы проверяются как в
+/// [`cblas_dgemm`].
+///
+/// # Safety
+/// Указатели покрывают матрицы указанных размеров, `C` не пересекается с `A` и `B`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments, clippy::float_cmp)]
+pub unsafe extern "C" fn cblas_dsymm(
+    layout: CBLAS_LAYOUT,
+    side: CBLAS_SIDE,
+    uplo: CBLAS_UPLO,
+    m: c_int,
+    n: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *const f64,
+    ldb: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+) {
+    if let Some(position) = check_dsymm(layout, side, uplo, m, n, lda, ldb, ldc) {
+        xerbla("cblas_dsymm", position);
+        return;
+    }
+    let [m, n, lda, ldb, ldc] = [m, n, lda, ldb, ldc].map(dim);
+    if m == 0 || n == 0 || (alpha == 0.0 && beta == 1.0) {
+        return;
+    }
+
+    let mut c_view = unsafe { matmut(c, layout, m, n, ldc) };
+    scale_matrix(c_view.rb_mut(), beta);
+    if alpha == 0.0 {
+        return;
+    }
+
+    let left = side == CBLAS_SIDE::Left;
+    let ka = if left { m } else { n };
+    let a_view = unsafe { matref(a, layout, ka, ka, lda) };
+    // Хранимый треугольник, приведённый к нижнему: A = L + strict(L)ᵀ.
+    let lower = if uplo == CBLAS_UPLO::Lower {
+        a_view
+    } else {
+        a_view.transpose()
+    };
+    let b_view = unsafe { matref(b, layout, m, n, ldb) };
+    let halves = [
+        (lower, BlockStructure::TriangularLower),
+        (lower.transpose(), BlockStructure::StrictTriangularUpper),
+    ];
+    for (half, structure) in halves {
+        if left {
+            triangular::matmul(
+                c_view.rb_mut(),
+                BlockStructure::Rectangular,
+                Accum::Add,
+                half,
+                structure,
+                b_view,
+                BlockStructure::Rectangular,
+                alpha,
+                par(),
+            );
+        } else {
+            triangular::matmul(
+                c_view.rb_mut(),
+                BlockStructure::Rectangular,
+                Accum::Add,
+                b_view,
+                BlockStructure::Rectangular,
+                half,
+                structure,
+                alpha,
+                par(),
+            );
+        }
+    }
+}
+
+/// Симметричное обновление ранга `k`: `C := alpha·A·Aᵀ + beta·C` (`NoTrans`,
+/// `A` размера `n × k`) или `C := alpha·Aᵀ·A + beta·C` (`Trans`, `A` размера
+/// `k × n`).
+///
+/// Читается и пишется только треугольник `uplo` матрицы `C` (`n × n`).
+///
+/// # Safety
+/// Указатели покрывают матрицы указанных размеров, `C` не пересекается с `A`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments, clippy::float_cmp)]
+pub unsafe extern "C" fn cblas_dsyrk(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    n: c_int,
+    k: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+) {
+    if let Some(position) = check_dsyrk(layout, uplo, trans, n, k, lda, ldc) {
+        xerbla("cblas_dsyrk", position);
+        return;
+    }
+    let [n, k, lda, ldc] = [n, k, lda, ldc].map(dim);
+    if n == 0 || ((alpha == 0.0 || k == 0) && beta == 1.0) {
+        return;
+    }
+
+    let lower = uplo == CBLAS_UPLO::Lower;
+    let mut c_view = unsafe { matmut(c, layout, n, n, ldc) };
+    scale_triangle(c_view.rb_mut(), lower, beta);
+    if alpha == 0.0 || k == 0 {
+        return;
+    }
+
+    // `a_view` — множитель `n × k` в `C += alpha·a_view·a_viewᵀ`.
+    let a_view = if trans != CBLAS_TRANSPOSE::NoTrans {
+        unsafe { matref(a, layout, k, n, lda) }.transpose()
+    } else {
+        unsafe { matref(a, layout, n, k, lda) }
+    };
+    triangular::matmul(
+        c_view,
+        triangle(lower, false),
+        Accum::Add,
+        a_view,
+        BlockStructure::Rectangular,
+        a_view.transpose(),
+        BlockStructure::Rectangular,
+        alpha,
+        par(),
+    );
+}
+
+/// Симметричное обновление ранга `2k`: `C := alpha·(A·Bᵀ + B·Aᵀ) + beta·C`
+/// (`NoTrans`, `A` и `B` размера `n × k`) или `C := alpha·(Aᵀ·B + Bᵀ·A) + beta·C`
+/// (`Trans`, `k × n`).
+///
+/// Читается и пишется только треугольник `uplo` матрицы `C` (`n × n`).
+///
+/// # Safety
+/// Указатели покрывают матрицы указанных размеров, `C` не пересекается с `A` и `B`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments, clippy::float_cmp)]
+pub unsafe extern "C" fn cblas_dsyr2k(
+    layout: CBLAS_LAYOUT,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    n: c_int,
+    k: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *const f64,
+    ldb: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+) {
+    if let Some(position) = check_dsyr2k(layout, uplo, trans, n, k, lda, ldb, ldc) {
+        xerbla("cblas_dsyr2k", position);
+        return;
+    }
+    let [n, k, lda, ldb, ldc] = [n, k, lda, ldb, ldc].map(dim);
+    if n == 0 || ((alpha == 0.0 || k == 0) && beta == 1.0) {
+        return;
+    }
+
+    let lower = uplo == CBLAS_UPLO::Lower;
+    let mut c_view = unsafe { matmut(c, layout, n, n, ldc) };
+    scale_triangle(c_view.rb_mut(), lower, beta);
+    if alpha == 0.0 || k == 0 {
+        return;
+    }
+
+    let transposed = trans != CBLAS_TRANSPOSE::NoTrans;
+    let factor = |ptr, ld| {
+        if transposed {
+            unsafe { matref(ptr, layout, k, n, ld) }.transpose()
+        } else {
+            unsafe { matref(ptr, layout, n, k, ld) }
+        }
+    };
+    let (a_view, b_view) = (factor(a, lda), factor(b, ldb));
+    for (lhs, rhs) in [(a_view, b_view), (b_view, a_view)] {
+        triangular::matmul(
+            c_view.rb_mut(),
+            triangle(lower, false),
+            Accum::Add,
+            lhs,
+            BlockStructure::Rectangular,
+            rhs.transpose(),
+            BlockStructure::Rectangular,
+            alpha,
+            par(),
+        );
+    }
+}
+
+/// `B := alpha·op(A)·B` (`Left`) или `B := alpha·B·op(A)` (`Right`) для
+/// треугольной `A`; `B` размера `m × n`, `A` — `m × m` или `n × n`.
+///
+/// Читается только треугольник `uplo`, при `Unit` — без диагонали. При
+/// `alpha == 0` матрица `A` не читается, а `B` обнуляется.
+///
+/// # Safety
+/// Указатели покрывают матрицы указанных размеров, `B` не пересекается с `A`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments, clippy::float_cmp)]
+pub unsafe extern "C" fn cblas_dtrmm(
+    layout: CBLAS_LAYOUT,
+    side: CBLAS_SIDE,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    m: c_int,
+    n: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *mut f64,
+    ldb: c_int,
+) {
+    if let Some(position) = check_dtrmm(layout, side, uplo, trans, diag, m, n, lda, ldb) {
+        xerbla("cblas_dtrmm", position);
+        return;
+    }
+    let [m, n, lda, ldb] = [m, n, lda, ldb].map(dim);
+    if m == 0 || n == 0 {
+        return;
+    }
+
+    let mut b_view = unsafe { matmut(b, layout, m, n, ldb) };
+    if alpha == 0.0 {
+        scale_matrix(b_view, 0.0);
+        return;
+    }
+
+    let left = side == CBLAS_SIDE::Left;
+    let ka = if left { m } else { n };
+    let a_view = unsafe { matref(a, layout, ka, ka, lda) };
+    let structure = triangle(uplo == CBLAS_UPLO::Lower, diag == CBLAS_DIAG::Unit);
+    let (a_view, structure) = if trans != CBLAS_TRANSPOSE::NoTrans {
+        (a_view.transpose(), structure.transpose())
+    } else {
+        (a_view, structure)
+    };
+    // `faer` не умножает на месте, поэтому исходная `B` копируется.
+    let input = b_view.as_ref().to_owned();
+    if left {
+        triangular::matmul(
+            b_view.rb_mut(),
+            BlockStructure::Rectangular,
+            Accum::Replace,
+            a_view,
+            structure,
+            &input,
+            BlockStructure::Rectangular,
+            alpha,
+            par(),
+        );
+    } else {
+        triangular::matmul(
+            b_view.rb_mut(),
+            BlockStructure::Rectangular,
+            Accum::Replace,
+            &input,
+            BlockStructure::Rectangular,
+            a_view,
+            structure,
+            alpha,
+            par(),
+        );
+    }
+}
+
+/// Решает `op(A)·X = alpha·B` (`Left`) или `X·op(A) = alpha·B` (`Right`) для
+/// треугольной `A` и записывает `X` в `B`.
+///
+/// Читается только треугольник `uplo`, при `Unit` — без диагонали. Как и
+/// эталон, вырожденность `A` не проверяется.
+///
+/// # Safety
+/// Указатели покрывают матрицы указанных размеров, `B` не пересекается с `A`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments, clippy::float_cmp)]
+pub unsafe extern "C" fn cblas_dtrsm(
+    layout: CBLAS_LAYOUT,
+    side: CBLAS_SIDE,
+    uplo: CBLAS_UPLO,
+    trans: CBLAS_TRANSPOSE,
+    diag: CBLAS_DIAG,
+    m: c_int,
+    n: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *mut f64,
+    ldb: c_int,
+) {
+    if let Some(position) = check_dtrmm(layout, side, uplo, trans, diag, m, n, lda, ldb) {
+        xerbla("cblas_dtrsm", position);
+        return;
+    }
+    let [m, n, lda, ldb] = [m, n, lda, ldb].map(dim);
+    if m == 0 || n == 0 {
+        return;
+    }
+
+    let mut b_view = unsafe { matmut(b, layout, m, n, ldb) };
+    scale_matrix(b_view.rb_mut(), alpha);
+    if alpha == 0.0 {
+        return;
+    }
+
+    let left = side == CBLAS_SIDE::Left;
+    let ka = if left { m } else { n };
+    let a_view = unsafe { matref(a, layout, ka, ka, lda) };
+    let transposed = trans != CBLAS_TRANSPOSE::NoTrans;
+    let a_view = if transposed {
+        a_view.transpose()
+    } else {
+        a_view
+    };
+    // Транспонирование переводит нижний треугольник в верхний.
+    let lower = (uplo == CBLAS_UPLO::Lower) != transposed;
+    let unit = diag == CBLAS_DIAG::Unit;
+    if left {
+        solve_triangular(a_view, b_view, lower, unit);
+    } else {
+        // X·op(A) = B  ⇔  op(A)ᵀ·Xᵀ = Bᵀ.
+        solve_triangular(a_view.transpose(), b_view.transpose_mut(), !lower, unit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_int;
+
+    use differentiable::check_gradient;
+
+    use super::*;
+    use crate::gemm_fixture::{K, M, N, c0, pack, point, split};
+    use crate::xerbla::{UNTOUCHED, assert_rejected};
+    use crate::{Gemm, gemm_vjp};
+
+    const LAYOUTS: [CBLAS_LAYOUT; 2] = [CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor];
+    const TRANSPOSES: [CBLAS_TRANSPOSE; 2] = [CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans];
+    const SIDES: [CBLAS_SIDE; 2] = [CBLAS_SIDE::Left, CBLAS_SIDE::Right];
+    const UPLOS: [CBLAS_UPLO; 2] = [CBLAS_UPLO::Upper, CBLAS_UPLO::Lower];
+    const DIAGS: [CBLAS_DIAG; 2] = [CBLAS_DIAG::NonUnit, CBLAS_DIAG::Unit];
+
+    /// Индекс элемента `(row, col)` хранимой матрицы.
+    fn at(layout: CBLAS_LAYOUT, ld: usize, row: usize, col: usize) -> usize {
+        if layout == CBLAS_LAYOUT::RowMajor {
+            row * ld + col
+        } else {
+            row + col * ld
+        }
+    }
+
+    /// Детерминированное заполнение буферов без генератора случайных чисел.
+    fn filled(len: usize, seed: f64) -> Vec<f64> {
+        (0..len)
+            .map(|i| (i as f64).mul_add(seed, 0.3).sin())
+            .collect()
+    }
+
+    /// Буфер хранимой матрицы `rows × cols` с ведущим размером `ld`.
+    fn buffer_len(layout: CBLAS_LAYOUT, ld: usize, rows: usize, cols: usize) -> usize {
+        if layout == CBLAS_LAYOUT::RowMajor {
+            ld * rows
+        } else {
+            ld * cols
+        }
+    }
+
+    #[test]
+    fn all_layout_and_transpose_combinations_match_naive_gemm() {
+        let (m, n, k) = (3, 4, 5);
+        let (alpha, beta) = (1.5, -0.5);
+        for layout in LAYOUTS {
+            for transa in TRANSPOSES {
+                for transb in TRANSPOSES {
+                    let (ta, tb) = (
+                        transa == CBLAS_TRANSPOSE::Trans,
+                        transb == CBLAS_TRANSPOSE::Trans,
+                    );
+                    let (a_rows, a_cols) = if ta { (k, m) } else { (m, k) };
+                    let (b_rows, b_cols) = if tb { (n, k) } else { (k, n) };
+                    // Ведущие размеры с запасом, чтобы проверить страйды.
+                    let pad = |rows: usize, cols: usize| {
+                        (if layout == CBLAS_LAYOUT::RowMajor {
+                            cols
+                        } else {
+                            rows
+                        }) + 2
+                    };
+                    let (lda, ldb, ldc) = (pad(a_rows, a_cols), pad(b_rows, b_cols), pad(m, n));
+                    let a = filled(buffer_len(layout, lda, a_rows, a_cols), 0.7);
+                    let b = filled(buffer_len(layout, ldb, b_rows, b_cols), 1.3);
+                    let mut c = filled(buffer_len(layout, ldc, m, n), 2.1);
+                    let c0 = c.clone();
+
+                    let op_a = |i, p| {
+                        if ta {
+                            a[at(layout, lda, p, i)]
+                        } else {
+                            a[at(layout, lda, i, p)]
+                        }
+                    };
+                    let op_b = |p, j| {
+                        if tb {
+                            b[at(layout, ldb, j, p)]
+                        } else {
+                            b[at(layout, ldb, p, j)]
+                        }
+                    };
+                    let mut expected = c0.clone();
+                    for i in 0..m {
+                        for j in 0..n {
+                            let sum: f64 = (0..k).map(|p| op_a(i, p) * op_b(p, j)).sum();
+                            let index = at(layout, ldc, i, j);
+                            expected[index] = alpha * sum + beta * c0[index];
+                        }
+                    }
+
+                    unsafe {
+                        cblas_dgemm(
+                            layout,
+                            transa,
+                            transb,
+                            m as c_int,
+                            n as c_int,
+                            k as c_int,
+                            alpha,
+                            a.as_ptr(),
+                            lda as c_int,
+                            b.as_ptr(),
+                            ldb as c_int,
+                            beta,
+                            c.as_mut_ptr(),
+                            ldc as c_int,
+                        );
+                    }
+                    // Сравнение всего буфера заодно проверяет, что заполнитель не тронут.
+                    for (actual, expected) in c.iter().zip(&expected) {
+                        assert!(
+                            (actual - expected).abs() < 1e-12,
+                            "{layout:?} {transa:?} {transb:?}: {actual} vs {expected}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_beta_and_alpha_do_not_read_unused_operands() {
+        let (a, b) = ([1.0, 2.0, 3.0, 4.0], [1.0, 0.0, 0.0, 1.0]);
+        let mut c = [f64::NAN; 4];
+        unsafe {
+            cblas_dgemm(
+                CBLAS_LAYOUT::ColMajor,
+                CBLAS_TRANSPOSE::NoTrans,
+                CBLAS_TRANSPOSE::NoTrans,
+                2,
+                2,
+                2,
+                1.0,
+                a.as_ptr(),
+                2,
+                b.as_ptr(),
+                2,
+                0.0,
+                c.as_mut_ptr(),
+                2,
+            );
+        }
+        assert_eq!(c, a);
+
+        // При alpha == 0 A и B не читаются, поэтому годятся и нулевые указатели.
+        unsafe {
+            cblas_dgemm(
+                CBLAS_LAYOUT::ColMajor,
+                CBLAS_TRANSPOSE::NoTrans,
+                CBLAS_TRANSPOSE::NoTrans,
+                2,
+                2,
+                2,
+                0.0,
+                std::ptr::null(),
+                2,
+                std::ptr::null(),
+                2,
+                2.0,
+                c.as_mut_ptr(),
+                2,
+            );
+        }
+        assert_eq!(c, [2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn leading_dimensions_follow_reference_rules() {
+        let (row, col) = (CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor);
+        let (no, tr) = (CBLAS_TRANSPOSE::NoTrans, CBLAS_TRANSPOSE::Trans);
+        // m = 2, n = 3, k = 4.
+        assert_eq!(check_gemm(col, no, no, 2, 3, 4, 2, 4, 2), None);
+        assert_eq!(check_gemm(col, no, no, 2, 3, 4, 1, 4, 2), Some(9));
+        assert_eq!(check_gemm(col, tr, no, 2, 3, 4, 2, 4, 2), Some(9));
+        assert_eq!(check_gemm(col, no, tr, 2, 3, 4, 2, 3, 2), None);
+        assert_eq!(check_gemm(col, no, no, 2, 3, 4, 2, 4, 1), Some(14));
+        assert_eq!(check_gemm(row, no, no, 2, 3, 4, 4, 3, 3), None);
+        assert_eq!(check_gemm(row, no, no, 2, 3, 4, 2, 3, 3), Some(9));
+        assert_eq!(check_gemm(row, tr, tr, 2, 3, 4, 2, 4, 3), None);
+        assert_eq!(check_gemm(row, no, tr, 2, 3, 4, 4, 3, 3), Some(11));
+        assert_eq!(check_gemm(row, no, no, 2, 3, 4, 4, 3, 2), Some(14));
+        // Пустые матрицы всё равно требуют ведущий размер не меньше 1.
+        assert_eq!(check_gemm(col, no, no, 0, 0, 0, 0, 1, 1), Some(9));
+        assert_eq!(check_gemm(col, no, no, -1, 3, 4, 2, 4, 2), Some(4));
+        assert_eq!(
+            check_gemm(CBLAS_LAYOUT(0), no, no, 2, 3, 4, 2, 4, 2),
+            Some(1)
+        );
+        assert_eq!(
+            check_gemm(col, no, CBLAS_TRANSPOSE(7), 2, 3, 4, 2, 4, 2),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_leave_c_untouched() {
+        let (a, b) = ([1.0; 4], [1.0; 4]);
+        let mut c = [5.0; 4];
+        unsafe {
+            cblas_dgemm(
+                CBLAS_LAYOUT::ColMajor,
+                CBLAS_TRANSPOSE::NoTrans,
+                CBLAS_TRANSPOSE::NoTrans,
+                2,
+                2,
+                2,
+                1.0,
+                a.as_ptr(),
+                1,
+                b.as_ptr(),
+                2,
+                0.0,
+                c.as_mut_ptr(),
+                2,
+            );
+        }
+        assert_eq!(c, [5.0; 4]);
+    }
+
+    #[test]
+    fn every_invalid_argument_reaches_xerbla() {
+        let input = [1.0; 16];
+        let (layout, trans) = (CBLAS_LAYOUT, CBLAS_TRANSPOSE);
+        let (side, uplo, diag) = (CBLAS_SIDE, CBLAS_UPLO, CBLAS_DIAG);
+
+        // [layout, transa, transb, m, n, k, lda, ldb, ldc]
+        let gemm_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, -1, 6),
+            (6, 1, 9),
+            (7, 1, 11),
+            (8, 1, 14),
+        ];
+        let gemm_valid = [102, 111, 111, 2, 2, 2, 2, 2, 2];
+        assert_rejected(
+            "cblas_dgemm",
+            gemm_valid,
+            &gemm_cases,
+            |[l, ta, tb, m, n, k, lda, ldb, ldc]| {
+                let mut c = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dgemm(
+                        layout(l),
+                        trans(ta),
+                        trans(tb),
+                        m,
+                        n,
+                        k,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        input.as_ptr(),
+                        ldb,
+                        0.0,
+                        c.as_mut_ptr(),
+                        ldc,
+                    );
+                }
+                c
+            },
+        );
+        let complex_input = [c64::new(1.0, 1.0); 16];
+        let (one, zero) = (c64::new(1.0, 0.0), c64::new(0.0, 0.0));
+        assert_rejected(
+            "cblas_zgemm",
+            gemm_valid,
+            &gemm_cases,
+            |[l, ta, tb, m, n, k, lda, ldb, ldc]| {
+                let mut c = vec![c64::new(UNTOUCHED, UNTOUCHED); 16];
+                unsafe {
+                    cblas_zgemm(
+                        layout(l),
+                        trans(ta),
+                        trans(tb),
+                        m,
+                        n,
+                        k,
+                        &one,
+                        complex_input.as_ptr(),
+                        lda,
+                        complex_input.as_ptr(),
+                        ldb,
+                        &zero,
+                        c.as_mut_ptr(),
+                        ldc,
+                    );
+                }
+                c.iter().flat_map(|z| [z.re, z.im]).collect()
+            },
+        );
+
+        // [layout, side, uplo, m, n, lda, ldb, ldc]
+        let symm_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, 1, 8),
+            (6, 1, 10),
+            (7, 1, 13),
+        ];
+        assert_rejected(
+            "cblas_dsymm",
+            [102, 141, 121, 2, 2, 2, 2, 2],
+            &symm_cases,
+            |[l, sd, u, m, n, lda, ldb, ldc]| {
+                let mut c = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dsymm(
+                        layout(l),
+                        side(sd),
+                        uplo(u),
+                        m,
+                        n,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        input.as_ptr(),
+                        ldb,
+                        0.0,
+                        c.as_mut_ptr(),
+                        ldc,
+                    );
+                }
+                c
+            },
+        );
+
+        // [layout, uplo, trans, n, k, lda, ldc]
+        let syrk_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, 1, 8),
+            (6, 1, 11),
+        ];
+        assert_rejected(
+            "cblas_dsyrk",
+            [102, 121, 111, 2, 2, 2, 2],
+            &syrk_cases,
+            |[l, u, t, n, k, lda, ldc]| {
+                let mut c = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dsyrk(
+                        layout(l),
+                        uplo(u),
+                        trans(t),
+                        n,
+                        k,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        0.0,
+                        c.as_mut_ptr(),
+                        ldc,
+                    );
+                }
+                c
+            },
+        );
+
+        // [layout, uplo, trans, n, k, lda, ldb, ldc]
+        let syr2k_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, -1, 4),
+            (4, -1, 5),
+            (5, 1, 8),
+            (6, 1, 10),
+            (7, 1, 13),
+        ];
+        assert_rejected(
+            "cblas_dsyr2k",
+            [102, 121, 111, 2, 2, 2, 2, 2],
+            &syr2k_cases,
+            |[l, u, t, n, k, lda, ldb, ldc]| {
+                let mut c = vec![UNTOUCHED; 16];
+                unsafe {
+                    cblas_dsyr2k(
+                        layout(l),
+                        uplo(u),
+                        trans(t),
+                        n,
+                        k,
+                        1.0,
+                        input.as_ptr(),
+                        lda,
+                        input.as_ptr(),
+                        ldb,
+                        0.0,
+                        c.as_mut_ptr(),
+                        ldc,
+                    );
+                }
+                c
+            },
+        );
+
+        // [layout, side, uplo, trans, diag, m, n, lda, ldb] — общие для trmm и trsm.
+        let triangular_cases = [
+            (0, 0, 1),
+            (1, 0, 2),
+            (2, 0, 3),
+            (3, 0, 4),
+            (4, 0, 5),
+            (5, -1, 6),
+            (6, -1, 7),
+            (7, 1, 10),
+            (8, 1, 12),
+        ];
+        let routines: [(
+            &str,
+            unsafe extern "C" fn(_, _, _, _, _, _, _, _, _, _, _, _),
+        ); 2] = [("cblas_dtrmm", cblas_dtrmm), ("cblas_dtrsm", cblas_dtrsm)];
+        for (routine, function) in routines {
+            assert_rejected(
+                routine,
+                [102, 141, 121, 111, 131, 2, 2, 2, 2],
+                &triangular_cases,
+                |[l, sd, u, t, d, m, n, lda, ldb]| {
+                    let mut b = vec![UNTOUCHED; 16];
+                    unsafe {
+                        function(
+                            layout(l),
+                            side(sd),
+                            uplo(u),
+                            trans(t),
+                            diag(d),
+                            m,
+                            n,
+                            1.0,
+                            input.as_ptr(),
+                            lda,
+                            b.as_mut_ptr(),
+                            ldb,
+                        );
+                    }
+                    b
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn dgemm_gradient_passes_gradient_check() {
+        let c0 = c0();
+        let loss = |p: &[f64]| {
+            let (alpha, beta, a, b) = split(p);
+            let mut c = c0.clone();
+            unsafe {
+                cblas_dgemm(
+                    CBLAS_LAYOUT::ColMajor,
+                    CBLAS_TRANSPOSE::NoTrans,
+                    CBLAS_TRANSPOSE::NoTrans,
+                    M as c_int,
+                    N as c_int,
+                    K as c_int,
+                    alpha,
+                    a.as_ptr(),
+                    M as c_int,
+                    b.as_ptr(),
+                    K as c_int,
+                    beta,
+                    c.as_mut_ptr(),
+                    M as c_int,
+                );
+            }
+            c.iter().sum::<f64>()
+        };
+        // Эталон — ручные правила `gemm_vjp`, так что проверяются и они.
+        let gradient = |p: &[f64]| {
+            let (alpha, beta, a, b) = split(p);
+            let gemm = Gemm::col_major(M, N, K, alpha, a, b, beta, &c0);
+            let grad = gemm_vjp(&gemm, &vec![1.0; c0.len()]);
+            pack(grad.alpha, grad.beta, &grad.a, &grad.b)
+        };
+
+        check_gradient(loss, gradient, &point()).assert_ok();
+    }
+
+    fn in_triangle(uplo: CBLAS_UPLO, row: usize, col: usize) -> bool {
+        if uplo == CBLAS_UPLO::Lower {
+            row >= col
+        } else {
+            row <= col
+        }
+    }
+
+    /// Квадратная матрица `n × n`, у которой задан только треугольник `uplo`
+    /// (с хорошо обусловленной диагональю), а остальное — `NaN`: так тест
+    /// заметит чтение второго треугольника.
+    fn triangular_matrix(layout: CBLAS_LAYOUT, uplo: CBLAS_UPLO, n: usize, ld: usize) -> Vec<f64> {
+        let mut a = vec![f64::NAN; ld * n];
+        for row in 0..n {
+            for col in 0..n {
+                if in_triangle(uplo, row, col) {
+                    let value = ((row * n + col) as f64).mul_add(0.37, 0.1).sin();
+                    a[at(layout, ld, row, col)] = if row == col { 2.0 + value } else { value };
+                }
+            }
+        }
+        a
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], what: &str) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-12 * expected.abs().max(1.0),
+                "{what}: {actual} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn symm_matches_naive_for_sides_and_triangles() {
+        let (m, n) = (3, 4);
+        let (alpha, beta) = (1.5, -0.5);
+        for layout in LAYOUTS {
+            for side in SIDES {
+                for uplo in UPLOS {
+                    let ka = if side == CBLAS_SIDE::Left { m } else { n };
+                    let lda = ka + 1;
+                    let ld = if layout == CBLAS_LAYOUT::RowMajor {
+                        n
+                    } else {
+                        m
+                    } + 2;
+                    let a = triangular_matrix(layout, uplo, ka, lda);
+                    let symmetric = |row, col| {
+                        if in_triangle(uplo, row, col) {
+                            a[at(layout, lda, row, col)]
+                        } else {
+                            a[at(layout, lda, col, row)]
+                        }
+                    };
+                    let b = filled(buffer_len(layout, ld, m, n), 1.3);
+                    let mut c = filled(buffer_len(layout, ld, m, n), 2.1);
+                    let mut expected = c.clone();
+                    for i in 0..m {
+                        for j in 0..n {
+                            let sum: f64 = if side == CBLAS_SIDE::Left {
+                                (0..m)
+                                    .map(|p| symmetric(i, p) * b[at(layout, ld, p, j)])
+                                    .sum()
+                            } else {
+                                (0..n)
+                                    .map(|p| b[at(layout, ld, i, p)] * symmetric(p, j))
+                                    .sum()
+                            };
+                            let index = at(layout, ld, i, j);
+                            expected[index] = alpha * sum + beta * c[index];
+                        }
+                    }
+
+                    unsafe {
+                        cblas_dsymm(
+                            layout,
+                            side,
+                            uplo,
+                            m as c_int,
+                            n as c_int,
+                            alpha,
+                            a.as_ptr(),
+                            lda as c_int,
+                            b.as_ptr(),
+                            ld as c_int,
+                            beta,
+                            c.as_mut_ptr(),
+                            ld as c_int,
+                        );
+                    }
+                    assert_close(&c, &expected, &format!("{layout:?} {side:?} {uplo:?}"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn syrk_and_syr2k_update_only_the_stored_triangle() {
+        let (n, k) = (4, 3);
+        let (alpha, beta) = (0.5, 2.0);
+        for layout in LAYOUTS {
+            for uplo in UPLOS {
+                for trans in TRANSPOSES {
+                    let transposed = trans == CBLAS_TRANSPOSE::Trans;
+                    let (rows, cols) = if transposed { (k, n) } else { (n, k) };
+                    let ld = if layout == CBLAS_LAYOUT::RowMajor {
+                        cols
+                    } else {
+                        rows
+                    } + 1;
+                    let a = filled(buffer_len(layout, ld, rows, cols), 0.7);
+                    let b = filled(buffer_len(layout, ld, rows, cols), 1.9);
+                    // Элемент `(i, p)` множителя `n × k`.
+                    let factor = |x: &[f64], i, p| {
+                        if transposed {
+                            x[at(layout, ld, p, i)]
+                        } else {
+                            x[at(layout, ld, i, p)]
+                        }
+                    };
+                    let c0 = filled(n * n, 2.3);
+                    let (mut rank_k, mut rank_2k) = (c0.clone(), c0.clone());
+                    let (mut expected_k, mut expected_2k) = (c0.clone(), c0.clone());
+                    for i in 0..n {
+                        for j in 0..n {
+                            if !in_triangle(uplo, i, j) {
+                                continue;
+                            }
+                            let index = at(layout, n, i, j);
+                            let aa: f64 = (0..k).map(|p| factor(&a, i, p) * factor(&a, j, p)).sum();
+                            let ab: f64 = (0..k)
+                                .map(|p| {
+                                    factor(&a, i, p) * factor(&b, j, p)
+                                        + factor(&b, i, p) * factor(&a, j, p)
+                                })
+                                .sum();
+                            expected_k[index] = alpha * aa + beta * c0[index];
+                            expected_2k[index] = alpha * ab + beta * c0[index];
+                        }
+                    }
+
+                    unsafe {
+                        cblas_dsyrk(
+                            layout,
+                            uplo,
+                            trans,
+                            n as c_int,
+                            k as c_int,
+                            alpha,
+                            a.as_ptr(),
+                            ld as c_int,
+                            beta,
+                            rank_k.as_mut_ptr(),
+                            n as c_int,
+                        );
+                        cblas_dsyr2k(
+                            layout,
+                            uplo,
+                            trans,
+                            n as c_int,
+                            k as c_int,
+                            alpha,
+                            a.as_ptr(),
+                            ld as c_int,
+                            b.as_ptr(),
+                            ld as c_int,
+                            beta,
+                            rank_2k.as_mut_ptr(),
+                            n as c_int,
+                        );
+                    }
+                    let what = format!("{layout:?} {uplo:?} {trans:?}");
+                    assert_close(&rank_k, &expected_k, &format!("syrk {what}"));
+                    assert_close(&rank_2k, &expected_2k, &format!("syr2k {what}"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trmm_matches_naive_and_trsm_inverts_it() {
+        let (m, n) = (3, 4);
+        for layout in LAYOUTS {
+            for side in SIDES {
+                for uplo in UPLOS {
+                    for trans in TRANSPOSES {
+                        for diag in DIAGS {
+                            let left = side == CBLAS_SIDE::Left;
+                            let ka = if left { m } else { n };
+                            let lda = ka + 1;
+                            let ldb = if layout == CBLAS_LAYOUT::RowMajor {
+                                n
+                            } else {
+                                m
+                            } + 2;
+                            let a = triangular_matrix(layout, uplo, ka, lda);
+                            let op = |i: usize, j: usize| {
+                                let (row, col) = if trans == CBLAS_TRANSPOSE::Trans {
+                                    (j, i)
+                                } else {
+                                    (i, j)
+                                };
+                                if row == col && diag == CBLAS_DIAG::Unit {
+                                    1.0
+                                } else if in_triangle(uplo, row, col) {
+                                    a[at(layout, lda, row, col)]
+                                } else {
+                                    0.0
+                                }
+                            };
+                            let mut b = filled(buffer_len(layout, ldb, m, n), 1.1);
+                            let original = b.clone();
+                            let mut expected = b.clone();
+                            for i in 0..m {
+                                for j in 0..n {
+                                    let sum: f64 = if left {
+                                        (0..m).map(|p| op(i, p) * b[at(layout, ldb, p, j)]).sum()
+                                    } else {
+                                        (0..n).map(|p| b[at(layout, ldb, i, p)] * op(p, j)).sum()
+                                    };
+                                    expected[at(layout, ldb, i, j)] = 2.0 * sum;
+                                }
+                            }
+                            let what = format!("{layout:?} {side:?} {uplo:?} {trans:?} {diag:?}");
+
+                            unsafe {
+                                cblas_dtrmm(
+                                    layout,
+                                    side,
+                                    uplo,
+                                    trans,
+                                    diag,
+                                    m as c_int,
+                                    n as c_int,
+                                    2.0,
+                                    a.as_ptr(),
+                                    lda as c_int,
+                                    b.as_mut_ptr(),
+                                    ldb as c_int,
+                                );
+                            }
+                            assert_close(&b, &expected, &what);
+
+                            unsafe {
+                                cblas_dtrsm(
+                                    layout,
+                                    side,
+                                    uplo,
+                                    trans,
+                                    diag,
+                                    m as c_int,
+                                    n as c_int,
+                                    0.5,
+                                    a.as_ptr(),
+                                    lda as c_int,
+                                    b.as_mut_ptr(),
+                                    ldb as c_int,
+                                );
+                            }
+                            assert_close(&b, &original, &what);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_and_triangular_arguments_are_reported_by_position() {
+        let (row, col) = (CBLAS_LAYOUT::RowMajor, CBLAS_LAYOUT::ColMajor);
+        let (left, right) = (CBLAS_SIDE::Left, CBLAS_SIDE::Right);
+        let (lower, no, unit) = (
+            CBLAS_UPLO::Lower,
+            CBLAS_TRANSPOSE::NoTrans,
+            CBLAS_DIAG::Unit,
+        );
+        // m = 2, n = 3: A порядка m слева и n справа.
+        assert_eq!(check_dsymm(col, left, lower, 2, 3, 2, 2, 2), None);
+        assert_eq!(check_dsymm(col, right, lower, 2, 3, 2, 2, 2), Some(8));
+        assert_eq!(check_dsymm(row, left, lower, 2, 3, 2, 2, 3), Some(10));
+        assert_eq!(
+            check_dsymm(col, CBLAS_SIDE(0), lower, 2, 3, 2, 2, 2),
+            Some(2)
+        );
+        assert_eq!(
+            check_dsymm(col, left, CBLAS_UPLO(0), 2, 3, 2, 2, 2),
+            Some(3)
+        );
+        // n = 3, k = 2.
+        assert_eq!(check_dsyrk(col, lower, no, 3, 2, 3, 3), None);
+        assert_eq!(check_dsyrk(row, lower, no, 3, 2, 3, 3), None);
+        assert_eq!(
+            check_dsyrk(row, lower, CBLAS_TRANSPOSE::Trans, 3, 2, 2, 3),
+            Some(8)
+        );
+        assert_eq!(check_dsyrk(col, lower, no, 3, 2, 3, 2), Some(11));
+        assert_eq!(check_dsyr2k(col, lower, no, 3, -1, 3, 3, 3), Some(5));
+        assert_eq!(check_dsyr2k(col, lower, no, 3, 2, 3, 2, 3), Some(10));
+        assert_eq!(check_dtrmm(col, right, lower, no, unit, 2, 3, 3, 2), None);
+        assert_eq!(
+            check_dtrmm(col, right, lower, no, CBLAS_DIAG(0), 2, 3, 3, 2),
+            Some(5)
+        );
+        assert_eq!(
+            check_dtrmm(col, left, lower, no, unit, 2, 3, 1, 2),
+            Some(10)
+        );
+        assert_eq!(
+            check_dtrmm(row, left, lower, no, unit, 2, 3, 2, 2),
+            Some(12)
+        );
+    }
+
+    /// Комплексная матрица с ненулевыми мнимыми частями.
+    fn complex(len: usize, seed: f64) -> Vec<c64> {
+        filled(len, seed)
+            .into_iter()
+            .zip(filled(len, seed + 0.5))
+            .map(|(re, im)| c64::new(re, im))
+            .collect()
+    }
+
+    #[test]
+    fn zgemm_matches_naive_for_all_transpose_and_conjugate_combinations() {
+        let (m, n, k) = (2, 3, 4);
+        let (alpha, beta) = (c64::new(0.5, -1.0), c64::new(-0.25, 2.0));
+        let all = [
+            CBLAS_TRANSPOSE::NoTrans,
+            CBLAS_TRANSPOSE::Trans,
+            CBLAS_TRANSPOSE::ConjTrans,
+        ];
+        // `op(X)(i, j)` по хранимой матрице и её ведущему размеру.
+        let op = |x: &[c64], layout, ld, trans, i, j| {
+            if trans == CBLAS_TRANSPOSE::NoTrans {
+                x[at(layout, ld, i, j)]
+            } else if trans == CBLAS_TRANSPOSE::Trans {
+                x[at(layout, ld, j, i)]
+            } else {
+                x[at(layout, ld, j, i)].conj()
+            }
+        };
+        for layout in LAYOUTS {
+            for transa in all {
+                for transb in all {
+                    let ld = 5;
+                    let a = complex(ld * 5, 0.7);
+                    let b = complex(ld * 5, 1.3);
+                    let mut c = complex(buffer_len(layout, ld, m, n), 2.1);
+                    let mut expected = c.clone();
+                    for i in 0..m {
+                        for j in 0..n {
+                            let sum: c64 = (0..k)
+                                .map(|p| {
+                                    op(&a, layout, ld, transa, i, p)
+                                        * op(&b, layout, ld, transb, p, j)
+                                })
+                                .sum();
+                            let index = at(layout, ld, i, j);
+                            expected[index] = alpha * sum + beta * c[index];
+                        }
+                    }
+
+                    unsafe {
+                        cblas_zgemm(
+                            layout,
+                            transa,
+                            transb,
+                            m as c_int,
+                            n as c_int,
+                            k as c_int,
+                            &alpha,
+                            a.as_ptr(),
+                            ld as c_int,
+                            b.as_ptr(),
+                            ld as c_int,
+                            &beta,
+                            c.as_mut_ptr(),
+                            ld as c_int,
+                        );
+                    }
+                    for (actual, expected) in c.iter().zip(&expected) {
+                        assert!(
+                            (actual - expected).norm() < 1e-12,
+                            "{layout:?} {transa:?} {transb:?}: {actual} vs {expected}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_precision_and_real_conjugate_variants_agree_with_double() {
+        let (layout, no, ct) = (
+            CBLAS_LAYOUT::ColMajor,
+            CBLAS_TRANSPOSE::NoTrans,
+            CBLAS_TRANSPOSE::ConjTrans,
+        );
+        let a = filled(9, 0.7);
+        let b = filled(9, 1.3);
+        let (mut trans, mut conj_trans) = (vec![0.0; 9], vec![0.0; 9]);
+        let a32: Vec<f32> = a.iter().map(|&v| v as f32).collect();
+        let b32: Vec<f32> = b.iter().map(|&v| v as f32).collect();
+        let mut single = vec![0.0_f32; 9];
+        let za: Vec<c64> = a.iter().map(|&v| c64::new(v, -v)).collect();
+        let ca: Vec<c32> = za
+            .iter()
+            .map(|v| c32::new(v.re as f32, v.im as f32))
+            .collect();
+        let (mut zc, mut cc) = (vec![c64::new(0.0, 0.0); 9], vec![c32::new(0.0, 0.0); 9]);
+        let (one, zero) = (c64::new(1.0, 0.0), c64::new(0.0, 0.0));
+        let (one32, zero32) = (c32::new(1.0, 0.0), c32::new(0.0, 0.0));
+        unsafe {
+            let tr = CBLAS_TRANSPOSE::Trans;
+            cblas_dgemm(
+                layout,
+                tr,
+                no,
+                3,
+                3,
+                3,
+                1.0,
+                a.as_ptr(),
+                3,
+                b.as_ptr(),
+                3,
+                0.0,
+                trans.as_mut_ptr(),
+                3,
+            );
+            cblas_dgemm(
+                layout,
+                ct,
+                no,
+                3,
+                3,
+                3,
+                1.0,
+                a.as_ptr(),
+                3,
+                b.as_ptr(),
+                3,
+                0.0,
+                conj_trans.as_mut_ptr(),
+                3,
+            );
+            cblas_sgemm(
+                layout,
+                ct,
+                no,
+                3,
+                3,
+                3,
+                1.0,
+                a32.as_ptr(),
+                3,
+                b32.as_ptr(),
+                3,
+                0.0,
+                single.as_mut_ptr(),
+                3,
+            );
+            cblas_zgemm(
+                layout,
+                ct,
+                no,
+                3,
+                3,
+                3,
+                &one,
+                za.as_ptr(),
+                3,
+                za.as_ptr(),
+                3,
+                &zero,
+                zc.as_mut_ptr(),
+                3,
+            );
+            cblas_cgemm(
+                layout,
+                ct,
+                no,
+                3,
+                3,
+                3,
+                &one32,
+                ca.as_ptr(),
+                3,
+                ca.as_ptr(),
+                3,
+                &zero32,
+                cc.as_mut_ptr(),
+                3,
+            );
+        }
+        assert_eq!(trans, conj_trans);
+        for (double, single) in trans.iter().zip(&single) {
+            assert!(
+                (double - f64::from(*single)).abs() < 1e-5,
+                "{double} vs {single}"
+            );
+        }
+        // Aᴴ·A эрмитова и для A = (1 - i)·R равна 2·RᵀR.
+        let gram: Vec<f64> = {
+            let mut gram = vec![0.0; 9];
+            unsafe {
+                let tr = CBLAS_TRANSPOSE::Trans;
+                cblas_dgemm(
+                    layout,
+                    tr,
+                    no,
+                    3,
+                    3,
+                    3,
+                    2.0,
+                    a.as_ptr(),
+                    3,
+                    a.as_ptr(),
+                    3,
+                    0.0,
+                    gram.as_mut_ptr(),
+                    3,
+                );
+            }
+            gram
+        };
+        for ((z, c), expected) in zc.iter().zip(&cc).zip(&gram) {
+            assert!(
+                (z - c64::new(*expected, 0.0)).norm() < 1e-12,
+                "{z} vs {expected}"
+            );
+            assert!(
+                (f64::from(c.re) - expected).abs() < 1e-5 && c.im.abs() < 1e-5,
+                "{c} vs {expected}"
+            );
+        }
+    }
+}
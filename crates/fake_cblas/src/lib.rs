@@ -0,0 +1,306 @@
+//! Учебная реализация CBLAS поверх `faer` с ABI C.
+//!
+//! Библиотека собирается стабильным компилятором; Enzyme нужен только
+//! демонстрационному бинарнику `main.rs`.
+
+use core::ffi::c_int;
+use core::ops::{Mul, MulAssign};
+
+use faer::linalg::matmul::triangular::BlockStructure;
+use faer::linalg::triangular_solve::{
+    solve_lower_triangular_in_place, solve_unit_lower_triangular_in_place,
+    solve_unit_upper_triangular_in_place, solve_upper_triangular_in_place,
+};
+use faer::prelude::ReborrowMut;
+use faer::{c32, c64, mat};
+
+pub mod adjoint;
+pub mod batch;
+#[cfg(test)]
+mod gemm_fixture;
+pub mod level1;
+pub mod level2;
+pub mod level3;
+pub mod threads;
+pub mod xerbla;
+
+pub use adjoint::{Gemm, GemmGradient, GemmTangent, gemm_jvp, gemm_vjp};
+pub use batch::{cblas_dgemm_batch, cblas_dgemm_batch_strided};
+pub use level1::*;
+pub use level2::*;
+pub use level3::*;
+pub use threads::{NUM_THREADS_ENV, fake_cblas_get_num_threads, fake_cblas_set_num_threads};
+pub use xerbla::{XerblaHandler, fake_cblas_default_xerbla, fake_cblas_set_xerbla};
+
+use threads::par;
+use xerbla::xerbla;
+
+// Минимальные CBLAS enum-ы с числовыми значениями как в cblas.h:
+// CBLAS_LAYOUT { RowMajor=101, ColMajor=102 },
+// CBLAS_TRANSPOSE { NoTrans=111, Trans=112, ConjTrans=113 },
+// CBLAS_UPLO { Upper=121, Lower=122 },
+// CBLAS_DIAG { NonUnit=131, Unit=132 },
+// CBLAS_SIDE { Left=141, Right=142 }.
+//
+// Это обёртки над `c_int`, а не Rust-`enum`: из C может прийти любое число, и
+// его нужно уметь отвергнуть, а не получить неопределённое поведение.
+
+/// Раскладка матриц в памяти.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CBLAS_LAYOUT(pub c_int);
+
+#[allow(non_upper_case_globals)]
+impl CBLAS_LAYOUT {
+    pub const RowMajor: Self = Self(101);
+    pub const ColMajor: Self = Self(102);
+
+    pub(crate) fn is_valid(self) -> bool {
+        self == Self::RowMajor || self == Self::ColMajor
+    }
+}
+
+/// Операция `op(X)` над матрицей-аргументом.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CBLAS_TRANSPOSE(pub c_int);
+
+#[allow(non_upper_case_globals)]
+impl CBLAS_TRANSPOSE {
+    pub const NoTrans: Self = Self(111);
+    pub const Trans: Self = Self(112);
+    /// Эрмитово сопряжение; для вещественных матриц совпадает с `Trans`.
+    pub const ConjTrans: Self = Self(113);
+
+    /// `Some(true)` для транспонирования (в том числе с сопряжением), `None`
+    /// для неизвестного значения.
+    pub(crate) const fn is_transposed(self) -> Option<bool> {
+        match self {
+            Self::NoTrans => Some(false),
+            Self::Trans | Self::ConjTrans => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Какой треугольник симметричной или треугольной матрицы хранится.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CBLAS_UPLO(pub c_int);
+
+#[allow(non_upper_case_globals)]
+impl CBLAS_UPLO {
+    pub const Upper: Self = Self(121);
+    pub const Lower: Self = Self(122);
+
+    /// `Some(true)` для нижнего треугольника, `None` для неизвестного значения.
+    pub(crate) const fn is_lower(self) -> Option<bool> {
+        match self {
+            Self::Upper => Some(false),
+            Self::Lower => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Единичная ли диагональ у треугольной матрицы (тогда она не читается).
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CBLAS_DIAG(pub c_int);
+
+#[allow(non_upper_case_globals)]
+impl CBLAS_DIAG {
+    pub const NonUnit: Self = Self(131);
+    pub const Unit: Self = Self(132);
+
+    /// `Some(true)` для единичной диагонали, `None` для неизвестного значения.
+    pub(crate) const fn is_unit(self) -> Option<bool> {
+        match self {
+            Self::NonUnit => Some(false),
+            Self::Unit => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// С какой стороны стоит особая (симметричная или треугольная) матрица.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CBLAS_SIDE(pub c_int);
+
+#[allow(non_upper_case_globals)]
+impl CBLAS_SIDE {
+    pub const Left: Self = Self(141);
+    pub const Right: Self = Self(142);
+
+    /// `Some(true)` для умножения слева, `None` для неизвестного значения.
+    pub(crate) const fn is_left(self) -> Option<bool> {
+        match self {
+            Self::Left => Some(true),
+            Self::Right => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Тип элементов матриц: `f32` (`s*`), `f64` (`d*`), `c32` (`c*`), `c64` (`z*`).
+pub(crate) trait Element:
+    faer::traits::ComplexField + Copy + PartialEq + Mul<Output = Self> + MulAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+impl Element for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+}
+
+impl Element for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+}
+
+impl Element for c32 {
+    const ZERO: Self = c32::new(0.0, 0.0);
+    const ONE: Self = c32::new(1.0, 0.0);
+}
+
+impl Element for c64 {
+    const ZERO: Self = c64::new(0.0, 0.0);
+    const ONE: Self = c64::new(1.0, 0.0);
+}
+
+/// Вещественный тип элементов: `f32` для `s*`-функций, `f64` для `d*`.
+pub(crate) trait Real: Element + faer::traits::RealField {}
+
+impl Real for f32 {}
+impl Real for f64 {}
+
+/// Минимальный ведущий размер матрицы `rows × cols` по правилам эталонного
+/// BLAS: длина столбца для `ColMajor`, длина строки для `RowMajor`, но не меньше 1.
+pub(crate) fn min_ld(layout: CBLAS_LAYOUT, rows: c_int, cols: c_int) -> c_int {
+    let length = if layout == CBLAS_LAYOUT::RowMajor {
+        cols
+    } else {
+        rows
+    };
+    length.max(1)
+}
+
+/// Неотрицательный `c_int` после проверки аргументов.
+pub(crate) const fn dim(value: c_int) -> usize {
+    value.unsigned_abs() as usize
+}
+
+/// Вид на матрицу `rows × cols`, хранящуюся с ведущим размером `ld` в данной
+/// раскладке.
+///
+/// # Safety
+/// `ptr` должен указывать на `ld · (cols - 1) + rows` (`ColMajor`) или
+/// `ld · (rows - 1) + cols` (`RowMajor`) валидных элементов, если матрица
+/// непуста.
+pub(crate) const unsafe fn matref<'a, T>(
+    ptr: *const T,
+    layout: CBLAS_LAYOUT,
+    rows: usize,
+    cols: usize,
+    ld: usize,
+) -> mat::MatRef<'a, T> {
+    let (row_stride, col_stride) = strides(layout, ld);
+    unsafe { mat::MatRef::from_raw_parts(ptr, rows, cols, row_stride, col_stride) }
+}
+
+/// Изменяемый вариант [`matref`].
+///
+/// # Safety
+/// Те же требования, что у [`matref`], плюс отсутствие aliasing.
+pub(crate) const unsafe fn matmut<'a, T>(
+    ptr: *mut T,
+    layout: CBLAS_LAYOUT,
+    rows: usize,
+    cols: usize,
+    ld: usize,
+) -> mat::MatMut<'a, T> {
+    let (row_stride, col_stride) = strides(layout, ld);
+    unsafe { mat::MatMut::from_raw_parts_mut(ptr, rows, cols, row_stride, col_stride) }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+const fn strides(layout: CBLAS_LAYOUT, ld: usize) -> (isize, isize) {
+    // `ld` пришёл из неотрицательного `c_int`, переполнения нет.
+    if layout.0 == CBLAS_LAYOUT::RowMajor.0 {
+        (ld as isize, 1)
+    } else {
+        (1, ld as isize)
+    }
+}
+
+/// Вектор длины `n` с шагом `inc` как столбец `n × 1`. При отрицательном шаге
+/// вид начинается с последнего элемента буфера и идёт назад, что совпадает с
+/// соглашением BLAS из [`level1`].
+///
+/// # Safety
+/// `ptr` указывает на `1 + (n - 1)·|inc|` валидных элементов.
+pub(crate) unsafe fn vecref<'a, T>(ptr: *const T, n: usize, inc: c_int) -> mat::MatRef<'a, T> {
+    let start = if n == 0 { 0 } else { level1::offset(0, n, inc) };
+    unsafe { mat::MatRef::from_raw_parts(ptr.add(start), n, 1, inc as isize, 0) }
+}
+
+/// Изменяемый вариант [`vecref`].
+///
+/// # Safety
+/// Те же требования, что у [`vecref`], плюс отсутствие aliasing.
+pub(crate) unsafe fn vecmut<'a, T>(ptr: *mut T, n: usize, inc: c_int) -> mat::MatMut<'a, T> {
+    let start = if n == 0 { 0 } else { level1::offset(0, n, inc) };
+    unsafe { mat::MatMut::from_raw_parts_mut(ptr.add(start), n, 1, inc as isize, 0) }
+}
+
+/// Структура хранимого треугольника для треугольного `matmul` из `faer`.
+pub(crate) const fn triangle(lower: bool, unit: bool) -> BlockStructure {
+    match (lower, unit) {
+        (true, false) => BlockStructure::TriangularLower,
+        (true, true) => BlockStructure::UnitTriangularLower,
+        (false, false) => BlockStructure::TriangularUpper,
+        (false, true) => BlockStructure::UnitTriangularUpper,
+    }
+}
+
+/// Решает `tri · X = rhs` на месте `rhs`, читая из `tri` только треугольник
+/// `lower` и, при `unit`, без диагонали.
+pub(crate) fn solve_triangular<T: Element>(
+    tri: mat::MatRef<'_, T>,
+    rhs: mat::MatMut<'_, T>,
+    lower: bool,
+    unit: bool,
+) {
+    match (lower, unit) {
+        (true, false) => solve_lower_triangular_in_place(tri, rhs, par()),
+        (true, true) => solve_unit_lower_triangular_in_place(tri, rhs, par()),
+        (false, false) => solve_upper_triangular_in_place(tri, rhs, par()),
+        (false, true) => solve_unit_upper_triangular_in_place(tri, rhs, par()),
+    }
+}
+
+/// `view := beta · view`; при `beta == 0` старое содержимое не читается, чтобы
+/// `NaN` в неинициализированном `C` не просачивались в результат.
+pub(crate) fn scale_matrix<T: Element>(mut view: mat::MatMut<'_, T>, beta: T) {
+    if beta == T::ONE {
+        return;
+    }
+
+    if beta == T::ZERO {
+        faer::zip!(view.rb_mut().as_dyn_mut()).for_each(|faer::unzip!(elem)| {
+            *elem = T::ZERO;
+        });
+    } else {
+        faer::zip!(view.rb_mut().as_dyn_mut()).for_each(|faer::unzip!(elem)| {
+            *elem *= beta;
+        });
+    }
+}
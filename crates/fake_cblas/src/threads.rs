@@ -0,0 +1,91 @@
+//! Число потоков для ядер `faer`.
+//!
+//! По умолчанию вычисления последовательные (`Par::Seq`): через такой код
+//! проходит Enzyme, а малые матрицы не платят за синхронизацию. Переменная
+//! окружения `FAKE_CBLAS_NUM_THREADS` задаёт начальное значение при первом
+//! вызове BLAS, а [`fake_cblas_set_num_threads`] меняет его во время работы,
+//! как `openblas_set_num_threads`. Значение `0` означает все потоки пула `rayon`.
+
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use faer::Par;
+
+/// Имя переменной окружения с начальным числом потоков.
+pub const NUM_THREADS_ENV: &str = "FAKE_CBLAS_NUM_THREADS";
+
+/// Значение ещё не прочитано из окружения.
+const UNSET: usize = usize::MAX;
+
+static NUM_THREADS: AtomicUsize = AtomicUsize::new(UNSET);
+
+/// Число потоков из значения переменной окружения; отсутствующее или
+/// нечисловое значение даёт последовательное выполнение.
+fn parse_threads(value: Option<&str>) -> usize {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Запрошенное число потоков (`0` — все потоки пула).
+fn requested_threads() -> usize {
+    let current = NUM_THREADS.load(Ordering::Relaxed);
+    if current != UNSET {
+        return current;
+    }
+    let from_env = parse_threads(std::env::var(NUM_THREADS_ENV).ok().as_deref());
+    // Если сеттер успел раньше, его значение важнее окружения.
+    match NUM_THREADS.compare_exchange(UNSET, from_env, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => from_env,
+        Err(set) => set,
+    }
+}
+
+/// Параллелизм `faer` для запрошенного числа потоков.
+fn par_for(threads: usize) -> Par {
+    match threads {
+        1 => Par::Seq,
+        n => Par::rayon(n),
+    }
+}
+
+/// Текущий параллелизм для всех ядер библиотеки.
+pub(crate) fn par() -> Par {
+    par_for(requested_threads())
+}
+
+/// Задаёт число потоков для последующих вызовов; `n <= 0` означает все потоки
+/// пула `rayon`, `1` — последовательное выполнение.
+#[unsafe(no_mangle)]
+pub extern "C" fn fake_cblas_set_num_threads(n: c_int) {
+    let threads = usize::try_from(n).unwrap_or(0);
+    NUM_THREADS.store(threads, Ordering::Relaxed);
+}
+
+/// Фактическое число потоков, которое получат ядра `faer`.
+#[unsafe(no_mangle)]
+pub extern "C" fn fake_cblas_get_num_threads() -> c_int {
+    c_int::try_from(par().degree()).unwrap_or(c_int::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{par_for, parse_threads};
+    use faer::Par;
+
+    #[test]
+    fn environment_value_is_parsed_leniently() {
+        assert_eq!(parse_threads(None), 1);
+        assert_eq!(parse_threads(Some(" 4 ")), 4);
+        assert_eq!(parse_threads(Some("0")), 0);
+        assert_eq!(parse_threads(Some("many")), 1);
+        assert_eq!(parse_threads(Some("-2")), 1);
+    }
+
+    #[test]
+    fn one_thread_is_sequential_and_zero_uses_the_whole_pool() {
+        assert!(matches!(par_for(1), Par::Seq));
+        assert_eq!(par_for(3).degree(), 3);
+        assert_eq!(par_for(0).degree(), Par::rayon(0).degree());
+    }
+}
@@ -0,0 +1,150 @@
+//! Сообщения о неверных аргументах.
+//!
+//! Все функции проверяют аргументы по правилам эталонного BLAS и при ошибке
+//! ничего не вычисляют, а сообщают номер параметра (с единицы, считая
+//! `layout`) и имя функции обработчику. По умолчанию это
+//! [`fake_cblas_default_xerbla`], который печатает сообщение эталонного
+//! формата в stderr.
+//!
+//! В эталонном CBLAS обработчик — символ `cblas_xerbla`, который заменяют
+//! своим при линковке. Здесь этот символ не экспортируется: эталонная
+//! сигнатура вариативная (`int p, const char *rout, const char *form, ...`),
+//! а такие функции стабильный Rust определять не умеет. Подмена символа у
+//! статической библиотеки Rust к тому же дала бы конфликт, поэтому обработчик
+//! устанавливается во время работы через [`fake_cblas_set_xerbla`].
+
+use core::ffi::{CStr, c_char, c_int};
+use core::sync::atomic::{AtomicPtr, Ordering};
+use std::ffi::CString;
+
+/// Обработчик неверного аргумента: номер параметра и имя функции
+/// (NUL-терминированная строка, действительная только во время вызова).
+pub type XerblaHandler = unsafe extern "C" fn(position: c_int, routine: *const c_char);
+
+/// Установленный обработчик; нулевой указатель — [`fake_cblas_default_xerbla`].
+static HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Обработчик по умолчанию: печатает
+/// `Parameter <position> to routine <routine> was incorrect` в stderr.
+///
+/// # Safety
+/// `routine` — нулевой указатель или NUL-терминированная строка.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fake_cblas_default_xerbla(position: c_int, routine: *const c_char) {
+    let routine = if routine.is_null() {
+        "?".into()
+    } else {
+        unsafe { CStr::from_ptr(routine) }.to_string_lossy()
+    };
+    eprintln!("Parameter {position} to routine {routine} was incorrect");
+}
+
+/// Устанавливает обработчик неверных аргументов для всех потоков и возвращает
+/// предыдущий (`NULL`, если действовал [`fake_cblas_default_xerbla`]).
+/// `NULL` возвращает обработчик по умолчанию.
+#[unsafe(no_mangle)]
+pub extern "C" fn fake_cblas_set_xerbla(handler: Option<XerblaHandler>) -> Option<XerblaHandler> {
+    let new = handler.map_or(core::ptr::null_mut(), |handler| handler as *mut ());
+    let previous = HANDLER.swap(new, Ordering::AcqRel);
+    // В `HANDLER` попадают только указатели на `XerblaHandler`.
+    (!previous.is_null())
+        .then(|| unsafe { core::mem::transmute::<*mut (), XerblaHandler>(previous) })
+}
+
+/// Сообщает о неверном параметре `position` функции `routine` текущему
+/// обработчику.
+pub(crate) fn xerbla(routine: &str, position: c_int) {
+    let routine = CString::new(routine).expect("routine names contain no NUL bytes");
+    let current = HANDLER.load(Ordering::Acquire);
+    let handler = if current.is_null() {
+        fake_cblas_default_xerbla
+    } else {
+        unsafe { core::mem::transmute::<*mut (), XerblaHandler>(current) }
+    };
+    unsafe { handler(position, routine.as_ptr()) };
+}
+
+/// Значение, которым тесты заполняют выходные буферы перед неверным вызовом.
+#[cfg(test)]
+pub(crate) const UNTOUCHED: f64 = 7.0;
+
+#[cfg(test)]
+std::thread_local! {
+    static REPORTED: core::cell::RefCell<Vec<(String, c_int)>> =
+        const { core::cell::RefCell::new(Vec::new()) };
+}
+
+/// Тестовый обработчик: запоминает сообщения в потоке, где случилась ошибка.
+/// Проверка аргументов идёт до параллельной части, поэтому это поток теста.
+#[cfg(test)]
+unsafe extern "C" fn record(position: c_int, routine: *const c_char) {
+    let routine = unsafe { CStr::from_ptr(routine) }
+        .to_str()
+        .expect("routine name is UTF-8")
+        .to_owned();
+    REPORTED.with_borrow_mut(|reported| reported.push((routine, position)));
+}
+
+/// Сообщения об ошибках, выданные во время `run`. Тестовый обработчик
+/// остаётся установленным: все тесты ставят один и тот же.
+#[cfg(test)]
+pub(crate) fn reported<R>(run: impl FnOnce() -> R) -> (Vec<(String, c_int)>, R) {
+    fake_cblas_set_xerbla(Some(record));
+    REPORTED.take();
+    let result = run();
+    (REPORTED.take(), result)
+}
+
+/// Проверяет каждый путь отказа функции `routine`: `call` получает целые
+/// аргументы (значения `CBLAS_*` и размеры) и возвращает выходной буфер,
+/// заполненный [`UNTOUCHED`]. Корректные `valid` не должны давать сообщений,
+/// а замена `args[index] = value` из `cases` — ровно одно сообщение с номером
+/// `position` и нетронутый выход.
+#[cfg(test)]
+pub(crate) fn assert_rejected<const N: usize>(
+    routine: &str,
+    valid: [c_int; N],
+    cases: &[(usize, c_int, c_int)],
+    call: impl Fn([c_int; N]) -> Vec<f64>,
+) {
+    let (errors, _) = reported(|| call(valid));
+    assert!(
+        errors.is_empty(),
+        "{routine}: valid call reported {errors:?}"
+    );
+    for &(index, value, position) in cases {
+        let mut args = valid;
+        args[index] = value;
+        let (errors, output) = reported(|| call(args));
+        assert_eq!(
+            errors,
+            [(routine.to_owned(), position)],
+            "{routine}: argument {index} = {value}"
+        );
+        assert!(
+            output.iter().all(|&x| x == UNTOUCHED),
+            "{routine}: output changed after invalid argument {index} = {value}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_char;
+
+    use super::{fake_cblas_default_xerbla, fake_cblas_set_xerbla, record, reported, xerbla};
+
+    #[test]
+    fn handler_receives_routine_and_position() {
+        let (errors, ()) = reported(|| xerbla("cblas_dgemm", 9));
+        assert_eq!(errors, [("cblas_dgemm".to_owned(), 9)]);
+
+        // Повторная установка возвращает предыдущий обработчик.
+        assert!(fake_cblas_set_xerbla(Some(record)).is_some());
+    }
+
+    #[test]
+    fn default_handler_accepts_null_routine() {
+        unsafe { fake_cblas_default_xerbla(3, core::ptr::null::<c_char>()) };
+    }
+}
@@ -0,0 +1,156 @@
+/* Вызовы fake_cblas из C: по одной проверке на каждый уровень BLAS. */
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "cblas.h"
+
+static int reported_position;
+static char reported_routine[64];
+
+static void record_xerbla(int p, const char *rout) {
+    reported_position = p;
+    snprintf(reported_routine, sizeof reported_routine, "%s", rout);
+}
+
+int main(void) {
+    /* A — 2×3, B — 3×2, построчно. */
+    const double a[] = {1, 2, 3, 4, 5, 6};
+    const double b[] = {7, 8, 9, 10, 11, 12};
+    double c[] = {1, 1, 1, 1};
+    const double expected[] = {58 + 0.5, 64 + 0.5, 139 + 0.5, 154 + 0.5};
+
+    cblas_dgemm(CblasRowMajor, CblasNoTrans, CblasNoTrans, 2, 2, 3, 1.0, a, 3, b, 2, 0.5, c, 2);
+    for (int i = 0; i < 4; ++i) {
+        if (fabs(c[i] - expected[i]) > 1e-12) {
+            fprintf(stderr, "c[%d] = %f, expected %f\n", i, c[i], expected[i]);
+            return 1;
+        }
+    }
+
+    /* lda = 2 меньше длины строки A: C не должна измениться. */
+    cblas_dgemm(CblasRowMajor, CblasNoTrans, CblasNoTrans, 2, 2, 3, 1.0, a, 2, b, 2, 0.0, c, 2);
+    for (int i = 0; i < 4; ++i) {
+        if (c[i] != expected[i]) {
+            fprintf(stderr, "c[%d] changed after invalid call\n", i);
+            return 1;
+        }
+    }
+
+    /* Свой обработчик получает ту же ошибку вместо сообщения в stderr. */
+    if (fake_cblas_set_xerbla(record_xerbla) != NULL) {
+        fprintf(stderr, "unexpected xerbla handler installed\n");
+        return 1;
+    }
+    cblas_dgemm(CblasRowMajor, CblasNoTrans, CblasNoTrans, 2, 2, 3, 1.0, a, 2, b, 2, 0.0, c, 2);
+    if (reported_position != 9 || strcmp(reported_routine, "cblas_dgemm") != 0) {
+        fprintf(stderr, "xerbla handler got %d in %s\n", reported_position, reported_routine);
+        return 1;
+    }
+    if (fake_cblas_set_xerbla(fake_cblas_default_xerbla) != record_xerbla ||
+        fake_cblas_set_xerbla(NULL) != fake_cblas_default_xerbla) {
+        fprintf(stderr, "previous xerbla handler was not returned\n");
+        return 1;
+    }
+
+    /* Level 1: отрицательный шаг обходит y с конца, y = [6, 5, 4]. */
+    const double x[] = {1, 2, 3};
+    const double y[] = {4, -1, 5, -1, 6};
+    if (cblas_ddot(3, x, 1, y, -2) != 28.0 || cblas_idamax(3, x, 1) != 2) {
+        fprintf(stderr, "level 1 mismatch\n");
+        return 1;
+    }
+
+    /* Level 2: x := L·x для L = [[2, 0], [1, 3]] по столбцам, затем решение L·z = x возвращает исходный x. */
+    const double l[] = {2, 1, 0, 3};
+    double v[] = {1, 2};
+    cblas_dtrmv(CblasColMajor, CblasLower, CblasNoTrans, CblasNonUnit, 2, l, 2, v, 1);
+    if (v[0] != 2.0 || v[1] != 7.0) {
+        fprintf(stderr, "trmv mismatch: %f %f\n", v[0], v[1]);
+        return 1;
+    }
+    cblas_dtrsv(CblasColMajor, CblasLower, CblasNoTrans, CblasNonUnit, 2, l, 2, v, 1);
+    if (fabs(v[0] - 1.0) > 1e-12 || fabs(v[1] - 2.0) > 1e-12) {
+        fprintf(stderr, "trsv mismatch: %f %f\n", v[0], v[1]);
+        return 1;
+    }
+
+    /* Level 3: B := B·op(A) справа и обратное решение возвращают исходную строку. */
+    double row[] = {1, 2};
+    cblas_dtrmm(CblasRowMajor, CblasRight, CblasLower, CblasTrans, CblasNonUnit, 1, 2, 1.0, l, 2,
+                row, 2);
+    cblas_dtrsm(CblasRowMajor, CblasRight, CblasLower, CblasTrans, CblasNonUnit, 1, 2, 1.0, l, 2,
+                row, 2);
+    if (fabs(row[0] - 1.0) > 1e-12 || fabs(row[1] - 2.0) > 1e-12) {
+        fprintf(stderr, "trmm/trsm mismatch: %f %f\n", row[0], row[1]);
+        return 1;
+    }
+
+    /* zgemm: conj(1 + 2i)·(3 + 4i) = 11 - 2i. */
+    const double one[] = {1, 0}, zero[] = {0, 0};
+    const double za[] = {1, 2}, zb[] = {3, 4};
+    double zc[] = {-1, -1};
+    cblas_zgemm(CblasColMajor, CblasConjTrans, CblasNoTrans, 1, 1, 1, one, za, 1, zb, 1, zero, zc,
+                1);
+    if (zc[0] != 11.0 || zc[1] != -2.0) {
+        fprintf(stderr, "zgemm mismatch: %f %+fi\n", zc[0], zc[1]);
+        return 1;
+    }
+
+    /* Потоки: тест запускает программу с FAKE_CBLAS_NUM_THREADS=2. */
+    if (fake_cblas_get_num_threads() != 2) {
+        fprintf(stderr, "expected 2 threads from the environment, got %d\n",
+                fake_cblas_get_num_threads());
+        return 1;
+    }
+    enum { N = 96 };
+    static double pa[N * N], pb[N * N], seq[N * N], par[N * N];
+    for (int i = 0; i < N * N; ++i) {
+        pa[i] = sin(i);
+        pb[i] = cos(i);
+    }
+    fake_cblas_set_num_threads(1);
+    cblas_dgemm(CblasColMajor, CblasNoTrans, CblasNoTrans, N, N, N, 1.0, pa, N, pb, N, 0.0, seq, N);
+    fake_cblas_set_num_threads(4);
+    if (fake_cblas_get_num_threads() != 4) {
+        fprintf(stderr, "set_num_threads(4) was ignored\n");
+        return 1;
+    }
+    cblas_dgemm(CblasColMajor, CblasNoTrans, CblasNoTrans, N, N, N, 1.0, pa, N, pb, N, 0.0, par, N);
+    for (int i = 0; i < N * N; ++i) {
+        if (fabs(seq[i] - par[i]) > 1e-9) {
+            fprintf(stderr, "parallel dgemm differs at %d: %f vs %f\n", i, par[i], seq[i]);
+            return 1;
+        }
+    }
+
+    /* Пакеты 2×2 при 4 потоках сверяются с циклом по dgemm. */
+    enum { BATCH = 8 };
+    double bc[BATCH * 4], loop[BATCH * 4], pc[BATCH * 4];
+    const double *a_ptrs[BATCH], *b_ptrs[BATCH];
+    double *c_ptrs[BATCH];
+    for (int i = 0; i < BATCH; ++i) {
+        cblas_dgemm(CblasColMajor, CblasNoTrans, CblasTrans, 2, 2, 2, 1.0, pa + 4 * i, 2, pb, 2,
+                    0.0, loop + 4 * i, 2);
+        a_ptrs[i] = pa + 4 * i;
+        b_ptrs[i] = pb;
+        c_ptrs[i] = pc + 4 * i;
+    }
+    cblas_dgemm_batch_strided(CblasColMajor, CblasNoTrans, CblasTrans, 2, 2, 2, 1.0, pa, 2, 4, pb,
+                              2, 0, 0.0, bc, 2, 4, BATCH);
+    const CBLAS_TRANSPOSE no_trans = CblasNoTrans, trans = CblasTrans;
+    const int two = 2, batch = BATCH;
+    const double alpha = 1.0, beta = 0.0;
+    cblas_dgemm_batch(CblasColMajor, &no_trans, &trans, &two, &two, &two, &alpha, a_ptrs, &two,
+                      b_ptrs, &two, &beta, c_ptrs, &two, 1, &batch);
+    for (int i = 0; i < BATCH * 4; ++i) {
+        if (fabs(bc[i] - loop[i]) > 1e-12 || fabs(pc[i] - loop[i]) > 1e-12) {
+            fprintf(stderr, "dgemm batch differs at %d: %f %f vs %f\n", i, bc[i], pc[i],
+                    loop[i]);
+            return 1;
+        }
+    }
+
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,91 @@
+//! Сборка C-программы с `include/cblas.h` и линковка со статической
+//! библиотекой системным компилятором C.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `libfake_cblas.a`: при `cargo test` она остаётся в `target/<profile>/deps`
+/// рядом с тестом, после `cargo build` — копируется в `target/<profile>`.
+fn staticlib() -> PathBuf {
+    let exe = std::env::current_exe().expect("test executable path");
+    let deps = exe
+        .parent()
+        .expect("test executable has a parent directory");
+    [Some(deps), deps.parent()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("libfake_cblas.a"))
+        .find(|path| path.exists())
+        .expect("libfake_cblas.a is not built")
+}
+
+/// Системные библиотеки, которые требует статическая библиотека Rust
+/// (`rustc --print native-static-libs`).
+fn native_libs() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["-lSystem", "-lc", "-lm"]
+    } else {
+        &[
+            "-lgcc_s",
+            "-lutil",
+            "-lrt",
+            "-lpthread",
+            "-lm",
+            "-ldl",
+            "-lc",
+        ]
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn c_program_links_against_staticlib() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library = staticlib();
+
+    let target = env!("FAKE_CBLAS_TARGET");
+    let compiler = cc::Build::new()
+        .target(target)
+        .host(target)
+        .opt_level(0)
+        .cargo_metadata(false)
+        .get_compiler();
+
+    let output_dir = std::env::temp_dir().join(format!("fake_cblas_c_abi_{}", std::process::id()));
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let program = output_dir.join("cblas");
+
+    let status = compiler
+        .to_command()
+        .arg(manifest.join("tests/c/cblas.c"))
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg("-o")
+        .arg(&program)
+        .arg(&library)
+        .args(native_libs())
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "C compilation failed: {status}");
+
+    let output = Command::new(&program)
+        .env("FAKE_CBLAS_NUM_THREADS", "2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "C program failed:\n{stdout}\n{stderr}"
+    );
+    assert_eq!(stdout.trim(), "ok");
+    // Второй неверный вызов уходит в обработчик программы, а не в stderr.
+    assert_eq!(
+        stderr
+            .matches("Parameter 9 to routine cblas_dgemm was incorrect")
+            .count(),
+        1
+    );
+
+    std::fs::remove_dir_all(&output_dir).ok();
+}
@@ -31,6 +31,11 @@ impl<const N: usize> Dual<N> {
         }
     }
 
+    /// Переменная номер `index` со значением `value`: единичная производная по
+    /// ней самой и нулевые по остальным.
+    ///
+    /// # Panics
+    /// Если `index >= N`.
     #[must_use]
     pub fn variable(index: usize, value: f64) -> Self {
         assert!(
@@ -97,6 +102,10 @@ impl<const N: usize> Dual<N> {
         }
     }
 
+    /// Натуральный логарифм.
+    ///
+    /// # Panics
+    /// Если значение не положительно.
     #[must_use]
     pub fn ln(self) -> Self {
         assert!(
@@ -110,6 +119,10 @@ impl<const N: usize> Dual<N> {
         }
     }
 
+    /// Квадратный корень.
+    ///
+    /// # Panics
+    /// Если значение отрицательно.
     #[must_use]
     pub fn sqrt(self) -> Self {
         assert!(
@@ -292,3 +305,28 @@ impl<const N: usize> Neg for Dual<N> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Dual, variables};
+    use differentiable::check_gradient;
+
+    #[test]
+    #[allow(clippy::suboptimal_flops)]
+    fn dual_derivatives_pass_gradient_check() {
+        // Одна и та же формула над `f64` и над `Dual`: все элементарные функции.
+        let value = |[x, y, z]: [f64; 3]| {
+            x.sin() * y + (x / z).exp() - y.sqrt().ln() * z.tan() + (-z).powf(2.5).cos() * x.powi(3)
+        };
+        let dual = |[x, y, z]: [Dual<3>; 3]| {
+            x.sin() * y + (x / z).exp() - y.sqrt().ln() * z.tan() + (-z).powf(2.5).cos() * x.powi(3)
+        };
+
+        check_gradient(
+            |x| value([x[0], x[1], x[2]]),
+            |x| dual(variables([x[0], x[1], x[2]])).derivatives().to_vec(),
+            &[0.6, 1.7, -0.9],
+        )
+        .assert_ok();
+    }
+}
@@ -0,0 +1,2 @@
+pub mod dual_numbers;
+pub mod naive_dual_numbers;
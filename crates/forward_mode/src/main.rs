@@ -1,8 +1,5 @@
-mod dual_numbers;
-mod naive_dual_numbers;
-
-use dual_numbers::{Dual, variables};
-use naive_dual_numbers::NaiveDual;
+use forward_mode::dual_numbers::{Dual, variables};
+use forward_mode::naive_dual_numbers::NaiveDual;
 
 fn naive_forward_pass() {
     let f = |x: NaiveDual, y: NaiveDual| x * y + x.sin() * y;
@@ -7,6 +7,15 @@ pub struct NaiveDual {
 }
 
 impl NaiveDual {
+    /// Значение с произвольной касательной: производная по направлению.
+    #[must_use]
+    pub const fn new(value: f64, derivative: f64) -> Self {
+        Self {
+            value,
+            derivatives: derivative,
+        }
+    }
+
     #[must_use]
     pub const fn constant(value: f64) -> Self {
         Self {
@@ -72,6 +81,10 @@ impl NaiveDual {
         }
     }
 
+    /// Натуральный логарифм.
+    ///
+    /// # Panics
+    /// Если значение не положительно.
     #[must_use]
     pub fn ln(self) -> Self {
         assert!(
@@ -85,6 +98,10 @@ impl NaiveDual {
         }
     }
 
+    /// Квадратный корень.
+    ///
+    /// # Panics
+    /// Если значение отрицательно.
     #[must_use]
     pub fn sqrt(self) -> Self {
         assert!(
@@ -0,0 +1,173 @@
+use crate::tape::CustomOp;
+
+/// Численно устойчивый `softplus(x) = log(1 + exp(x))` с аналитической производной
+/// `σ(x)`, чтобы не дифференцировать через ветвление и `ln_1p`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Softplus;
+
+impl Softplus {
+    #[must_use]
+    pub fn value(x: f64) -> f64 {
+        if x > 0.0 {
+            x + (-x).exp().ln_1p()
+        } else {
+            x.exp().ln_1p()
+        }
+    }
+
+    /// Логистическая сигмоида без переполнения при больших `|x|`.
+    #[must_use]
+    pub fn sigmoid(x: f64) -> f64 {
+        if x >= 0.0 {
+            1.0 / (1.0 + (-x).exp())
+        } else {
+            let ex = x.exp();
+            ex / (1.0 + ex)
+        }
+    }
+}
+
+impl CustomOp for Softplus {
+    fn name(&self) -> &'static str {
+        "softplus"
+    }
+
+    fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        vec![Self::value(inputs[0])]
+    }
+
+    fn backward(&self, inputs: &[f64], _outputs: &[f64], output_adjoints: &[f64]) -> Vec<f64> {
+        vec![output_adjoints[0] * Self::sigmoid(inputs[0])]
+    }
+}
+
+/// Решение линейной системы `A x = b` размера `n`.
+///
+/// Входы: `n * n` элементов `A` по строкам, затем `n` элементов `b`. Выходы: `x`.
+/// Обратное правило: `λ = A⁻ᵀ x̄`, `b̄ = λ`, `Ā = -λ xᵀ` — одна дополнительная
+/// факторизация вместо дифференцирования через метод Гаусса.
+#[derive(Clone, Copy, Debug)]
+pub struct LinearSolve {
+    n: usize,
+}
+
+impl LinearSolve {
+    #[must_use]
+    pub const fn new(n: usize) -> Self {
+        Self { n }
+    }
+
+    /// Метод Гаусса с выбором ведущего элемента по столбцу.
+    ///
+    /// # Panics
+    /// Если матрица вырождена.
+    #[must_use]
+    pub fn solve(n: usize, matrix: &[f64], rhs: &[f64], transposed: bool) -> Vec<f64> {
+        let mut a: Vec<f64> = (0..n * n)
+            .map(|index| {
+                let (row, col) = (index / n, index % n);
+                if transposed {
+                    matrix[col * n + row]
+                } else {
+                    matrix[index]
+                }
+            })
+            .collect();
+        let mut x = rhs.to_vec();
+
+        for col in 0..n {
+            let pivot = (col..n)
+                .max_by(|&lhs, &rhs| a[lhs * n + col].abs().total_cmp(&a[rhs * n + col].abs()))
+                .unwrap_or(col);
+            assert!(
+                a[pivot * n + col] != 0.0,
+                "LinearSolve: matrix is singular (column {col})."
+            );
+            if pivot != col {
+                for k in 0..n {
+                    a.swap(col * n + k, pivot * n + k);
+                }
+                x.swap(col, pivot);
+            }
+            for row in col + 1..n {
+                let factor = a[row * n + col] / a[col * n + col];
+                for k in col..n {
+                    a[row * n + k] -= factor * a[col * n + k];
+                }
+                x[row] -= factor * x[col];
+            }
+        }
+
+        for row in (0..n).rev() {
+            let tail: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+            x[row] = (x[row] - tail) / a[row * n + row];
+        }
+        x
+    }
+}
+
+impl CustomOp for LinearSolve {
+    fn name(&self) -> &'static str {
+        "linear_solve"
+    }
+
+    fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        assert_eq!(
+            inputs.len(),
+            n * n + n,
+            "LinearSolve expects {} inputs.",
+            n * n + n
+        );
+        Self::solve(n, &inputs[..n * n], &inputs[n * n..], false)
+    }
+
+    fn backward(&self, inputs: &[f64], outputs: &[f64], output_adjoints: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let lambda = Self::solve(n, &inputs[..n * n], output_adjoints, true);
+        let mut adjoints: Vec<f64> = lambda
+            .iter()
+            .flat_map(|&lambda_row| outputs.iter().map(move |&x_col| -lambda_row * x_col))
+            .collect();
+        adjoints.extend_from_slice(&lambda);
+        adjoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinearSolve, Softplus};
+    use crate::tape::Tape;
+    use differentiable::check_gradient;
+    use std::sync::Arc;
+
+    #[test]
+    fn softplus_adjoint_is_sigmoid_even_for_large_inputs() {
+        for x0 in [-100.0, -1.0, 0.0, 1.0, 100.0] {
+            let tape = Tape::new();
+            let x = tape.var(x0);
+            let y = tape.custom(Arc::new(Softplus), &[x])[0];
+            let grads = tape.gradient(y);
+            assert!(y.value().is_finite());
+            assert!((grads.wrt(x) - Softplus::sigmoid(x0)).abs() < 1e-15);
+        }
+    }
+
+    #[test]
+    fn linear_solve_gradient_matches_finite_differences() {
+        // L = x0 + 2 * x1
+        let loss = |inputs: &[f64]| {
+            let x = LinearSolve::solve(2, &inputs[..4], &inputs[4..], false);
+            2.0f64.mul_add(x[1], x[0])
+        };
+        let gradient = |point: &[f64]| {
+            let tape = Tape::new();
+            let inputs: Vec<_> = point.iter().map(|&v| tape.var(v)).collect();
+            let x = tape.custom(Arc::new(LinearSolve::new(2)), &inputs);
+            let grads = tape.gradient(x[0] + x[1] * 2.0);
+            inputs.iter().map(|&input| grads.wrt(input)).collect()
+        };
+
+        check_gradient(loss, gradient, &[4.0, 1.0, 2.0, 3.0, 1.0, 2.0]).assert_ok();
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::tape::{Gradients, Op, Tape, Var};
+
+/// Экспорт записанного вычислительного графа в Graphviz DOT и JSON.
+///
+/// Узлы подписываются операцией, значением прямого прохода и, если передан
+/// результат [`Tape::gradient`], сопряжённым значением.
+pub struct GraphExport<'a> {
+    tape: &'a Tape,
+    gradients: Option<&'a Gradients>,
+    names: HashMap<usize, String>,
+}
+
+impl<'a> GraphExport<'a> {
+    #[must_use]
+    pub fn new(tape: &'a Tape) -> Self {
+        Self {
+            tape,
+            gradients: None,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Добавляет в подписи узлов сопряжённые значения после обратного прохода.
+    #[must_use]
+    pub const fn with_gradients(mut self, gradients: &'a Gradients) -> Self {
+        self.gradients = Some(gradients);
+        self
+    }
+
+    /// Даёт узлу человекочитаемое имя (например, `x` для входа).
+    #[must_use]
+    pub fn name(mut self, var: Var<'_>, name: &str) -> Self {
+        self.names.insert(var.index(), name.to_owned());
+        self
+    }
+
+    /// Граф в формате Graphviz DOT: рёбра идут от аргументов к результату.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let nodes = self.tape.nodes();
+        let calls = self.tape.custom_calls();
+        let mut dot =
+            String::from("digraph tape {\n    rankdir=LR;\n    node [fontname=\"monospace\"];\n");
+
+        for (index, node) in nodes.iter().enumerate() {
+            let mut label = format!("{}\\nv = {:.6}", self.op_label(index), node.value());
+            if let Some(adjoint) = self.adjoint(index) {
+                let _ = write!(label, "\\n∂ = {adjoint:.6}");
+            }
+            let shape = match node.op() {
+                Op::Input => "ellipse",
+                Op::Const => "plaintext",
+                _ => "box",
+            };
+            let _ = writeln!(dot, "    n{index} [label=\"{label}\", shape={shape}];");
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            let args = match *node.op() {
+                Op::Custom { call, .. } => calls[call].inputs().to_vec(),
+                ref op => op.args(),
+            };
+            for arg in args {
+                let _ = writeln!(dot, "    n{arg} -> n{index};");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Граф в JSON: список узлов и список вызовов пользовательских операций.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let nodes = self.tape.nodes();
+        let calls = self.tape.custom_calls();
+
+        let nodes: Vec<Value> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let mut entry = json!({
+                    "id": index,
+                    "op": node.op().name(),
+                    "args": node.op().args(),
+                    "value": node.value(),
+                });
+                match *node.op() {
+                    Op::Powi(_, exponent) => entry["exponent"] = json!(exponent),
+                    Op::Powf(_, exponent) => entry["exponent"] = json!(exponent),
+                    Op::Custom { call, output } => {
+                        entry["call"] = json!(call);
+                        entry["output"] = json!(output);
+                    }
+                    _ => {}
+                }
+                if let Some(name) = self.names.get(&index) {
+                    entry["name"] = json!(name);
+                }
+                if let Some(adjoint) = self.adjoint(index) {
+                    entry["adjoint"] = json!(adjoint);
+                }
+                entry
+            })
+            .collect();
+
+        let calls: Vec<Value> = calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "op": call.op().name(),
+                    "inputs": call.inputs(),
+                    "outputs": call.outputs().collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+
+        json!({ "nodes": nodes, "custom_calls": calls })
+    }
+
+    /// Записывает DOT-файл (рендер: `dot -Tsvg graph.dot -o graph.svg`).
+    ///
+    /// # Errors
+    /// Ошибки ввода-вывода при записи файла.
+    pub fn write_dot(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_dot())
+    }
+
+    /// Записывает JSON-файл с отступами.
+    ///
+    /// # Errors
+    /// Ошибки ввода-вывода при записи файла.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        std::fs::write(path, json)
+    }
+
+    fn adjoint(&self, index: usize) -> Option<f64> {
+        self.gradients
+            .and_then(|gradients| gradients.adjoints().get(index).copied())
+    }
+
+    /// Подпись узла для DOT: пользовательские имена и имена операций могут
+    /// содержать `"` и `\`, которые внутри `label="…"` нужно экранировать.
+    fn op_label(&self, index: usize) -> String {
+        let nodes = self.tape.nodes();
+        let op = match *nodes[index].op() {
+            Op::Powi(_, exponent) => format!("powi({exponent})"),
+            Op::Powf(_, exponent) => format!("powf({exponent})"),
+            Op::Custom { call, output } => {
+                format!("{}[{output}]", self.tape.custom_calls()[call].op().name())
+            }
+            ref op => op.name().to_owned(),
+        };
+        let label = match self.names.get(&index) {
+            Some(name) => format!("{name} = {op}"),
+            None => op,
+        };
+        escape_dot(&label)
+    }
+}
+
+/// Экранирует строку для строкового литерала DOT.
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphExport;
+    use crate::tape::Tape;
+
+    #[test]
+    fn dot_contains_every_node_and_edge() {
+        let tape = Tape::new();
+        let x = tape.var(2.0);
+        let y = tape.var(1.0);
+        let f = x * y + x.sin();
+        let grads = tape.gradient(f);
+
+        let dot = GraphExport::new(&tape)
+            .with_gradients(&grads)
+            .name(x, "x")
+            .name(y, "y")
+            .to_dot();
+
+        for index in 0..tape.len() {
+            assert!(dot.contains(&format!("n{index} [label=")));
+        }
+        for edge in ["n0 -> n2", "n1 -> n2", "n0 -> n3", "n2 -> n4", "n3 -> n4"] {
+            assert!(dot.contains(edge), "missing edge {edge}");
+        }
+        assert!(dot.contains("x = input"));
+        assert!(dot.contains("∂ = 1.000000"));
+    }
+
+    #[test]
+    fn dot_escapes_quotes_and_backslashes_in_names() {
+        let tape = Tape::new();
+        let x = tape.var(1.0);
+        let _ = x.exp();
+
+        let dot = GraphExport::new(&tape)
+            .name(x, r#"say "hi" \ bye"#)
+            .to_dot();
+        assert!(dot.contains(r#"label="say \"hi\" \\ bye = input\nv = "#));
+    }
+
+    #[test]
+    fn json_round_trips_through_serde() {
+        let tape = Tape::new();
+        let x = tape.var(3.0);
+        let f = x.powi(2) * 0.5;
+        let grads = tape.gradient(f);
+
+        let text = GraphExport::new(&tape)
+            .with_gradients(&grads)
+            .to_json()
+            .to_string();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let nodes = json["nodes"].as_array().unwrap();
+
+        assert_eq!(nodes.len(), tape.len());
+        assert_eq!(nodes[1]["op"], "powi");
+        assert_eq!(nodes[1]["exponent"], 2);
+        assert_eq!(nodes[0]["adjoint"], 3.0);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod custom_ops;
+pub mod export;
+pub mod optimize;
+pub mod parallel;
+pub mod real;
+pub mod serialize;
+pub mod sparse;
+pub mod stats;
+pub mod tape;
+pub mod tensor;
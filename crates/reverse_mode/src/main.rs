@@ -1,3 +1,11 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use reverse_mode::custom_ops::Softplus;
+use reverse_mode::export::GraphExport;
+use reverse_mode::optimize::optimize;
+use reverse_mode::tape;
+
 /// Небольшая лента значений, которые понадобятся на обратном проходе.
 #[derive(Debug, Clone, Copy)]
 pub struct Tape {
@@ -38,6 +46,66 @@ pub fn reverse_from_tape(t: &Tape, seed_df: f64, y: f64) -> (f64, f64) {
     (dx, dy)
 }
 
+/// Та же функция, но лента строится автоматически, а softplus идёт через ручной VJP.
+fn tape_pass(x0: f64, y0: f64, output_dir: &Path) {
+    let tape = tape::Tape::new();
+    let x = tape.var(x0);
+    let y = tape.var(y0);
+    let product = x * y;
+    let f = if x.gt(y) { product + x.sin() } else { product };
+    let softplus = tape.custom(Arc::new(Softplus), &[f])[0];
+
+    let grads = tape.gradient(f);
+    println!(
+        "Лента: f = {}, df/dx = {}, df/dy = {}",
+        f.value(),
+        grads.wrt(x),
+        grads.wrt(y)
+    );
+    for tie in grads.near_ties() {
+        println!("Внимание: почти равные операнды в сравнении {tie}, градиент односторонний");
+    }
+
+    let grads = tape.gradient(softplus);
+    println!(
+        "softplus(f) = {}, d/dx = {}, d/dy = {}",
+        softplus.value(),
+        grads.wrt(x),
+        grads.wrt(y)
+    );
+
+    println!("Статистика ленты:\n{}", tape.stats());
+
+    let optimized = optimize(&tape, softplus);
+    println!("Оптимизация ленты: {}", optimized.report());
+
+    let export = GraphExport::new(&tape)
+        .with_gradients(&grads)
+        .name(x, "x")
+        .name(y, "y");
+    let dot_path = output_dir.join("tape.dot");
+    let json_path = output_dir.join("tape.json");
+    match export
+        .write_dot(&dot_path)
+        .and_then(|()| export.write_json(&json_path))
+    {
+        Ok(()) => println!(
+            "Граф ленты записан в {} и {}",
+            dot_path.display(),
+            json_path.display()
+        ),
+        Err(error) => eprintln!("Не удалось записать граф: {error}"),
+    }
+}
+
+/// Каталог для графа ленты: первый аргумент командной строки, иначе временный
+/// каталог, чтобы запуск примера не засорял рабочее дерево.
+fn output_dir() -> PathBuf {
+    std::env::args_os()
+        .nth(1)
+        .map_or_else(std::env::temp_dir, PathBuf::from)
+}
+
 fn main() {
     let x = 2.0;
     let y = 1.0;
@@ -46,11 +114,14 @@ fn main() {
 
     println!("f({x}, {y}) = {value}");
     println!("df/dx = {dx}, df/dy = {dy}");
+
+    tape_pass(x, y, &output_dir());
 }
 
 #[cfg(test)]
 mod tests {
     use super::{primal_with_tape, reverse_from_tape};
+    use differentiable::check_gradient;
 
     #[test]
     fn trig_branch_is_used_when_x_greater_than_y() {
@@ -75,4 +146,17 @@ mod tests {
         assert!((dx - y).abs() < 1e-12);
         assert!((dy - x).abs() < 1e-12);
     }
+
+    #[test]
+    fn hand_written_adjoints_pass_gradient_check() {
+        let f = |p: &[f64]| primal_with_tape(p[0], p[1]).0;
+        let grad = |p: &[f64]| {
+            let (_, tape) = primal_with_tape(p[0], p[1]);
+            let (dx, dy) = reverse_from_tape(&tape, 1.0, p[1]);
+            vec![dx, dy]
+        };
+        // По обе стороны от ветвления x > y, вдали от границы.
+        check_gradient(f, grad, &[2.0, 1.0]).assert_ok();
+        check_gradient(f, grad, &[0.5, 1.0]).assert_ok();
+    }
 }
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::tape::{CustomCall, Node, Op, Tape, Var};
+
+/// Сводка по проходу оптимизации: сколько узлов было и стало и почему.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OptimizationReport {
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+    /// Узлы, все аргументы которых константы: заменены на константу.
+    pub folded: usize,
+    /// Повторные вычисления (в том числе одинаковые константы), слитые с первым.
+    pub deduplicated: usize,
+    /// Узлы, не влияющие на выход (включая константы, ставшие ненужными после свёртки).
+    pub eliminated: usize,
+}
+
+impl fmt::Display for OptimizationReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} -> {} узлов (свёрнуто констант: {}, общих подвыражений: {}, мёртвых: {})",
+            self.nodes_before, self.nodes_after, self.folded, self.deduplicated, self.eliminated
+        )
+    }
+}
+
+/// Оптимизированная копия ленты и соответствие старых индексов новым.
+#[derive(Debug)]
+pub struct Optimized {
+    tape: Tape,
+    output: usize,
+    remap: Vec<Option<usize>>,
+    report: OptimizationReport,
+}
+
+impl Optimized {
+    #[must_use]
+    pub const fn tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    #[must_use]
+    pub fn output(&self) -> Var<'_> {
+        self.tape.var_at(self.output)
+    }
+
+    /// Узел новой ленты, в который превратилась переменная исходной.
+    /// `None`, если узел был удалён как не влияющий на выход.
+    #[must_use]
+    pub fn map(&self, var: Var<'_>) -> Option<Var<'_>> {
+        self.remap
+            .get(var.index())
+            .copied()
+            .flatten()
+            .map(|index| self.tape.var_at(index))
+    }
+
+    #[must_use]
+    pub const fn report(&self) -> OptimizationReport {
+        self.report
+    }
+}
+
+/// Ключ для поиска общих подвыражений: имя операции, аргументы в новой ленте
+/// и параметр (показатель степени) в виде битов.
+type Key = (&'static str, [usize; 2], u64);
+
+/// Оптимизирует ленту относительно выхода `output`:
+/// - сворачивает узлы, все аргументы которых константы (значение уже посчитано
+///   на прямом проходе);
+/// - объединяет одинаковые операции над одинаковыми аргументами (для `add` и
+///   `mul` с учётом коммутативности) и одинаковые константы;
+/// - удаляет узлы, от которых выход не зависит.
+///
+/// Входы сохраняются всегда, чтобы по ним можно было спросить градиент.
+/// Пользовательские операции считаются непрозрачными: их вызовы не сливаются,
+/// но сворачиваются, если все их входы константы.
+///
+/// # Panics
+/// Если `output` принадлежит другой ленте.
+#[must_use]
+pub fn optimize(tape: &Tape, output: Var<'_>) -> Optimized {
+    assert!(
+        std::ptr::eq(tape, output.tape()),
+        "Output belongs to a different tape."
+    );
+    let nodes = tape.nodes();
+    let calls = tape.custom_calls();
+    let live = liveness(&nodes, &calls, output.index());
+    let mut rebuild = Rebuild::new(nodes.len());
+
+    for (index, node) in nodes.iter().enumerate() {
+        match *node.op() {
+            Op::Input => rebuild.keep_input(index, node),
+            _ if !live[index] => {}
+            Op::Const => rebuild.constant(index, node.value()),
+            Op::Custom { call, output: 0 } => rebuild.custom(&calls[call], &nodes),
+            Op::Custom { .. } => {}
+            ref op => rebuild.elementary(index, op, node.value()),
+        }
+    }
+
+    // Свёртка оставляет осиротевшие константы-аргументы: убираем их вторым проходом.
+    let Rebuild {
+        nodes: new_nodes,
+        calls: new_calls,
+        remap,
+        mut report,
+        ..
+    } = rebuild;
+    let output = remap[output.index()].expect("output is always live");
+    let (new_nodes, new_calls, compaction) = compact(&new_nodes, &new_calls, output);
+    let remap = remap
+        .into_iter()
+        .map(|index| index.and_then(|index| compaction[index]))
+        .collect();
+    let output = compaction[output].expect("output is always live");
+
+    report.nodes_before = nodes.len();
+    report.nodes_after = new_nodes.len();
+    report.eliminated =
+        (report.nodes_before - report.nodes_after).saturating_sub(report.deduplicated);
+
+    Optimized {
+        tape: Tape::from_parts(new_nodes, new_calls),
+        output,
+        remap,
+        report,
+    }
+}
+
+/// Новая лента, собираемая за один проход по исходной.
+struct Rebuild {
+    nodes: Vec<Node>,
+    calls: Vec<CustomCall>,
+    remap: Vec<Option<usize>>,
+    constants: HashMap<u64, usize>,
+    expressions: HashMap<Key, usize>,
+    report: OptimizationReport,
+}
+
+impl Rebuild {
+    fn new(len: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            calls: Vec::new(),
+            remap: vec![None; len],
+            constants: HashMap::new(),
+            expressions: HashMap::new(),
+            report: OptimizationReport::default(),
+        }
+    }
+
+    fn keep_input(&mut self, index: usize, node: &Node) {
+        self.nodes.push(node.clone());
+        self.remap[index] = Some(self.nodes.len() - 1);
+    }
+
+    fn constant(&mut self, index: usize, value: f64) {
+        let (new_index, fresh) = self.intern(value);
+        if !fresh {
+            self.report.deduplicated += 1;
+        }
+        self.remap[index] = Some(new_index);
+    }
+
+    fn custom(&mut self, call: &CustomCall, nodes: &[Node]) {
+        let inputs = self.mapped(call.inputs());
+        if self.all_constant(&inputs) {
+            for old in call.outputs() {
+                self.remap[old] = Some(self.intern(nodes[old].value()).0);
+                self.report.folded += 1;
+            }
+            return;
+        }
+
+        let new_call = self.calls.len();
+        let start = self.nodes.len();
+        for (slot, old) in call.outputs().enumerate() {
+            let op = Op::Custom {
+                call: new_call,
+                output: slot,
+            };
+            self.nodes.push(Node::new(op, nodes[old].value()));
+            self.remap[old] = Some(start + slot);
+        }
+        self.calls.push(CustomCall::new(
+            call.shared_op(),
+            inputs,
+            start..self.nodes.len(),
+        ));
+    }
+
+    fn elementary(&mut self, index: usize, op: &Op, value: f64) {
+        let args = self.mapped(&op.args());
+        if self.all_constant(&args) {
+            self.remap[index] = Some(self.intern(value).0);
+            self.report.folded += 1;
+            return;
+        }
+
+        let key = expression_key(op, &args);
+        if let Some(&existing) = self.expressions.get(&key) {
+            self.remap[index] = Some(existing);
+            self.report.deduplicated += 1;
+            return;
+        }
+        self.nodes.push(Node::new(with_args(op, &args), value));
+        self.remap[index] = Some(self.nodes.len() - 1);
+        self.expressions.insert(key, self.nodes.len() - 1);
+    }
+
+    /// Индекс константы с данным значением; `true`, если она создана только что.
+    fn intern(&mut self, value: f64) -> (usize, bool) {
+        if let Some(&index) = self.constants.get(&value.to_bits()) {
+            return (index, false);
+        }
+        self.nodes.push(Node::new(Op::Const, value));
+        self.constants.insert(value.to_bits(), self.nodes.len() - 1);
+        (self.nodes.len() - 1, true)
+    }
+
+    fn mapped(&self, args: &[usize]) -> Vec<usize> {
+        args.iter()
+            .map(|&arg| self.remap[arg].expect("argument of a live node must be kept"))
+            .collect()
+    }
+
+    fn all_constant(&self, args: &[usize]) -> bool {
+        args.iter().all(|&arg| self.nodes[arg].op() == &Op::Const)
+    }
+}
+
+/// Удаляет узлы, не влияющие на выход (кроме входов), и перенумеровывает остальные.
+fn compact(
+    nodes: &[Node],
+    calls: &[CustomCall],
+    output: usize,
+) -> (Vec<Node>, Vec<CustomCall>, Vec<Option<usize>>) {
+    let live = liveness(nodes, calls, output);
+    let mut remap: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut kept_nodes = Vec::new();
+    let mut kept_calls = Vec::new();
+
+    for (index, node) in nodes.iter().enumerate() {
+        if !live[index] && node.op() != &Op::Input {
+            continue;
+        }
+        let op = match *node.op() {
+            Op::Custom { call, output } => {
+                if output == 0 {
+                    let call = &calls[call];
+                    let inputs = call.inputs().iter().filter_map(|&arg| remap[arg]).collect();
+                    let start = kept_nodes.len();
+                    kept_calls.push(CustomCall::new(
+                        call.shared_op(),
+                        inputs,
+                        start..start + call.outputs().len(),
+                    ));
+                }
+                Op::Custom {
+                    call: kept_calls.len() - 1,
+                    output,
+                }
+            }
+            ref op => {
+                let args: Vec<usize> = op.args().iter().filter_map(|&arg| remap[arg]).collect();
+                with_args(op, &args)
+            }
+        };
+        kept_nodes.push(Node::new(op, node.value()));
+        remap[index] = Some(kept_nodes.len() - 1);
+    }
+
+    (kept_nodes, kept_calls, remap)
+}
+
+/// Узлы, от которых зависит `output` (обход от выхода к входам).
+fn liveness(nodes: &[Node], calls: &[CustomCall], output: usize) -> Vec<bool> {
+    let mut live = vec![false; nodes.len()];
+    live[output] = true;
+    for index in (0..=output).rev() {
+        if !live[index] {
+            continue;
+        }
+        match *nodes[index].op() {
+            Op::Custom { call, .. } => {
+                let call = &calls[call];
+                for out in call.outputs() {
+                    live[out] = true;
+                }
+                for &arg in call.inputs() {
+                    live[arg] = true;
+                }
+            }
+            ref op => {
+                for arg in op.args() {
+                    live[arg] = true;
+                }
+            }
+        }
+    }
+    live
+}
+
+fn expression_key(op: &Op, args: &[usize]) -> Key {
+    let mut pair = [args[0], args.get(1).copied().unwrap_or(usize::MAX)];
+    if matches!(op, Op::Add(..) | Op::Mul(..)) && pair[1] < pair[0] {
+        pair.swap(0, 1);
+    }
+    let parameter = match *op {
+        Op::Powi(_, exponent) => f64::from(exponent).to_bits(),
+        Op::Powf(_, exponent) => exponent.to_bits(),
+        _ => 0,
+    };
+    (op.name(), pair, parameter)
+}
+
+/// Та же операция, но с аргументами из новой ленты.
+fn with_args(op: &Op, args: &[usize]) -> Op {
+    match *op {
+        Op::Add(..) => Op::Add(args[0], args[1]),
+        Op::Sub(..) => Op::Sub(args[0], args[1]),
+        Op::Mul(..) => Op::Mul(args[0], args[1]),
+        Op::Div(..) => Op::Div(args[0], args[1]),
+        Op::Neg(_) => Op::Neg(args[0]),
+        Op::Sin(_) => Op::Sin(args[0]),
+        Op::Cos(_) => Op::Cos(args[0]),
+        Op::Tan(_) => Op::Tan(args[0]),
+        Op::Exp(_) => Op::Exp(args[0]),
+        Op::Ln(_) => Op::Ln(args[0]),
+        Op::Sqrt(_) => Op::Sqrt(args[0]),
+        Op::Powi(_, exponent) => Op::Powi(args[0], exponent),
+        Op::Powf(_, exponent) => Op::Powf(args[0], exponent),
+        Op::Input | Op::Const | Op::Custom { .. } => op.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::optimize;
+    use crate::tape::Tape;
+
+    #[test]
+    fn passes_shrink_tape_and_keep_gradients() {
+        let tape = Tape::new();
+        let x = tape.var(0.7);
+        let y = tape.var(-1.3);
+        let unused = tape.var(5.0);
+        // Константное подвыражение, повторный sin(x) и мёртвая ветка.
+        let scale = tape.constant(2.0) * tape.constant(3.0);
+        let f = x.sin() * y + x.sin() * scale + (y * x).exp();
+        let _dead = (unused * x).cos();
+
+        let grads = tape.gradient(f);
+        let optimized = optimize(&tape, f);
+        let report = optimized.report();
+        let new_tape = optimized.tape();
+        let new_grads = new_tape.gradient(optimized.output());
+
+        assert!(report.nodes_after < report.nodes_before);
+        assert_eq!(report.folded, 1);
+        assert!(report.deduplicated >= 1);
+        assert!(report.eliminated >= 2);
+        assert_eq!(new_tape.len(), report.nodes_after);
+        assert_eq!(optimized.output().value().to_bits(), f.value().to_bits());
+        for var in [x, y, unused] {
+            let mapped = optimized.map(var).unwrap();
+            assert!((new_grads.wrt(mapped) - grads.wrt(var)).abs() < 1e-12);
+        }
+    }
+}
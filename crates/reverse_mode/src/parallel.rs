@@ -0,0 +1,165 @@
+use rayon::ThreadPool;
+use rayon::prelude::*;
+
+use crate::tape::{Tape, Var};
+
+/// Значение суммы и её градиент по параметрам.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParallelGradient {
+    pub value: f64,
+    pub gradient: Vec<f64>,
+}
+
+/// Градиент суммы `Σ term(params, i, samples[i])` по `params` на потоках `pool`.
+///
+/// [`Tape`] можно передавать между потоками (`Send`), но не разделять (`!Sync`),
+/// поэтому у каждого потока своя лента. Выборка режется на
+/// `pool.current_num_threads()` непрерывных кусков одинаковой длины, каждый
+/// кусок записывается на отдельную ленту и проходится обратно, а частичные
+/// суммы складываются в порядке кусков. Разбиение и порядок сложения зависят
+/// только от числа потоков пула, поэтому при фиксированном числе потоков
+/// результат побитово воспроизводим независимо от планировщика.
+///
+/// Пул создаёт вызывающая сторона один раз (например, на весь цикл
+/// оптимизации), чтобы не запускать потоки ОС на каждой итерации.
+#[must_use]
+pub fn par_gradient<S, F>(
+    pool: &ThreadPool,
+    params: &[f64],
+    samples: &[S],
+    term: F,
+) -> ParallelGradient
+where
+    S: Sync,
+    F: for<'t> Fn(&[Var<'t>], usize, &S) -> Var<'t> + Sync,
+{
+    let threads = pool.current_num_threads().max(1);
+    let chunk_len = samples.len().div_ceil(threads).max(1);
+
+    let partials: Vec<ParallelGradient> = pool.install(|| {
+        samples
+            .par_chunks(chunk_len)
+            .enumerate()
+            .map(|(chunk, samples)| chunk_gradient(params, samples, chunk * chunk_len, &term))
+            .collect()
+    });
+
+    let mut total = ParallelGradient {
+        value: 0.0,
+        gradient: vec![0.0; params.len()],
+    };
+    for partial in partials {
+        total.value += partial.value;
+        for (sum, adjoint) in total.gradient.iter_mut().zip(partial.gradient) {
+            *sum += adjoint;
+        }
+    }
+    total
+}
+
+/// Последовательный градиент одного куска выборки на собственной ленте.
+fn chunk_gradient<S, F>(params: &[f64], samples: &[S], offset: usize, term: &F) -> ParallelGradient
+where
+    F: for<'t> Fn(&[Var<'t>], usize, &S) -> Var<'t>,
+{
+    let tape = Tape::new();
+    let vars: Vec<Var<'_>> = params.iter().map(|&value| tape.var(value)).collect();
+    let mut terms = samples
+        .iter()
+        .enumerate()
+        .map(|(index, sample)| term(&vars, offset + index, sample));
+    let Some(first) = terms.next() else {
+        return ParallelGradient {
+            value: 0.0,
+            gradient: vec![0.0; params.len()],
+        };
+    };
+    let sum = terms.fold(first, |sum, term| sum + term);
+
+    let grads = tape.gradient(sum);
+    ParallelGradient {
+        value: sum.value(),
+        gradient: vars.iter().map(|&var| grads.wrt(var)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::par_gradient;
+    use crate::tape::{Tape, Var};
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+    use std::f64::consts::PI;
+
+    fn pool(threads: usize) -> ThreadPool {
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+    }
+
+    fn temps() -> Vec<f64> {
+        (0..1068)
+            .map(|i| {
+                let t = f64::from(i);
+                (2.0 * PI * t / 365.0).sin().mul_add(12.0, 5.0) + (t * 0.37).sin()
+            })
+            .collect()
+    }
+
+    /// Квадрат невязки модели `a * sin(2π(t + c) / p) + b`, как в `sse_loss`.
+    fn residual<'t>(params: &[Var<'t>], index: usize, temp: f64) -> Var<'t> {
+        let [a, b, p, c] = [params[0], params[1], params[2], params[3]];
+        let day = f64::from(u32::try_from(index).unwrap());
+        let arg = (c + day) * (2.0 * PI) / p;
+        let error = a * arg.sin() + b - temp;
+        error * error
+    }
+
+    #[test]
+    fn parallel_gradient_matches_single_tape() {
+        let data = temps();
+        let params = [10.0, 4.0, 360.0, 3.0];
+
+        let tape = Tape::new();
+        let vars: Vec<Var<'_>> = params.iter().map(|&value| tape.var(value)).collect();
+        let loss = data
+            .iter()
+            .enumerate()
+            .map(|(index, &temp)| residual(&vars, index, temp))
+            .reduce(|sum, term| sum + term)
+            .unwrap();
+        let grads = tape.gradient(loss);
+
+        for threads in [1, 2, 3, 8] {
+            let pool = pool(threads);
+            let result = par_gradient(&pool, &params, &data, |p, i, &t| residual(p, i, t));
+            assert!((result.value - loss.value()).abs() < 1e-9 * loss.value());
+            for (var, parallel) in vars.iter().zip(&result.gradient) {
+                let sequential = grads.wrt(*var);
+                assert!((parallel - sequential).abs() <= 1e-9 * sequential.abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_thread_count_is_bitwise_reproducible() {
+        let data = temps();
+        let params = [10.0, 4.0, 360.0, 3.0];
+        // Один пул на все вызовы, как в цикле оптимизации.
+        let pool = pool(4);
+        let first = par_gradient(&pool, &params, &data, |p, i, &t| residual(p, i, t));
+        for _ in 0..5 {
+            let again = par_gradient(&pool, &params, &data, |p, i, &t| residual(p, i, t));
+            assert_eq!(first.value.to_bits(), again.value.to_bits());
+            for (lhs, rhs) in first.gradient.iter().zip(&again.gradient) {
+                assert_eq!(lhs.to_bits(), rhs.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn tape_can_be_moved_to_another_thread() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Tape>();
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Вещественный тип значений на ленте: `f32` или `f64`.
+///
+/// Лента хранит значения прямого прохода в `T`, а сопряжённые на обратном
+/// проходе можно накапливать в другом типе (см. [`crate::tape::Tape::gradient_in`]).
+/// Пользовательские операции и сравнения всегда работают в `f64`.
+pub trait Real:
+    Copy
+    + Debug
+    + Display
+    + PartialOrd
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + Default
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Округление `f64` до `Self`.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    /// Перевод в другой вещественный тип (через `f64`, для `f32 -> f64` точно).
+    #[must_use]
+    fn cast<R: Real>(self) -> R {
+        R::from_f64(self.to_f64())
+    }
+
+    #[must_use]
+    fn sin(self) -> Self;
+    #[must_use]
+    fn cos(self) -> Self;
+    #[must_use]
+    fn tan(self) -> Self;
+    #[must_use]
+    fn exp(self) -> Self;
+    #[must_use]
+    fn ln(self) -> Self;
+    #[must_use]
+    fn sqrt(self) -> Self;
+    #[must_use]
+    fn powi(self, exponent: i32) -> Self;
+    #[must_use]
+    fn powf(self, exponent: Self) -> Self;
+}
+
+macro_rules! impl_real {
+    ($ty:ty) => {
+        impl Real for $ty {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            #[allow(clippy::cast_possible_truncation, clippy::cast_lossless)]
+            fn from_f64(value: f64) -> Self {
+                value as Self
+            }
+
+            #[allow(clippy::cast_lossless)]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn sin(self) -> Self {
+                <$ty>::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                <$ty>::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                <$ty>::tan(self)
+            }
+
+            fn exp(self) -> Self {
+                <$ty>::exp(self)
+            }
+
+            fn ln(self) -> Self {
+                <$ty>::ln(self)
+            }
+
+            fn sqrt(self) -> Self {
+                <$ty>::sqrt(self)
+            }
+
+            fn powi(self, exponent: i32) -> Self {
+                <$ty>::powi(self, exponent)
+            }
+
+            fn powf(self, exponent: Self) -> Self {
+                <$ty>::powf(self, exponent)
+            }
+        }
+    };
+}
+
+impl_real!(f32);
+impl_real!(f64);
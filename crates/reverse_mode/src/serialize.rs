@@ -0,0 +1,822 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+
+use crate::tape::{CustomCall, CustomOp, Node, Op, Tape, Var};
+
+/// Сигнатура двоичного формата.
+pub const MAGIC: [u8; 4] = *b"RMTP";
+/// Текущая версия формата (двоичного и JSON).
+pub const FORMAT_VERSION: u32 = 1;
+const JSON_FORMAT: &str = "reverse_mode.tape";
+
+/// Пользовательские операции, доступные при загрузке: ищутся по [`CustomOp::name`].
+pub type CustomOpRegistry = HashMap<String, Arc<dyn CustomOp>>;
+
+/// Ошибка чтения или проверки сохранённой ленты.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    BadMagic,
+    UnsupportedVersion(u32),
+    Truncated,
+    TrailingBytes,
+    Json(String),
+    UnknownOp(String),
+    /// Узел ссылается на себя, на более поздний узел или за пределы ленты.
+    BadReference {
+        node: usize,
+        arg: usize,
+    },
+    /// Несогласованность вызовов пользовательских операций, входов или выходов.
+    Malformed(String),
+    UnknownCustomOp(String),
+    InputCount {
+        expected: usize,
+        received: usize,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(formatter, "not a reverse_mode tape (bad magic)"),
+            Self::UnsupportedVersion(version) => {
+                write!(formatter, "unsupported tape format version {version}")
+            }
+            Self::Truncated => write!(formatter, "tape data is truncated"),
+            Self::TrailingBytes => write!(formatter, "unexpected bytes after the tape"),
+            Self::Json(message) => write!(formatter, "invalid tape JSON: {message}"),
+            Self::UnknownOp(op) => write!(formatter, "unknown operation `{op}`"),
+            Self::BadReference { node, arg } => {
+                write!(formatter, "node {node} refers to invalid node {arg}")
+            }
+            Self::Malformed(message) => write!(formatter, "malformed tape: {message}"),
+            Self::UnknownCustomOp(name) => {
+                write!(formatter, "custom op `{name}` is not in the registry")
+            }
+            Self::InputCount { expected, received } => {
+                write!(
+                    formatter,
+                    "expected {expected} input values, received {received}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SavedCall {
+    name: String,
+    inputs: Vec<usize>,
+    outputs: Range<usize>,
+}
+
+/// Независимая от процесса копия ленты: операции, константы, индексы входов
+/// и выходов. Пользовательские операции сохраняются по имени.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TapeSnapshot {
+    nodes: Vec<Node>,
+    calls: Vec<SavedCall>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+/// Результат повторного прогона: значения выходов и градиент каждого выхода
+/// по входам (в порядке [`TapeSnapshot::inputs`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub outputs: Vec<f64>,
+    pub gradients: Vec<Vec<f64>>,
+}
+
+impl TapeSnapshot {
+    /// Снимок ленты. Входами считаются все узлы [`Op::Input`] в порядке записи.
+    ///
+    /// # Panics
+    /// Если выход принадлежит другой ленте.
+    #[must_use]
+    pub fn capture(tape: &Tape, outputs: &[Var<'_>]) -> Self {
+        for output in outputs {
+            assert!(
+                std::ptr::eq(tape, output.tape()),
+                "Output belongs to a different tape."
+            );
+        }
+        let nodes = tape.nodes().to_vec();
+        let calls = tape
+            .custom_calls()
+            .iter()
+            .map(|call| SavedCall {
+                name: call.op().name().to_owned(),
+                inputs: call.inputs().to_vec(),
+                outputs: call.outputs(),
+            })
+            .collect();
+        let inputs = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.op() == &Op::Input)
+            .map(|(index, _)| index)
+            .collect();
+
+        Self {
+            nodes,
+            calls,
+            inputs,
+            outputs: outputs.iter().map(Var::index).collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    #[must_use]
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    /// Значения входов, с которыми лента была записана.
+    #[must_use]
+    pub fn recorded_inputs(&self) -> Vec<f64> {
+        self.inputs
+            .iter()
+            .map(|&index| self.nodes[index].value())
+            .collect()
+    }
+
+    /// Пересчитывает ленту на новых значениях входов и выполняет обратный
+    /// проход от каждого выхода.
+    ///
+    /// Лента фиксирует один путь выполнения: ветвления не перевычисляются, так
+    /// что повторный прогон корректен, пока входы не меняют выбранную ветку.
+    ///
+    /// # Errors
+    /// Если число значений не совпадает с числом входов, пользовательской
+    /// операции нет в `registry` или она вернула другое число выходов.
+    pub fn replay(&self, inputs: &[f64], registry: &CustomOpRegistry) -> Result<Replay, LoadError> {
+        if inputs.len() != self.inputs.len() {
+            return Err(LoadError::InputCount {
+                expected: self.inputs.len(),
+                received: inputs.len(),
+            });
+        }
+        let tape = self.rebuild(inputs, registry)?;
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|&index| tape.var_at(index).value())
+            .collect();
+        let gradients = self
+            .outputs
+            .iter()
+            .map(|&index| {
+                let grads = tape.gradient(tape.var_at(index));
+                self.inputs
+                    .iter()
+                    .map(|&input| grads.wrt(tape.var_at(input)))
+                    .collect()
+            })
+            .collect();
+        Ok(Replay { outputs, gradients })
+    }
+
+    fn rebuild(&self, inputs: &[f64], registry: &CustomOpRegistry) -> Result<Tape, LoadError> {
+        let mut values: Vec<f64> = self.nodes.iter().map(Node::value).collect();
+        for (&index, &value) in self.inputs.iter().zip(inputs) {
+            values[index] = value;
+        }
+
+        let mut calls = Vec::with_capacity(self.calls.len());
+        for (index, node) in self.nodes.iter().enumerate() {
+            match *node.op() {
+                Op::Custom { call, output: 0 } => {
+                    let saved = &self.calls[call];
+                    let op = registry
+                        .get(&saved.name)
+                        .ok_or_else(|| LoadError::UnknownCustomOp(saved.name.clone()))?;
+                    let arguments: Vec<f64> = saved.inputs.iter().map(|&arg| values[arg]).collect();
+                    let results = op.forward(&arguments);
+                    if results.len() != saved.outputs.len() {
+                        return Err(LoadError::Malformed(format!(
+                            "custom op `{}` returned {} outputs, expected {}",
+                            saved.name,
+                            results.len(),
+                            saved.outputs.len()
+                        )));
+                    }
+                    values[saved.outputs.clone()].copy_from_slice(&results);
+                    calls.push(CustomCall::new(
+                        Arc::clone(op),
+                        saved.inputs.clone(),
+                        saved.outputs.clone(),
+                    ));
+                }
+                Op::Input | Op::Const | Op::Custom { .. } => {}
+                ref op => values[index] = evaluate(op, &values),
+            }
+        }
+
+        let nodes = self
+            .nodes
+            .iter()
+            .zip(values)
+            .map(|(node, value)| Node::new(node.op().clone(), value))
+            .collect();
+        Ok(Tape::from_parts(nodes, calls))
+    }
+
+    /// Двоичный формат (little endian): сигнатура, версия, узлы, вызовы
+    /// пользовательских операций, индексы входов и выходов.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+
+        write_len(&mut bytes, self.nodes.len());
+        for node in &self.nodes {
+            bytes.push(op_tag(node.op()));
+            match *node.op() {
+                Op::Powi(arg, exponent) => {
+                    write_len(&mut bytes, arg);
+                    bytes.extend_from_slice(&exponent.to_le_bytes());
+                }
+                Op::Powf(arg, exponent) => {
+                    write_len(&mut bytes, arg);
+                    bytes.extend_from_slice(&exponent.to_le_bytes());
+                }
+                Op::Custom { call, output } => {
+                    write_len(&mut bytes, call);
+                    write_len(&mut bytes, output);
+                }
+                ref op => {
+                    for arg in op.args() {
+                        write_len(&mut bytes, arg);
+                    }
+                }
+            }
+            bytes.extend_from_slice(&node.value().to_le_bytes());
+        }
+
+        write_len(&mut bytes, self.calls.len());
+        for call in &self.calls {
+            write_len(&mut bytes, call.name.len());
+            bytes.extend_from_slice(call.name.as_bytes());
+            write_indices(&mut bytes, &call.inputs);
+            write_len(&mut bytes, call.outputs.start);
+            write_len(&mut bytes, call.outputs.len());
+        }
+
+        write_indices(&mut bytes, &self.inputs);
+        write_indices(&mut bytes, &self.outputs);
+        bytes
+    }
+
+    /// Читает и проверяет двоичный формат.
+    ///
+    /// # Errors
+    /// Неверная сигнатура или версия, обрезанные данные, неизвестная операция,
+    /// ссылки вперёд или за пределы ленты, несогласованные вызовы.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(LoadError::BadMagic);
+        }
+        let version = u32::from_le_bytes(reader.array()?);
+        if version != FORMAT_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+
+        let node_count = reader.index()?;
+        let mut nodes = Vec::new();
+        for _ in 0..node_count {
+            let tag = reader.take(1)?[0];
+            let op = match tag {
+                TAG_POWI => Op::Powi(reader.index()?, i32::from_le_bytes(reader.array()?)),
+                TAG_POWF => Op::Powf(reader.index()?, f64::from_le_bytes(reader.array()?)),
+                TAG_CUSTOM => Op::Custom {
+                    call: reader.index()?,
+                    output: reader.index()?,
+                },
+                _ => {
+                    let name =
+                        tag_name(tag).ok_or_else(|| LoadError::UnknownOp(tag.to_string()))?;
+                    let arity = name_arity(name);
+                    let args = (0..arity)
+                        .map(|_| reader.index())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    op_from_name(name, &args, None)?
+                }
+            };
+            nodes.push(Node::new(op, f64::from_le_bytes(reader.array()?)));
+        }
+
+        let call_count = reader.index()?;
+        let mut calls = Vec::new();
+        for _ in 0..call_count {
+            let name_len = reader.index()?;
+            let name = String::from_utf8(reader.take(name_len)?.to_vec())
+                .map_err(|_| LoadError::Malformed("custom op name is not UTF-8".to_owned()))?;
+            let inputs = reader.indices()?;
+            let start = reader.index()?;
+            let len = reader.index()?;
+            let end = start
+                .checked_add(len)
+                .ok_or_else(|| LoadError::Malformed("custom op outputs overflow".to_owned()))?;
+            calls.push(SavedCall {
+                name,
+                inputs,
+                outputs: start..end,
+            });
+        }
+
+        let inputs = reader.indices()?;
+        let outputs = reader.indices()?;
+        if reader.position != bytes.len() {
+            return Err(LoadError::TrailingBytes);
+        }
+
+        let snapshot = Self {
+            nodes,
+            calls,
+            inputs,
+            outputs,
+        };
+        snapshot.validate()?;
+        Ok(snapshot)
+    }
+
+    /// JSON-представление того же содержимого.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let mut entry = json!({
+                    "op": node.op().name(),
+                    "args": node.op().args(),
+                    // Битовое представление сохраняет NaN, бесконечности и -0.0.
+                    "value": node.value().to_bits(),
+                });
+                match *node.op() {
+                    Op::Powi(_, exponent) => entry["exponent"] = json!(exponent),
+                    Op::Powf(_, exponent) => entry["exponent"] = json!(exponent.to_bits()),
+                    Op::Custom { call, output } => {
+                        entry["call"] = json!(call);
+                        entry["output"] = json!(output);
+                    }
+                    _ => {}
+                }
+                entry
+            })
+            .collect();
+        let calls: Vec<Value> = self
+            .calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "name": call.name,
+                    "inputs": call.inputs,
+                    "outputs": [call.outputs.start, call.outputs.end],
+                })
+            })
+            .collect();
+
+        json!({
+            "format": JSON_FORMAT,
+            "version": FORMAT_VERSION,
+            "nodes": nodes,
+            "custom_calls": calls,
+            "inputs": self.inputs,
+            "outputs": self.outputs,
+        })
+        .to_string()
+    }
+
+    /// Читает и проверяет JSON-формат.
+    ///
+    /// # Errors
+    /// Некорректный JSON, чужой формат или версия, а также все ошибки
+    /// проверки, что и у [`TapeSnapshot::from_bytes`].
+    pub fn from_json(text: &str) -> Result<Self, LoadError> {
+        let json: Value =
+            serde_json::from_str(text).map_err(|error| LoadError::Json(error.to_string()))?;
+        if json["format"] != JSON_FORMAT {
+            return Err(LoadError::BadMagic);
+        }
+        let version = json_u64(&json["version"])?;
+        if version != u64::from(FORMAT_VERSION) {
+            return Err(LoadError::UnsupportedVersion(
+                u32::try_from(version).unwrap_or(u32::MAX),
+            ));
+        }
+
+        let mut nodes = Vec::new();
+        for entry in json_array(&json["nodes"])? {
+            let name = entry["op"]
+                .as_str()
+                .ok_or_else(|| LoadError::Json("node without `op`".to_owned()))?;
+            let op = if name == "custom" {
+                Op::Custom {
+                    call: json_index(&entry["call"])?,
+                    output: json_index(&entry["output"])?,
+                }
+            } else {
+                let args = json_indices(&entry["args"])?;
+                op_from_name(name, &args, entry.get("exponent"))?
+            };
+            let value = f64::from_bits(json_u64(&entry["value"])?);
+            nodes.push(Node::new(op, value));
+        }
+
+        let mut calls = Vec::new();
+        for entry in json_array(&json["custom_calls"])? {
+            let name = entry["name"]
+                .as_str()
+                .ok_or_else(|| LoadError::Json("custom call without `name`".to_owned()))?;
+            let bounds = json_indices(&entry["outputs"])?;
+            let [start, end] = bounds[..] else {
+                return Err(LoadError::Json("`outputs` must be [start, end]".to_owned()));
+            };
+            calls.push(SavedCall {
+                name: name.to_owned(),
+                inputs: json_indices(&entry["inputs"])?,
+                outputs: start..end,
+            });
+        }
+
+        let snapshot = Self {
+            nodes,
+            calls,
+            inputs: json_indices(&json["inputs"])?,
+            outputs: json_indices(&json["outputs"])?,
+        };
+        snapshot.validate()?;
+        Ok(snapshot)
+    }
+
+    /// Структурная проверка: топологический порядок, согласованность вызовов,
+    /// входы указывают на узлы `input`, выходы существуют.
+    fn validate(&self) -> Result<(), LoadError> {
+        let len = self.nodes.len();
+        for (index, node) in self.nodes.iter().enumerate() {
+            match *node.op() {
+                Op::Custom { call, output } => {
+                    let saved = self.calls.get(call).ok_or_else(|| {
+                        LoadError::Malformed(format!("node {index} refers to missing call {call}"))
+                    })?;
+                    if index.checked_sub(saved.outputs.start) != Some(output) {
+                        return Err(LoadError::Malformed(format!(
+                            "node {index} is not output {output} of call {call}"
+                        )));
+                    }
+                }
+                ref op => {
+                    if let Some(&arg) = op.args().iter().find(|&&arg| arg >= index) {
+                        return Err(LoadError::BadReference { node: index, arg });
+                    }
+                }
+            }
+        }
+
+        for (call_index, call) in self.calls.iter().enumerate() {
+            if call.outputs.is_empty() || call.outputs.end > len {
+                return Err(LoadError::Malformed(format!(
+                    "call {call_index} has invalid outputs {:?}",
+                    call.outputs
+                )));
+            }
+            for (slot, index) in call.outputs.clone().enumerate() {
+                let expected = Op::Custom {
+                    call: call_index,
+                    output: slot,
+                };
+                if self.nodes[index].op() != &expected {
+                    return Err(LoadError::Malformed(format!(
+                        "node {index} should be output {slot} of call {call_index}"
+                    )));
+                }
+            }
+            if let Some(&arg) = call.inputs.iter().find(|&&arg| arg >= call.outputs.start) {
+                return Err(LoadError::BadReference {
+                    node: call.outputs.start,
+                    arg,
+                });
+            }
+        }
+
+        for &input in &self.inputs {
+            if self.nodes.get(input).map(Node::op) != Some(&Op::Input) {
+                return Err(LoadError::Malformed(format!(
+                    "input {input} is not an input node"
+                )));
+            }
+        }
+        if let Some(&output) = self.outputs.iter().find(|&&output| output >= len) {
+            return Err(LoadError::Malformed(format!(
+                "output {output} is out of range"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Значение элементарной операции по значениям аргументов.
+fn evaluate(op: &Op, values: &[f64]) -> f64 {
+    match *op {
+        Op::Add(a, b) => values[a] + values[b],
+        Op::Sub(a, b) => values[a] - values[b],
+        Op::Mul(a, b) => values[a] * values[b],
+        Op::Div(a, b) => values[a] / values[b],
+        Op::Neg(a) => -values[a],
+        Op::Sin(a) => values[a].sin(),
+        Op::Cos(a) => values[a].cos(),
+        Op::Tan(a) => values[a].tan(),
+        Op::Exp(a) => values[a].exp(),
+        Op::Ln(a) => values[a].ln(),
+        Op::Sqrt(a) => values[a].sqrt(),
+        Op::Powi(a, exponent) => values[a].powi(exponent),
+        Op::Powf(a, exponent) => values[a].powf(exponent),
+        Op::Input | Op::Const | Op::Custom { .. } => unreachable!("not an elementary op"),
+    }
+}
+
+const TAG_POWI: u8 = 13;
+const TAG_POWF: u8 = 14;
+const TAG_CUSTOM: u8 = 15;
+/// Порядок имён задаёт числовые теги двоичного формата; менять только вместе
+/// с [`FORMAT_VERSION`].
+const TAG_NAMES: [&str; 16] = [
+    "input", "const", "add", "sub", "mul", "div", "neg", "sin", "cos", "tan", "exp", "ln", "sqrt",
+    "powi", "powf", "custom",
+];
+
+fn op_tag(op: &Op) -> u8 {
+    let position = TAG_NAMES
+        .iter()
+        .position(|&name| name == op.name())
+        .expect("every op has a tag");
+    u8::try_from(position).expect("tag fits into u8")
+}
+
+fn tag_name(tag: u8) -> Option<&'static str> {
+    TAG_NAMES.get(usize::from(tag)).copied()
+}
+
+fn name_arity(name: &str) -> usize {
+    match name {
+        "input" | "const" => 0,
+        "add" | "sub" | "mul" | "div" => 2,
+        _ => 1,
+    }
+}
+
+fn op_from_name(name: &str, args: &[usize], exponent: Option<&Value>) -> Result<Op, LoadError> {
+    let arity = name_arity(name);
+    if args.len() != arity || !TAG_NAMES.contains(&name) || name == "custom" {
+        return Err(LoadError::UnknownOp(format!("{name}/{}", args.len())));
+    }
+    let op = match name {
+        "input" => Op::Input,
+        "const" => Op::Const,
+        "add" => Op::Add(args[0], args[1]),
+        "sub" => Op::Sub(args[0], args[1]),
+        "mul" => Op::Mul(args[0], args[1]),
+        "div" => Op::Div(args[0], args[1]),
+        "neg" => Op::Neg(args[0]),
+        "sin" => Op::Sin(args[0]),
+        "cos" => Op::Cos(args[0]),
+        "tan" => Op::Tan(args[0]),
+        "exp" => Op::Exp(args[0]),
+        "ln" => Op::Ln(args[0]),
+        "sqrt" => Op::Sqrt(args[0]),
+        "powi" => {
+            let exponent = exponent
+                .and_then(Value::as_i64)
+                .and_then(|exponent| i32::try_from(exponent).ok())
+                .ok_or_else(|| LoadError::Json("`powi` without integer exponent".to_owned()))?;
+            Op::Powi(args[0], exponent)
+        }
+        "powf" => {
+            let bits = exponent
+                .and_then(Value::as_u64)
+                .ok_or_else(|| LoadError::Json("`powf` without exponent bits".to_owned()))?;
+            Op::Powf(args[0], f64::from_bits(bits))
+        }
+        _ => return Err(LoadError::UnknownOp(name.to_owned())),
+    };
+    Ok(op)
+}
+
+fn write_len(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend_from_slice(&(value as u64).to_le_bytes());
+}
+
+fn write_indices(bytes: &mut Vec<u8>, indices: &[usize]) {
+    write_len(bytes, indices.len());
+    for &index in indices {
+        write_len(bytes, index);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], LoadError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(LoadError::Truncated)?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], LoadError> {
+        let slice = self.take(N)?;
+        Ok(slice.try_into().expect("slice has exactly N bytes"))
+    }
+
+    fn index(&mut self) -> Result<usize, LoadError> {
+        let value = u64::from_le_bytes(self.array()?);
+        usize::try_from(value)
+            .map_err(|_| LoadError::Malformed(format!("index {value} is too large")))
+    }
+
+    fn indices(&mut self) -> Result<Vec<usize>, LoadError> {
+        let count = self.index()?;
+        // Не доверяем длине из файла при выделении памяти: каждое значение — 8 байт.
+        if count > (self.bytes.len() - self.position) / 8 {
+            return Err(LoadError::Truncated);
+        }
+        (0..count).map(|_| self.index()).collect()
+    }
+}
+
+fn json_array(value: &Value) -> Result<&Vec<Value>, LoadError> {
+    value
+        .as_array()
+        .ok_or_else(|| LoadError::Json(format!("expected an array, found {value}")))
+}
+
+fn json_u64(value: &Value) -> Result<u64, LoadError> {
+    value
+        .as_u64()
+        .ok_or_else(|| LoadError::Json(format!("expected an unsigned integer, found {value}")))
+}
+
+fn json_index(value: &Value) -> Result<usize, LoadError> {
+    let value = json_u64(value)?;
+    usize::try_from(value).map_err(|_| LoadError::Malformed(format!("index {value} is too large")))
+}
+
+fn json_indices(value: &Value) -> Result<Vec<usize>, LoadError> {
+    json_array(value)?.iter().map(json_index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomOpRegistry, LoadError, TapeSnapshot};
+    use crate::custom_ops::Softplus;
+    use crate::tape::{CustomOp, Tape};
+    use std::sync::Arc;
+
+    fn record(tape: &Tape) -> TapeSnapshot {
+        let x = tape.var(0.8);
+        let y = tape.var(-0.4);
+        let softplus = tape.custom(Arc::new(Softplus), &[x * y])[0];
+        let f = (x.sin() * y).exp() + softplus + x.powi(3) - y.powf(2.0) / 3.0;
+        let g = (x + 1.0).ln() * y;
+        TapeSnapshot::capture(tape, &[f, g])
+    }
+
+    fn registry() -> CustomOpRegistry {
+        let softplus: Arc<dyn CustomOp> = Arc::new(Softplus);
+        CustomOpRegistry::from([(softplus.name().to_owned(), softplus)])
+    }
+
+    #[test]
+    fn binary_and_json_round_trips_replay_identically() {
+        let tape = Tape::new();
+        let snapshot = record(&tape);
+        let recorded = snapshot.recorded_inputs();
+        let original = snapshot.replay(&recorded, &registry()).unwrap();
+
+        // Повторный прогон на записанных входах совпадает с исходной лентой.
+        let f = tape.var_at(snapshot.outputs()[0]);
+        let grads = tape.gradient(f);
+        assert_eq!(original.outputs[0].to_bits(), f.value().to_bits());
+        for (index, &input) in snapshot.inputs().iter().enumerate() {
+            let expected = grads.wrt(tape.var_at(input));
+            assert_eq!(original.gradients[0][index].to_bits(), expected.to_bits());
+        }
+
+        let from_bytes = TapeSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        let from_json = TapeSnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert_eq!(from_bytes, snapshot);
+        assert_eq!(from_json, snapshot);
+
+        let moved = [0.5, -0.3];
+        let expected = snapshot.replay(&moved, &registry()).unwrap();
+        assert_eq!(from_bytes.replay(&moved, &registry()).unwrap(), expected);
+        assert_eq!(from_json.replay(&moved, &registry()).unwrap(), expected);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let tape = Tape::new();
+        let bytes = record(&tape).to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            TapeSnapshot::from_bytes(&bad_magic),
+            Err(LoadError::BadMagic)
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert_eq!(
+            TapeSnapshot::from_bytes(&bad_version),
+            Err(LoadError::UnsupportedVersion(99))
+        );
+
+        for cut in [3, 10, bytes.len() / 2, bytes.len() - 1] {
+            assert_eq!(
+                TapeSnapshot::from_bytes(&bytes[..cut]),
+                Err(LoadError::Truncated)
+            );
+        }
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert_eq!(
+            TapeSnapshot::from_bytes(&trailing),
+            Err(LoadError::TrailingBytes)
+        );
+
+        // Первый узел `add` ссылается сам на себя.
+        let forward_reference = r#"{"format":"reverse_mode.tape","version":1,
+            "nodes":[{"op":"add","args":[0,0],"value":0}],
+            "custom_calls":[],"inputs":[],"outputs":[0]}"#;
+        assert_eq!(
+            TapeSnapshot::from_json(forward_reference),
+            Err(LoadError::BadReference { node: 0, arg: 0 })
+        );
+
+        // Номер выхода из файла не должен переполнять `outputs.start + output`.
+        let overflowing_output = format!(
+            r#"{{"format":"reverse_mode.tape","version":1,
+            "nodes":[{{"op":"input","args":[],"value":0}},
+                     {{"op":"custom","call":0,"output":{},"value":0}}],
+            "custom_calls":[{{"name":"softplus","inputs":[0],"outputs":[1,2]}}],
+            "inputs":[0],"outputs":[1]}}"#,
+            usize::MAX
+        );
+        assert!(matches!(
+            TapeSnapshot::from_json(&overflowing_output),
+            Err(LoadError::Malformed(_))
+        ));
+
+        let unknown = r#"{"format":"reverse_mode.tape","version":1,
+            "nodes":[{"op":"gamma","args":[],"value":0}],
+            "custom_calls":[],"inputs":[],"outputs":[0]}"#;
+        assert!(matches!(
+            TapeSnapshot::from_json(unknown),
+            Err(LoadError::UnknownOp(_))
+        ));
+        assert!(matches!(
+            TapeSnapshot::from_json("{"),
+            Err(LoadError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn replay_requires_registered_custom_ops() {
+        let tape = Tape::new();
+        let snapshot = record(&tape);
+        assert_eq!(
+            snapshot.replay(&[0.8, -0.4], &CustomOpRegistry::new()),
+            Err(LoadError::UnknownCustomOp("softplus".to_owned()))
+        );
+        assert_eq!(
+            snapshot.replay(&[0.8], &registry()),
+            Err(LoadError::InputCount {
+                expected: 2,
+                received: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::tape::{Node, Op, Tape, Var, powi_shifted};
+
+/// Разреженная матрица в формате CSR: ненулевые элементы построчно.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    /// Начало каждой строки в `col_indices`/`values`, длина `rows + 1`.
+    pub row_offsets: Vec<usize>,
+    pub col_indices: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+/// Разреженная матрица в формате CSC: ненулевые элементы по столбцам.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CscMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    /// Начало каждого столбца в `row_indices`/`values`, длина `cols + 1`.
+    pub col_offsets: Vec<usize>,
+    pub row_indices: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// Элемент `(row, col)`; вне шаблона разреженности — ноль.
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        self.col_indices[range.clone()]
+            .binary_search(&col)
+            .map_or(0.0, |position| self.values[range.start + position])
+    }
+
+    #[must_use]
+    pub const fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn to_dense(&self) -> Vec<Vec<f64>> {
+        let mut dense = vec![vec![0.0; self.cols]; self.rows];
+        for (row, dense_row) in dense.iter_mut().enumerate() {
+            for entry in self.row_offsets[row]..self.row_offsets[row + 1] {
+                dense_row[self.col_indices[entry]] = self.values[entry];
+            }
+        }
+        dense
+    }
+
+    #[must_use]
+    pub fn to_csc(&self) -> CscMatrix {
+        let mut col_offsets = vec![0; self.cols + 1];
+        for &col in &self.col_indices {
+            col_offsets[col + 1] += 1;
+        }
+        for col in 0..self.cols {
+            col_offsets[col + 1] += col_offsets[col];
+        }
+        let mut next = col_offsets.clone();
+        let mut row_indices = vec![0; self.nnz()];
+        let mut values = vec![0.0; self.nnz()];
+        for row in 0..self.rows {
+            for entry in self.row_offsets[row]..self.row_offsets[row + 1] {
+                let slot = &mut next[self.col_indices[entry]];
+                row_indices[*slot] = row;
+                values[*slot] = self.values[entry];
+                *slot += 1;
+            }
+        }
+        CscMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            col_offsets,
+            row_indices,
+            values,
+        }
+    }
+}
+
+/// Шаблон разреженности: отсортированные номера ненулевых столбцов каждой строки.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparsityPattern {
+    pub cols: usize,
+    pub rows: Vec<Vec<usize>>,
+}
+
+impl SparsityPattern {
+    #[must_use]
+    pub fn nnz(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// Транспонированный шаблон (строки становятся столбцами).
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut rows = vec![Vec::new(); self.cols];
+        for (row, cols) in self.rows.iter().enumerate() {
+            for &col in cols {
+                rows[col].push(row);
+            }
+        }
+        Self {
+            cols: self.rows.len(),
+            rows,
+        }
+    }
+
+    /// Жадная раскраска строк: две строки с общим ненулевым столбцом получают
+    /// разные цвета (раскраска на расстоянии 2 в двудольном графе строк и
+    /// столбцов). Строки одного цвета можно получить одним обратным проходом.
+    #[must_use]
+    pub fn color_rows(&self) -> Coloring {
+        let by_col = self.transpose();
+        let mut colors = vec![usize::MAX; self.rows.len()];
+        let mut forbidden = Vec::new();
+        let mut count = 0;
+        for (row, cols) in self.rows.iter().enumerate() {
+            for &col in cols {
+                for &neighbour in &by_col.rows[col] {
+                    if colors[neighbour] != usize::MAX {
+                        if forbidden.len() <= colors[neighbour] {
+                            forbidden.resize(colors[neighbour] + 1, usize::MAX);
+                        }
+                        forbidden[colors[neighbour]] = row;
+                    }
+                }
+            }
+            let color = (0..=forbidden.len())
+                .find(|&color| forbidden.get(color) != Some(&row))
+                .unwrap_or(forbidden.len());
+            colors[row] = color;
+            count = count.max(color + 1);
+        }
+        Coloring { colors, count }
+    }
+
+    /// Жадная раскраска столбцов: столбцы одного цвета не имеют общих строк.
+    #[must_use]
+    pub fn color_columns(&self) -> Coloring {
+        self.transpose().color_rows()
+    }
+}
+
+/// Цвет каждой строки (столбца) и число цветов, то есть число проходов.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coloring {
+    pub colors: Vec<usize>,
+    pub count: usize,
+}
+
+/// Разреженная производная и число проходов, потребовавшихся для её вычисления.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseDerivative {
+    pub matrix: CsrMatrix,
+    pub sweeps: usize,
+}
+
+/// Шаблон якобиана `outputs` по `inputs`: зависимости прослеживаются по ленте
+/// вперёд, множество входов каждого узла — объединение множеств аргументов.
+#[must_use]
+pub fn jacobian_pattern(tape: &Tape, inputs: &[Var<'_>], outputs: &[Var<'_>]) -> SparsityPattern {
+    let nodes = tape.nodes();
+    let calls = tape.custom_calls();
+    let positions = input_positions(inputs);
+    let mut deps: Vec<BTreeSet<usize>> = Vec::with_capacity(nodes.len());
+    for (index, node) in nodes.iter().enumerate() {
+        let set = match *node.op() {
+            Op::Input => positions.get(&index).into_iter().copied().collect(),
+            Op::Custom { call, .. } => union(&deps, calls[call].inputs()),
+            ref op => union(&deps, &op.args()),
+        };
+        deps.push(set);
+    }
+    SparsityPattern {
+        cols: inputs.len(),
+        rows: outputs
+            .iter()
+            .map(|output| deps[output.index()].iter().copied().collect())
+            .collect(),
+    }
+}
+
+/// Шаблон гессиана `output` по `inputs`.
+///
+/// Для каждого узла, влияющего на выход, отмечаются пары входов, которые
+/// встречаются в нелинейной операции: `x * y` связывает входы `x` с входами `y`,
+/// `sin(x)` — входы `x` между собой. Шаблон консервативен: ноль, получившийся
+/// из-за сокращения слагаемых, будет в шаблоне.
+#[must_use]
+pub fn hessian_pattern(tape: &Tape, inputs: &[Var<'_>], output: Var<'_>) -> SparsityPattern {
+    let nodes = tape.nodes();
+    let calls = tape.custom_calls();
+    let positions = input_positions(inputs);
+    let live = influences(&nodes, &calls, output.index());
+    let mut deps: Vec<BTreeSet<usize>> = Vec::with_capacity(nodes.len());
+    let mut rows = vec![BTreeSet::new(); inputs.len()];
+    let mut link = |lhs: &BTreeSet<usize>, rhs: &BTreeSet<usize>| {
+        for &i in lhs {
+            for &j in rhs {
+                rows[i].insert(j);
+                rows[j].insert(i);
+            }
+        }
+    };
+
+    for (index, node) in nodes.iter().enumerate() {
+        let set = match *node.op() {
+            Op::Input => positions.get(&index).into_iter().copied().collect(),
+            Op::Custom { call, .. } => union(&deps, calls[call].inputs()),
+            ref op => union(&deps, &op.args()),
+        };
+        if live[index] {
+            match *node.op() {
+                Op::Mul(a, b) => link(&deps[a], &deps[b]),
+                Op::Div(_, b) => link(&set, &deps[b]),
+                ref op if is_nonlinear(op) => link(&set, &set),
+                _ => {}
+            }
+        }
+        deps.push(set);
+    }
+    SparsityPattern {
+        cols: inputs.len(),
+        rows: rows
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect(),
+    }
+}
+
+/// Разреженный якобиан `outputs` по `inputs` с сжатием строк.
+///
+/// Строки раскрашиваются так, чтобы строки одного цвета не имели общих
+/// столбцов; для каждого цвета делается один обратный проход, засеянный во
+/// всех выходах этого цвета, и каждый ненулевой элемент читается напрямую.
+/// Число проходов равно числу цветов, а не числу выходов.
+///
+/// # Panics
+/// Если переменные принадлежат другой ленте.
+#[must_use]
+pub fn sparse_jacobian(tape: &Tape, inputs: &[Var<'_>], outputs: &[Var<'_>]) -> SparseDerivative {
+    let pattern = jacobian_pattern(tape, inputs, outputs);
+    let coloring = pattern.color_rows();
+    let mut values: Vec<Vec<f64>> = pattern
+        .rows
+        .iter()
+        .map(|row| vec![0.0; row.len()])
+        .collect();
+
+    for color in 0..coloring.count {
+        let seeds: Vec<(Var<'_>, f64)> = outputs
+            .iter()
+            .zip(&coloring.colors)
+            .filter(|&(_, &row_color)| row_color == color)
+            .map(|(&output, _)| (output, 1.0))
+            .collect();
+        let grads = tape.reverse_many(&seeds);
+        for (row, &row_color) in coloring.colors.iter().enumerate() {
+            if row_color != color {
+                continue;
+            }
+            for (value, &col) in values[row].iter_mut().zip(&pattern.rows[row]) {
+                *value = grads.wrt(inputs[col]);
+            }
+        }
+    }
+
+    SparseDerivative {
+        matrix: to_csr(&pattern, &values),
+        sweeps: coloring.count,
+    }
+}
+
+/// Разреженный гессиан `output` по `inputs`.
+///
+/// Столбцы гессиана раскрашиваются так, чтобы столбцы одного цвета не имели
+/// общих ненулевых строк (раскраска на расстоянии 2 в графе смежности), и для
+/// каждого цвета считается одно произведение гессиана на вектор (прямой
+/// проход касательных и обратный проход сопряжённых вместе с их производными).
+///
+/// # Panics
+/// Если на ленте есть пользовательские операции: для них известен только
+/// первый порядок.
+#[must_use]
+pub fn sparse_hessian(tape: &Tape, inputs: &[Var<'_>], output: Var<'_>) -> SparseDerivative {
+    let pattern = hessian_pattern(tape, inputs, output);
+    let coloring = pattern.color_columns();
+    let mut values: Vec<Vec<f64>> = pattern
+        .rows
+        .iter()
+        .map(|row| vec![0.0; row.len()])
+        .collect();
+
+    for color in 0..coloring.count {
+        let direction: Vec<f64> = coloring
+            .colors
+            .iter()
+            .map(|&col_color| if col_color == color { 1.0 } else { 0.0 })
+            .collect();
+        let product = hessian_vector_product(tape, inputs, output, &direction);
+        for (row, cols) in pattern.rows.iter().enumerate() {
+            for (value, &col) in values[row].iter_mut().zip(cols) {
+                if coloring.colors[col] == color {
+                    *value = product[row];
+                }
+            }
+        }
+    }
+
+    SparseDerivative {
+        matrix: to_csr(&pattern, &values),
+        sweeps: coloring.count,
+    }
+}
+
+/// Произведение гессиана `output` по `inputs` на вектор `direction`.
+///
+/// # Panics
+/// Если длины `inputs` и `direction` различаются или на ленте есть
+/// пользовательские операции.
+#[must_use]
+pub fn hessian_vector_product(
+    tape: &Tape,
+    inputs: &[Var<'_>],
+    output: Var<'_>,
+    direction: &[f64],
+) -> Vec<f64> {
+    assert_eq!(
+        inputs.len(),
+        direction.len(),
+        "Direction has {} components for {} inputs.",
+        direction.len(),
+        inputs.len()
+    );
+    let nodes = tape.nodes();
+    let positions = input_positions(inputs);
+    let value = |index: usize| nodes[index].value();
+
+    let mut tangents = vec![0.0; nodes.len()];
+    for index in 0..=output.index() {
+        let node = &nodes[index];
+        tangents[index] = match *node.op() {
+            Op::Input => positions
+                .get(&index)
+                .map_or(0.0, |&position| direction[position]),
+            Op::Const => 0.0,
+            Op::Add(a, b) => tangents[a] + tangents[b],
+            Op::Sub(a, b) => tangents[a] - tangents[b],
+            Op::Mul(a, b) => tangents[a].mul_add(value(b), value(a) * tangents[b]),
+            Op::Div(a, b) => node.value().mul_add(-tangents[b], tangents[a]) / value(b),
+            Op::Custom { .. } => panic!("Second derivatives of custom ops are not supported."),
+            ref op => {
+                let arg = op.args()[0];
+                unary_derivatives(op, value(arg), node.value()).0 * tangents[arg]
+            }
+        };
+    }
+
+    let mut adjoints = vec![0.0_f64; nodes.len()];
+    let mut adjoint_tangents = vec![0.0_f64; nodes.len()];
+    adjoints[output.index()] = 1.0;
+    for index in (0..=output.index()).rev() {
+        let (adjoint, adjoint_tangent) = (adjoints[index], adjoint_tangents[index]);
+        if adjoint == 0.0 && adjoint_tangent == 0.0 {
+            continue;
+        }
+        let node = &nodes[index];
+        match *node.op() {
+            Op::Input | Op::Const | Op::Custom { .. } => {}
+            Op::Add(a, b) => {
+                adjoints[a] += adjoint;
+                adjoint_tangents[a] += adjoint_tangent;
+                adjoints[b] += adjoint;
+                adjoint_tangents[b] += adjoint_tangent;
+            }
+            Op::Sub(a, b) => {
+                adjoints[a] += adjoint;
+                adjoint_tangents[a] += adjoint_tangent;
+                adjoints[b] -= adjoint;
+                adjoint_tangents[b] -= adjoint_tangent;
+            }
+            Op::Mul(a, b) => {
+                adjoints[a] += adjoint * value(b);
+                adjoint_tangents[a] += adjoint_tangent.mul_add(value(b), adjoint * tangents[b]);
+                adjoints[b] += adjoint * value(a);
+                adjoint_tangents[b] += adjoint_tangent.mul_add(value(a), adjoint * tangents[a]);
+            }
+            Op::Div(a, b) => {
+                let (denominator, quotient) = (value(b), node.value());
+                let (tangent_b, tangent) = (tangents[b], tangents[index]);
+                adjoints[a] += adjoint / denominator;
+                adjoint_tangents[a] +=
+                    adjoint.mul_add(-tangent_b / denominator, adjoint_tangent) / denominator;
+                adjoints[b] -= adjoint * quotient / denominator;
+                adjoint_tangents[b] -= (adjoint_tangent.mul_add(quotient, adjoint * tangent)
+                    - adjoint * quotient * tangent_b / denominator)
+                    / denominator;
+            }
+            ref op => {
+                let arg = op.args()[0];
+                let (first, second) = unary_derivatives(op, value(arg), node.value());
+                adjoints[arg] += adjoint * first;
+                adjoint_tangents[arg] +=
+                    adjoint_tangent.mul_add(first, adjoint * second * tangents[arg]);
+            }
+        }
+    }
+
+    inputs
+        .iter()
+        .map(|input| adjoint_tangents[input.index()])
+        .collect()
+}
+
+/// Унарная операция с ненулевой второй производной (пользовательские
+/// операции считаются нелинейными по всем входам).
+#[allow(clippy::float_cmp)]
+const fn is_nonlinear(op: &Op) -> bool {
+    match *op {
+        Op::Sin(_)
+        | Op::Cos(_)
+        | Op::Tan(_)
+        | Op::Exp(_)
+        | Op::Ln(_)
+        | Op::Sqrt(_)
+        | Op::Custom { .. } => true,
+        Op::Powi(_, exponent) => exponent != 0 && exponent != 1,
+        Op::Powf(_, exponent) => exponent != 0.0 && exponent != 1.0,
+        _ => false,
+    }
+}
+
+/// Первая и вторая производные унарной операции по аргументу `x`
+/// при значении результата `y`.
+fn unary_derivatives(op: &Op, x: f64, y: f64) -> (f64, f64) {
+    match *op {
+        Op::Neg(_) => (-1.0, 0.0),
+        Op::Sin(_) => (x.cos(), -y),
+        Op::Cos(_) => (-x.sin(), -y),
+        Op::Tan(_) => {
+            let secant2 = y.mul_add(y, 1.0);
+            (secant2, 2.0 * y * secant2)
+        }
+        Op::Exp(_) => (y, y),
+        Op::Ln(_) => (1.0 / x, -1.0 / (x * x)),
+        Op::Sqrt(_) => (0.5 / y, -0.25 / (y * x)),
+        Op::Powi(_, exponent) => {
+            let n = f64::from(exponent);
+            (
+                n * powi_shifted(x, exponent, 1),
+                n * (n - 1.0) * powi_shifted(x, exponent, 2),
+            )
+        }
+        Op::Powf(_, exponent) => (
+            exponent * x.powf(exponent - 1.0),
+            exponent * (exponent - 1.0) * x.powf(exponent - 2.0),
+        ),
+        _ => unreachable!("{} is not a unary op", op.name()),
+    }
+}
+
+fn input_positions(inputs: &[Var<'_>]) -> HashMap<usize, usize> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(position, input)| (input.index(), position))
+        .collect()
+}
+
+fn union(deps: &[BTreeSet<usize>], args: &[usize]) -> BTreeSet<usize> {
+    args.iter()
+        .flat_map(|&arg| deps[arg].iter().copied())
+        .collect()
+}
+
+/// Узлы, от которых зависит `output`.
+fn influences(nodes: &[Node], calls: &[crate::tape::CustomCall], output: usize) -> Vec<bool> {
+    let mut live = vec![false; nodes.len()];
+    live[output] = true;
+    for index in (0..=output).rev() {
+        if !live[index] {
+            continue;
+        }
+        let args = match *nodes[index].op() {
+            Op::Custom { call, .. } => calls[call].inputs().to_vec(),
+            ref op => op.args(),
+        };
+        for arg in args {
+            live[arg] = true;
+        }
+    }
+    live
+}
+
+fn to_csr(pattern: &SparsityPattern, values: &[Vec<f64>]) -> CsrMatrix {
+    let mut row_offsets = Vec::with_capacity(pattern.rows.len() + 1);
+    row_offsets.push(0);
+    for row in &pattern.rows {
+        row_offsets.push(row_offsets.last().copied().unwrap_or(0) + row.len());
+    }
+    CsrMatrix {
+        rows: pattern.rows.len(),
+        cols: pattern.cols,
+        row_offsets,
+        col_indices: pattern.rows.concat(),
+        values: values.concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hessian_vector_product, sparse_hessian, sparse_jacobian};
+    use crate::tape::{Tape, Var};
+
+    /// Цепная функция Розенброка: гессиан трёхдиагональный.
+    fn rosenbrock<'t>(x: &[Var<'t>]) -> Var<'t> {
+        x.windows(2)
+            .map(|pair| {
+                let step = pair[1] - pair[0].powi(2);
+                let offset = -pair[0] + 1.0;
+                step * step * 100.0 + offset * offset
+            })
+            .reduce(|sum, term| sum + term)
+            .unwrap()
+    }
+
+    #[test]
+    fn banded_jacobian_needs_few_sweeps() {
+        let tape = Tape::new();
+        let x: Vec<Var<'_>> = (0..12)
+            .map(|i| tape.var(0.3f64.mul_add(f64::from(i), 0.1)))
+            .collect();
+        let residuals: Vec<Var<'_>> = x
+            .windows(3)
+            .map(|w| w[0].sin() * w[1] - w[2].exp())
+            .collect();
+
+        let jacobian = sparse_jacobian(&tape, &x, &residuals);
+        assert_eq!(jacobian.sweeps, 3);
+        assert_eq!(jacobian.matrix.nnz(), 3 * residuals.len());
+
+        for (row, &residual) in residuals.iter().enumerate() {
+            let grads = tape.gradient(residual);
+            for (col, &input) in x.iter().enumerate() {
+                let dense = grads.wrt(input);
+                assert!((jacobian.matrix.get(row, col) - dense).abs() < 1e-12);
+            }
+        }
+
+        let csc = jacobian.matrix.to_csc();
+        assert_eq!(csc.col_offsets.len(), x.len() + 1);
+        assert_eq!(csc.values.len(), jacobian.matrix.nnz());
+        let first_col = &csc.row_indices[csc.col_offsets[0]..csc.col_offsets[1]];
+        assert_eq!(first_col, &[0]);
+    }
+
+    #[test]
+    fn tridiagonal_hessian_matches_dense() {
+        let tape = Tape::new();
+        let x: Vec<Var<'_>> = (0..10)
+            .map(|i| tape.var(0.1f64.mul_add(f64::from(i), -0.4)))
+            .collect();
+        let f = rosenbrock(&x);
+
+        let hessian = sparse_hessian(&tape, &x, f);
+        assert_eq!(hessian.sweeps, 3);
+        assert_eq!(hessian.matrix.nnz(), 3 * x.len() - 2);
+
+        for col in 0..x.len() {
+            let mut unit = vec![0.0; x.len()];
+            unit[col] = 1.0;
+            let column = hessian_vector_product(&tape, &x, f, &unit);
+            for (row, value) in column.iter().enumerate() {
+                assert!((hessian.matrix.get(row, col) - value).abs() < 1e-9);
+            }
+        }
+
+        // Аналитически: ∂²f/∂x₀² = 1200 x₀² - 400 x₁ + 2, ∂²f/∂x₀∂x₁ = -400 x₀.
+        let (x0, x1) = (x[0].value(), x[1].value());
+        let expected = (1200.0 * x0).mul_add(x0, 400.0f64.mul_add(-x1, 2.0));
+        assert!((hessian.matrix.get(0, 0) - expected).abs() < 1e-9);
+        assert!(400.0f64.mul_add(x0, hessian.matrix.get(0, 1)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hessian_of_powi_near_minimum_exponent_does_not_overflow() {
+        for exponent in [i32::MIN, i32::MIN + 1] {
+            let tape = Tape::new();
+            let x = tape.var(1.0);
+            let hessian = sparse_hessian(&tape, &[x], x.powi(exponent));
+            let n = f64::from(exponent);
+            let expected = n * (n - 1.0);
+            assert!(
+                (hessian.matrix.get(0, 0) - expected).abs() <= 1e-12 * expected,
+                "powi({exponent}): {} vs {expected}",
+                hessian.matrix.get(0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn hessian_vector_product_handles_quotients_and_roots() {
+        let tape = Tape::new();
+        let x = tape.var(1.3);
+        let y = tape.var(0.7);
+        let f = (x / y).ln() + (x * y).sqrt() + x.tan() * y.cos();
+
+        let h = 1e-5;
+        let gradient = |dx: f64, dy: f64| {
+            let tape = Tape::new();
+            let x = tape.var(1.3 + dx);
+            let y = tape.var(0.7 + dy);
+            let f = (x / y).ln() + (x * y).sqrt() + x.tan() * y.cos();
+            let grads = tape.gradient(f);
+            [grads.wrt(x), grads.wrt(y)]
+        };
+        for (direction, (dx, dy)) in [([1.0, 0.0], (h, 0.0)), ([0.0, 1.0], (0.0, h))] {
+            let exact = hessian_vector_product(&tape, &[x, y], f, &direction);
+            let (plus, minus) = (gradient(dx, dy), gradient(-dx, -dy));
+            for (component, value) in exact.iter().enumerate() {
+                let approx = (plus[component] - minus[component]) / (2.0 * h);
+                assert!((value - approx).abs() < 1e-6, "{value} vs {approx}");
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem::size_of;
+use std::time::Duration;
+
+use crate::real::Real;
+use crate::tape::{Comparison, CustomCall, Node};
+
+/// Стоимость ленты: состав узлов, занятая память и время проходов.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TapeStats {
+    pub nodes: usize,
+    /// Число узлов по имени операции ([`crate::tape::Op::name`]).
+    pub by_op: BTreeMap<&'static str, usize>,
+    pub custom_calls: usize,
+    pub comparisons: usize,
+    /// Байты под записанные узлы, вызовы и сравнения.
+    pub bytes_used: usize,
+    /// Байты, зарезервированные под них (с учётом ёмкости буферов).
+    pub bytes_reserved: usize,
+    /// Наибольшая длина ленты с момента создания, в том числе до `clear`.
+    pub peak_nodes: usize,
+    /// Время записи: от первого узла до начала первого обратного прохода.
+    pub forward_time: Duration,
+    /// Суммарное время всех обратных проходов.
+    pub reverse_time: Duration,
+    pub reverse_sweeps: usize,
+}
+
+impl TapeStats {
+    pub(crate) fn collect<T: Real>(
+        nodes: &Vec<Node<T>>,
+        calls: &Vec<CustomCall>,
+        comparisons: &Vec<Comparison>,
+    ) -> Self {
+        let mut by_op = BTreeMap::new();
+        for node in nodes {
+            *by_op.entry(node.op().name()).or_insert(0) += 1;
+        }
+        let call_inputs: usize = calls.iter().map(|call| call.inputs().len()).sum();
+        let reserved_inputs: usize = calls.iter().map(CustomCall::inputs_capacity).sum();
+
+        Self {
+            nodes: nodes.len(),
+            by_op,
+            custom_calls: calls.len(),
+            comparisons: comparisons.len(),
+            bytes_used: nodes.len() * size_of::<Node<T>>()
+                + calls.len() * size_of::<CustomCall>()
+                + call_inputs * size_of::<usize>()
+                + comparisons.len() * size_of::<Comparison>(),
+            bytes_reserved: nodes.capacity() * size_of::<Node<T>>()
+                + calls.capacity() * size_of::<CustomCall>()
+                + reserved_inputs * size_of::<usize>()
+                + comparisons.capacity() * size_of::<Comparison>(),
+            peak_nodes: nodes.len(),
+            ..Self::default()
+        }
+    }
+}
+
+impl fmt::Display for TapeStats {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "узлов: {} (пик {}), вызовов: {}, сравнений: {}",
+            self.nodes, self.peak_nodes, self.custom_calls, self.comparisons
+        )?;
+        writeln!(
+            formatter,
+            "память: {} байт занято, {} зарезервировано",
+            self.bytes_used, self.bytes_reserved
+        )?;
+        writeln!(
+            formatter,
+            "прямой проход: {:?}, обратные проходы: {} за {:?}",
+            self.forward_time, self.reverse_sweeps, self.reverse_time
+        )?;
+        let ops: Vec<String> = self
+            .by_op
+            .iter()
+            .map(|(name, count)| format!("{name}: {count}"))
+            .collect();
+        write!(formatter, "операции: {}", ops.join(", "))
+    }
+}
@@ -0,0 +1,1137 @@
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Range, Sub};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::real::Real;
+use crate::stats::TapeStats;
+
+/// Пользовательская операция с ручным сопряжённым правилом (VJP).
+///
+/// Операция может иметь несколько входов и несколько выходов. На прямом проходе
+/// `forward` получает значения входов и возвращает значения выходов, на обратном
+/// `backward` по сопряжённым выходов возвращает вклад в сопряжённые входов.
+pub trait CustomOp: Send + Sync {
+    /// Имя операции (используется в отладочном выводе).
+    fn name(&self) -> &str;
+
+    /// Прямой проход: значения выходов по значениям входов.
+    fn forward(&self, inputs: &[f64]) -> Vec<f64>;
+
+    /// Обратный проход: `∂L/∂inputs` по `∂L/∂outputs`.
+    /// Должен вернуть ровно `inputs.len()` значений.
+    fn backward(&self, inputs: &[f64], outputs: &[f64], output_adjoints: &[f64]) -> Vec<f64>;
+}
+
+/// Операция, записанная в узел ленты. Индексы ссылаются на более ранние узлы.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Op {
+    Input,
+    Const,
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Neg(usize),
+    Sin(usize),
+    Cos(usize),
+    Tan(usize),
+    Exp(usize),
+    Ln(usize),
+    Sqrt(usize),
+    Powi(usize, i32),
+    Powf(usize, f64),
+    /// Выход `output` пользовательской операции номер `call`.
+    Custom {
+        call: usize,
+        output: usize,
+    },
+}
+
+impl Op {
+    /// Короткое имя операции (для экспорта графа и статистики).
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Const => "const",
+            Self::Add(..) => "add",
+            Self::Sub(..) => "sub",
+            Self::Mul(..) => "mul",
+            Self::Div(..) => "div",
+            Self::Neg(_) => "neg",
+            Self::Sin(_) => "sin",
+            Self::Cos(_) => "cos",
+            Self::Tan(_) => "tan",
+            Self::Exp(_) => "exp",
+            Self::Ln(_) => "ln",
+            Self::Sqrt(_) => "sqrt",
+            Self::Powi(..) => "powi",
+            Self::Powf(..) => "powf",
+            Self::Custom { .. } => "custom",
+        }
+    }
+
+    /// Индексы узлов-аргументов элементарной операции.
+    /// Для пользовательских операций аргументы хранятся в [`CustomCall`].
+    #[must_use]
+    pub fn args(&self) -> Vec<usize> {
+        match *self {
+            Self::Input | Self::Const | Self::Custom { .. } => Vec::new(),
+            Self::Add(a, b) | Self::Sub(a, b) | Self::Mul(a, b) | Self::Div(a, b) => vec![a, b],
+            Self::Neg(a)
+            | Self::Sin(a)
+            | Self::Cos(a)
+            | Self::Tan(a)
+            | Self::Exp(a)
+            | Self::Ln(a)
+            | Self::Sqrt(a)
+            | Self::Powi(a, _)
+            | Self::Powf(a, _) => vec![a],
+        }
+    }
+}
+
+/// Узел ленты: операция и её значение на прямом проходе.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<T = f64> {
+    op: Op,
+    value: T,
+}
+
+impl<T: Real> Node<T> {
+    pub(crate) const fn new(op: Op, value: T) -> Self {
+        Self { op, value }
+    }
+
+    #[must_use]
+    pub const fn op(&self) -> &Op {
+        &self.op
+    }
+
+    #[must_use]
+    pub const fn value(&self) -> T {
+        self.value
+    }
+}
+
+/// Вызов пользовательской операции: её входы и непрерывный диапазон узлов-выходов.
+#[derive(Clone)]
+pub struct CustomCall {
+    op: Arc<dyn CustomOp>,
+    inputs: Vec<usize>,
+    outputs: Range<usize>,
+}
+
+impl CustomCall {
+    pub(crate) const fn new(
+        op: Arc<dyn CustomOp>,
+        inputs: Vec<usize>,
+        outputs: Range<usize>,
+    ) -> Self {
+        Self {
+            op,
+            inputs,
+            outputs,
+        }
+    }
+
+    pub(crate) fn shared_op(&self) -> Arc<dyn CustomOp> {
+        Arc::clone(&self.op)
+    }
+
+    #[must_use]
+    pub fn op(&self) -> &dyn CustomOp {
+        self.op.as_ref()
+    }
+
+    #[must_use]
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    #[must_use]
+    pub fn outputs(&self) -> Range<usize> {
+        self.outputs.clone()
+    }
+
+    pub(crate) const fn inputs_capacity(&self) -> usize {
+        self.inputs.capacity()
+    }
+}
+
+impl fmt::Debug for CustomCall {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("CustomCall")
+            .field("op", &self.op.name())
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .finish()
+    }
+}
+
+/// Допуск по умолчанию, в пределах которого сравнение считается почти ничьей.
+pub const DEFAULT_TIE_TOLERANCE: f64 = 1e-9;
+
+/// Вид сравнения, записанного на ленту.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl CmpOp {
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn apply(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+            Self::Eq => lhs == rhs,
+        }
+    }
+}
+
+/// Сравнение двух узлов, выполненное на прямом проходе.
+///
+/// Градиента у сравнения нет, но от его результата зависит, какая ветвь
+/// записалась на ленту. По индексам узлов нельзя понять, на что повлияло
+/// сравнение (при `x == y` в `primal_with_tape` выход — узел, записанный ещё до
+/// сравнения), поэтому после обратного прохода проверяются все сравнения ленты.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    pub op: CmpOp,
+    pub lhs: usize,
+    pub rhs: usize,
+    pub result: bool,
+    /// `lhs - rhs` на прямом проходе.
+    pub margin: f64,
+}
+
+impl Comparison {
+    /// Операнды отличаются не больше чем на `tolerance` (абсолютно для малых
+    /// значений и относительно для больших).
+    #[must_use]
+    pub fn is_near_tie(&self, lhs: f64, rhs: f64, tolerance: f64) -> bool {
+        self.margin.abs() <= tolerance * lhs.abs().max(rhs.abs()).max(1.0)
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "n{} {} n{} = {} (разность {:e})",
+            self.lhs,
+            self.op.symbol(),
+            self.rhs,
+            self.result,
+            self.margin
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Recording<T: Real> {
+    nodes: Vec<Node<T>>,
+    calls: Vec<CustomCall>,
+    comparisons: Vec<Comparison>,
+    timing: Timing,
+}
+
+/// Счётчики для [`TapeStats`], переживающие [`Tape::clear`] (кроме времени записи).
+#[derive(Clone, Copy, Debug, Default)]
+struct Timing {
+    started: Option<Instant>,
+    forward: Option<Duration>,
+    reverse: Duration,
+    sweeps: usize,
+    peak: usize,
+}
+
+/// Лента (Wengert list) для reverse-mode: на прямом проходе каждая операция над
+/// [`Var`] дописывает узел, обратный проход идёт по узлам в обратном порядке.
+///
+/// Значения прямого прохода хранятся в `T` (`f64` по умолчанию или `f32`).
+#[derive(Debug)]
+pub struct Tape<T: Real = f64> {
+    recording: RefCell<Recording<T>>,
+    tie_tolerance: f64,
+}
+
+impl<T: Real> Default for Tape<T> {
+    fn default() -> Self {
+        Self::with_tie_tolerance(DEFAULT_TIE_TOLERANCE)
+    }
+}
+
+impl Tape {
+    /// Лента со значениями в `f64`; для других типов — `Tape::<f32>::default()`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Real> Tape<T> {
+    /// Лента, которая после обратного прохода сообщает о сравнениях, чьи
+    /// операнды отличались не больше чем на `tolerance`.
+    #[must_use]
+    pub fn with_tie_tolerance(tolerance: f64) -> Self {
+        Self {
+            recording: RefCell::default(),
+            tie_tolerance: tolerance,
+        }
+    }
+
+    /// Лента с заранее выделенным местом под `nodes` узлов.
+    ///
+    /// Вместе с [`Tape::clear`] работает как арена: в цикле оптимизатора
+    /// одна и та же память переиспользуется на каждой итерации.
+    #[must_use]
+    pub fn with_capacity(nodes: usize) -> Self {
+        let tape = Self::default();
+        tape.reserve(nodes);
+        tape
+    }
+
+    /// Резервирует место ещё под `additional` узлов.
+    pub fn reserve(&self, additional: usize) {
+        self.recording.borrow_mut().nodes.reserve(additional);
+    }
+
+    /// Сколько узлов помещается без перевыделения памяти.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.recording.borrow().nodes.capacity()
+    }
+
+    /// Стирает записанные узлы, вызовы и сравнения, сохраняя выделенную память.
+    ///
+    /// Требует `&mut self`, поэтому на момент очистки ни одной [`Var`] этой
+    /// ленты уже нет. Пик длины и суммарное время обратных проходов сохраняются.
+    pub fn clear(&mut self) {
+        let recording = self.recording.get_mut();
+        recording.timing.peak = recording.timing.peak.max(recording.nodes.len());
+        recording.timing.started = None;
+        recording.timing.forward = None;
+        recording.nodes.clear();
+        recording.calls.clear();
+        recording.comparisons.clear();
+    }
+
+    /// Статистика ленты: узлы по операциям, память, пик длины и время проходов.
+    #[must_use]
+    pub fn stats(&self) -> TapeStats {
+        let recording = self.recording.borrow();
+        let timing = recording.timing;
+        let mut stats =
+            TapeStats::collect(&recording.nodes, &recording.calls, &recording.comparisons);
+        stats.peak_nodes = stats.peak_nodes.max(timing.peak);
+        stats.forward_time = timing
+            .forward
+            .or_else(|| timing.started.map(|started| started.elapsed()))
+            .unwrap_or_default();
+        stats.reverse_time = timing.reverse;
+        stats.reverse_sweeps = timing.sweeps;
+        stats
+    }
+
+    /// Независимая переменная (вход функции).
+    pub fn var(&self, value: T) -> Var<'_, T> {
+        self.push(Op::Input, value)
+    }
+
+    /// Константа: участвует в вычислениях, но градиент по ней не нужен.
+    pub fn constant(&self, value: T) -> Var<'_, T> {
+        self.push(Op::Const, value)
+    }
+
+    /// Переменная, соответствующая уже записанному узлу `index`.
+    ///
+    /// # Panics
+    /// Если узла с таким индексом нет.
+    #[must_use]
+    pub fn var_at(&self, index: usize) -> Var<'_, T> {
+        let value = self.recording.borrow().nodes[index].value;
+        Var {
+            tape: self,
+            index,
+            value,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.recording.borrow().nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.recording.borrow().nodes.is_empty()
+    }
+
+    /// Записанные узлы в порядке прямого прохода.
+    pub fn nodes(&self) -> Ref<'_, [Node<T>]> {
+        Ref::map(self.recording.borrow(), |recording| {
+            recording.nodes.as_slice()
+        })
+    }
+
+    /// Записанные вызовы пользовательских операций.
+    pub fn custom_calls(&self) -> Ref<'_, [CustomCall]> {
+        Ref::map(self.recording.borrow(), |recording| {
+            recording.calls.as_slice()
+        })
+    }
+
+    /// Сравнения, выполненные через [`Var::lt`], [`Var::gt`] и т. п.
+    pub fn comparisons(&self) -> Ref<'_, [Comparison]> {
+        Ref::map(self.recording.borrow(), |recording| {
+            recording.comparisons.as_slice()
+        })
+    }
+
+    /// Записывает пользовательскую операцию и возвращает её выходы.
+    /// Операция вычисляется в `f64`, выходы округляются до `T`.
+    ///
+    /// # Panics
+    /// Если входы принадлежат другой ленте.
+    pub fn custom<'t>(&'t self, op: Arc<dyn CustomOp>, inputs: &[Var<'t, T>]) -> Vec<Var<'t, T>> {
+        for input in inputs {
+            self.assert_owns(*input);
+        }
+        let input_values: Vec<f64> = inputs.iter().map(|input| input.value.to_f64()).collect();
+        let output_values: Vec<T> = op
+            .forward(&input_values)
+            .into_iter()
+            .map(T::from_f64)
+            .collect();
+
+        let mut recording = self.recording.borrow_mut();
+        let call = recording.calls.len();
+        let start = recording.nodes.len();
+        let outputs = start..start + output_values.len();
+        recording.calls.push(CustomCall {
+            op,
+            inputs: inputs.iter().map(|input| input.index).collect(),
+            outputs: outputs.clone(),
+        });
+        recording.nodes.extend(
+            output_values
+                .iter()
+                .enumerate()
+                .map(|(output, &value)| Node {
+                    op: Op::Custom { call, output },
+                    value,
+                }),
+        );
+
+        outputs
+            .zip(output_values)
+            .map(|(index, value)| Var {
+                tape: self,
+                index,
+                value,
+            })
+            .collect()
+    }
+
+    /// Градиент `output` по всем узлам ленты (seed = 1).
+    #[must_use]
+    pub fn gradient(&self, output: Var<'_, T>) -> Gradients<T> {
+        self.reverse(output, T::ONE)
+    }
+
+    /// Градиент `output` с накоплением сопряжённых в типе `A`.
+    ///
+    /// Например, `Tape<f32>::gradient_in::<f64>` считает прямой проход в `f32`, а
+    /// суммирует вклады в `f64`: при большом числе слагаемых (длинная выборка в
+    /// функции потерь) это убирает накопленную ошибку округления сумм.
+    #[must_use]
+    pub fn gradient_in<A: Real>(&self, output: Var<'_, T>) -> Gradients<A> {
+        self.reverse(output, A::ONE)
+    }
+
+    /// Обратный проход от `output` с начальным сопряжённым `seed`.
+    ///
+    /// Тип сопряжённых `A` задаётся типом `seed`. Локальные производные
+    /// считаются в `A` по значениям прямого прохода, переведённым из `T`.
+    ///
+    /// # Panics
+    /// Если `output` принадлежит другой ленте или пользовательская операция
+    /// вернула сопряжённые неверной длины.
+    #[must_use]
+    pub fn reverse<A: Real>(&self, output: Var<'_, T>, seed: A) -> Gradients<A> {
+        self.reverse_many(&[(output, seed)])
+    }
+
+    /// Обратный проход, засеянный сразу в нескольких узлах: сопряжённые равны
+    /// градиенту `Σ seedᵢ · outputᵢ`. Используется для сжатых якобианов, где
+    /// строки одного цвета не имеют общих входов.
+    ///
+    /// # Panics
+    /// Как [`Tape::reverse`].
+    #[must_use]
+    pub fn reverse_many<A: Real>(&self, seeds: &[(Var<'_, T>, A)]) -> Gradients<A> {
+        for &(output, _) in seeds {
+            self.assert_owns(output);
+        }
+        let started = Instant::now();
+        {
+            let timing = &mut self.recording.borrow_mut().timing;
+            if timing.forward.is_none() {
+                timing.forward = timing.started.map(|recorded| started - recorded);
+            }
+        }
+
+        let seeds: Vec<(usize, A)> = seeds
+            .iter()
+            .map(|&(output, seed)| (output.index, seed))
+            .collect();
+        let gradients = self.sweep(&seeds);
+
+        let timing = &mut self.recording.borrow_mut().timing;
+        timing.reverse += started.elapsed();
+        timing.sweeps += 1;
+        gradients
+    }
+
+    fn sweep<A: Real>(&self, seeds: &[(usize, A)]) -> Gradients<A> {
+        let recording = self.recording.borrow();
+        let nodes = &recording.nodes;
+        let mut adjoints = vec![A::ZERO; nodes.len()];
+        for &(output, seed) in seeds {
+            adjoints[output] += seed;
+        }
+        let end = seeds
+            .iter()
+            .map(|&(output, _)| output + 1)
+            .max()
+            .unwrap_or(0);
+
+        for index in (0..end).rev() {
+            let adjoint = adjoints[index];
+            let node = &nodes[index];
+            let value_of = |arg: usize| nodes[arg].value.cast::<A>();
+            let value = node.value.cast::<A>();
+
+            match node.op {
+                Op::Input | Op::Const => {}
+                Op::Custom { call, output } => {
+                    // Все выходы вызова идут подряд и потребляются только позже,
+                    // поэтому на первом выходе их сопряжённые уже окончательны.
+                    if output != 0 {
+                        continue;
+                    }
+                    let call = &recording.calls[call];
+                    let output_adjoints: Vec<f64> = adjoints[call.outputs.clone()]
+                        .iter()
+                        .map(|adjoint| adjoint.to_f64())
+                        .collect();
+                    if output_adjoints.iter().all(|&adjoint| adjoint == 0.0) {
+                        continue;
+                    }
+                    let to_f64 = |arg: usize| nodes[arg].value.to_f64();
+                    let inputs: Vec<f64> = call.inputs.iter().map(|&arg| to_f64(arg)).collect();
+                    let outputs: Vec<f64> = call.outputs.clone().map(to_f64).collect();
+                    let input_adjoints = call.op.backward(&inputs, &outputs, &output_adjoints);
+                    assert_eq!(
+                        input_adjoints.len(),
+                        call.inputs.len(),
+                        "Custom op `{}` returned {} adjoints for {} inputs.",
+                        call.op.name(),
+                        input_adjoints.len(),
+                        call.inputs.len()
+                    );
+                    for (&arg, input_adjoint) in call.inputs.iter().zip(input_adjoints) {
+                        adjoints[arg] += A::from_f64(input_adjoint);
+                    }
+                }
+                _ if adjoint == A::ZERO => {}
+                Op::Add(a, b) => {
+                    adjoints[a] += adjoint;
+                    adjoints[b] += adjoint;
+                }
+                Op::Sub(a, b) => {
+                    adjoints[a] += adjoint;
+                    adjoints[b] -= adjoint;
+                }
+                Op::Mul(a, b) => {
+                    adjoints[a] += adjoint * value_of(b);
+                    adjoints[b] += adjoint * value_of(a);
+                }
+                Op::Div(a, b) => {
+                    let denominator = value_of(b);
+                    adjoints[a] += adjoint / denominator;
+                    adjoints[b] -= adjoint * value / denominator;
+                }
+                Op::Neg(a) => adjoints[a] -= adjoint,
+                Op::Sin(a) => adjoints[a] += adjoint * value_of(a).cos(),
+                Op::Cos(a) => adjoints[a] -= adjoint * value_of(a).sin(),
+                Op::Tan(a) => {
+                    let cos = value_of(a).cos();
+                    adjoints[a] += adjoint / (cos * cos);
+                }
+                Op::Exp(a) => adjoints[a] += adjoint * value,
+                Op::Ln(a) => adjoints[a] += adjoint / value_of(a),
+                Op::Sqrt(a) => adjoints[a] += adjoint / (value + value),
+                Op::Powi(a, exponent) => {
+                    if exponent != 0 {
+                        let factor = A::from_f64(f64::from(exponent));
+                        adjoints[a] += adjoint * factor * powi_shifted(value_of(a), exponent, 1);
+                    }
+                }
+                Op::Powf(a, exponent) => {
+                    if exponent != 0.0 {
+                        let factor = A::from_f64(exponent);
+                        adjoints[a] += adjoint * factor * value_of(a).powf(factor - A::ONE);
+                    }
+                }
+            }
+        }
+
+        Gradients {
+            adjoints,
+            near_ties: self.near_ties(&recording),
+        }
+    }
+
+    fn near_ties(&self, recording: &Recording<T>) -> Vec<Comparison> {
+        let value_of = |index: usize| recording.nodes[index].value.to_f64();
+        recording
+            .comparisons
+            .iter()
+            .filter(|comparison| {
+                comparison.is_near_tie(
+                    value_of(comparison.lhs),
+                    value_of(comparison.rhs),
+                    self.tie_tolerance,
+                )
+            })
+            .copied()
+            .collect()
+    }
+
+    pub(crate) const fn from_parts(nodes: Vec<Node<T>>, calls: Vec<CustomCall>) -> Self {
+        Self {
+            recording: RefCell::new(Recording {
+                nodes,
+                calls,
+                comparisons: Vec::new(),
+                timing: Timing {
+                    started: None,
+                    forward: None,
+                    reverse: Duration::ZERO,
+                    sweeps: 0,
+                    peak: 0,
+                },
+            }),
+            tie_tolerance: DEFAULT_TIE_TOLERANCE,
+        }
+    }
+
+    fn compare(&self, op: CmpOp, lhs: Var<'_, T>, rhs: Var<'_, T>) -> bool {
+        self.assert_owns(lhs);
+        self.assert_owns(rhs);
+        let (lhs_value, rhs_value) = (lhs.value.to_f64(), rhs.value.to_f64());
+        let result = op.apply(lhs_value, rhs_value);
+        self.recording.borrow_mut().comparisons.push(Comparison {
+            op,
+            lhs: lhs.index,
+            rhs: rhs.index,
+            result,
+            margin: lhs_value - rhs_value,
+        });
+        result
+    }
+
+    fn push(&self, op: Op, value: T) -> Var<'_, T> {
+        let mut recording = self.recording.borrow_mut();
+        let index = recording.nodes.len();
+        if index == 0 {
+            recording.timing.started = Some(Instant::now());
+        }
+        recording.nodes.push(Node { op, value });
+        Var {
+            tape: self,
+            index,
+            value,
+        }
+    }
+
+    fn assert_owns(&self, var: Var<'_, T>) {
+        assert!(
+            std::ptr::eq(self, var.tape),
+            "Variable {} belongs to a different tape.",
+            var.index
+        );
+    }
+}
+
+/// Результат обратного прохода: сопряжённые значения всех узлов ленты в типе `A`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradients<A = f64> {
+    adjoints: Vec<A>,
+    near_ties: Vec<Comparison>,
+}
+
+impl<A: Real> Gradients<A> {
+    /// Производная выхода по переменной `var`.
+    #[must_use]
+    pub fn wrt<T: Real>(&self, var: Var<'_, T>) -> A {
+        self.adjoints[var.index]
+    }
+
+    #[must_use]
+    pub fn adjoints(&self) -> &[A] {
+        &self.adjoints
+    }
+
+    /// Сравнения, операнды которых совпали с точностью до допуска ленты.
+    /// Если список не пуст, градиент может быть односторонним: при малом
+    /// сдвиге входов выполнилась бы другая ветвь.
+    #[must_use]
+    pub fn near_ties(&self) -> &[Comparison] {
+        &self.near_ties
+    }
+}
+
+/// Переменная на ленте: значение прямого прохода и индекс узла.
+#[derive(Clone, Copy)]
+pub struct Var<'t, T: Real = f64> {
+    tape: &'t Tape<T>,
+    index: usize,
+    value: T,
+}
+
+impl<T: Real> fmt::Debug for Var<'_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Var")
+            .field("index", &self.index)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<'t, T: Real> Var<'t, T> {
+    #[must_use]
+    pub const fn value(&self) -> T {
+        self.value
+    }
+
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    #[must_use]
+    pub const fn tape(&self) -> &'t Tape<T> {
+        self.tape
+    }
+
+    #[must_use]
+    pub fn sin(self) -> Self {
+        self.unary(Op::Sin(self.index), self.value.sin())
+    }
+
+    #[must_use]
+    pub fn cos(self) -> Self {
+        self.unary(Op::Cos(self.index), self.value.cos())
+    }
+
+    #[must_use]
+    pub fn tan(self) -> Self {
+        self.unary(Op::Tan(self.index), self.value.tan())
+    }
+
+    #[must_use]
+    pub fn exp(self) -> Self {
+        self.unary(Op::Exp(self.index), self.value.exp())
+    }
+
+    /// # Panics
+    /// Если значение не положительно.
+    #[must_use]
+    pub fn ln(self) -> Self {
+        assert!(
+            self.value > T::ZERO,
+            "ln is only defined for positive values, received {}",
+            self.value
+        );
+        self.unary(Op::Ln(self.index), self.value.ln())
+    }
+
+    /// # Panics
+    /// Если значение отрицательно.
+    #[must_use]
+    pub fn sqrt(self) -> Self {
+        assert!(
+            self.value >= T::ZERO,
+            "sqrt is only defined for non-negative values, received {}",
+            self.value
+        );
+        self.unary(Op::Sqrt(self.index), self.value.sqrt())
+    }
+
+    #[must_use]
+    pub fn powi(self, exponent: i32) -> Self {
+        self.unary(Op::Powi(self.index, exponent), self.value.powi(exponent))
+    }
+
+    #[must_use]
+    pub fn powf(self, exponent: f64) -> Self {
+        let value = self.value.powf(T::from_f64(exponent));
+        self.unary(Op::Powf(self.index, exponent), value)
+    }
+
+    /// `self < rhs` с записью сравнения на ленту.
+    ///
+    /// # Panics
+    /// Если `rhs` принадлежит другой ленте.
+    #[must_use]
+    pub fn lt(self, rhs: Self) -> bool {
+        self.tape.compare(CmpOp::Lt, self, rhs)
+    }
+
+    /// `self <= rhs` с записью сравнения на ленту.
+    ///
+    /// # Panics
+    /// Если `rhs` принадлежит другой ленте.
+    #[must_use]
+    pub fn le(self, rhs: Self) -> bool {
+        self.tape.compare(CmpOp::Le, self, rhs)
+    }
+
+    /// `self > rhs` с записью сравнения на ленту.
+    ///
+    /// # Panics
+    /// Если `rhs` принадлежит другой ленте.
+    #[must_use]
+    pub fn gt(self, rhs: Self) -> bool {
+        self.tape.compare(CmpOp::Gt, self, rhs)
+    }
+
+    /// `self >= rhs` с записью сравнения на ленту.
+    ///
+    /// # Panics
+    /// Если `rhs` принадлежит другой ленте.
+    #[must_use]
+    pub fn ge(self, rhs: Self) -> bool {
+        self.tape.compare(CmpOp::Ge, self, rhs)
+    }
+
+    /// `self == rhs` с записью сравнения на ленту.
+    ///
+    /// # Panics
+    /// Если `rhs` принадлежит другой ленте.
+    #[must_use]
+    pub fn eq_value(self, rhs: Self) -> bool {
+        self.tape.compare(CmpOp::Eq, self, rhs)
+    }
+
+    fn unary(self, op: Op, value: T) -> Self {
+        self.tape.push(op, value)
+    }
+
+    fn binary(self, rhs: Self, op: Op, value: T) -> Self {
+        self.tape.assert_owns(rhs);
+        self.tape.push(op, value)
+    }
+
+    fn lift(self, value: T) -> Self {
+        self.tape.constant(value)
+    }
+}
+
+impl<T: Real> Add for Var<'_, T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.binary(rhs, Op::Add(self.index, rhs.index), self.value + rhs.value)
+    }
+}
+
+impl<T: Real> Add<T> for Var<'_, T> {
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self {
+        self + self.lift(rhs)
+    }
+}
+
+impl<T: Real> Sub for Var<'_, T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.binary(rhs, Op::Sub(self.index, rhs.index), self.value - rhs.value)
+    }
+}
+
+impl<T: Real> Sub<T> for Var<'_, T> {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self {
+        self - self.lift(rhs)
+    }
+}
+
+impl<T: Real> Mul for Var<'_, T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.binary(rhs, Op::Mul(self.index, rhs.index), self.value * rhs.value)
+    }
+}
+
+impl<T: Real> Mul<T> for Var<'_, T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        self * self.lift(rhs)
+    }
+}
+
+impl<T: Real> Div for Var<'_, T> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self.binary(rhs, Op::Div(self.index, rhs.index), self.value / rhs.value)
+    }
+}
+
+impl<T: Real> Div<T> for Var<'_, T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self {
+        self / self.lift(rhs)
+    }
+}
+
+impl<T: Real> Neg for Var<'_, T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.unary(Op::Neg(self.index), -self.value)
+    }
+}
+
+/// `x^(exponent - shift)` без целочисленного вычитания: у `i32::MIN` оно
+/// переполняется, и тогда показатель считается в `f64`.
+pub(crate) fn powi_shifted<R: Real>(x: R, exponent: i32, shift: i32) -> R {
+    exponent.checked_sub(shift).map_or_else(
+        || x.powf(R::from_f64(f64::from(exponent) - f64::from(shift))),
+        |lower| x.powi(lower),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomOp, Real, Tape, Var};
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    /// Перемножает два входа и возвращает произведение и сумму.
+    struct ProductAndSum;
+
+    impl CustomOp for ProductAndSum {
+        fn name(&self) -> &'static str {
+            "product_and_sum"
+        }
+
+        fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+            vec![inputs[0] * inputs[1], inputs[0] + inputs[1]]
+        }
+
+        fn backward(&self, inputs: &[f64], _outputs: &[f64], adjoints: &[f64]) -> Vec<f64> {
+            vec![
+                adjoints[0].mul_add(inputs[1], adjoints[1]),
+                adjoints[0].mul_add(inputs[0], adjoints[1]),
+            ]
+        }
+    }
+
+    #[test]
+    fn gradient_matches_hand_written_reverse_pass() {
+        let tape = Tape::new();
+        let x = tape.var(2.0);
+        let y = tape.var(1.0);
+        let f = x * y + x.sin();
+
+        let grads = tape.gradient(f);
+        assert!((f.value() - (2.0 + 2.0_f64.sin())).abs() < 1e-12);
+        assert!((grads.wrt(x) - (1.0 + 2.0_f64.cos())).abs() < 1e-12);
+        assert!((grads.wrt(y) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn powi_with_minimum_exponent_has_a_gradient() {
+        let tape = Tape::new();
+        let x = tape.var(1.0);
+        let grads = tape.gradient(x.powi(i32::MIN));
+        assert!((grads.wrt(x) - f64::from(i32::MIN)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn multi_output_custom_op_propagates_all_adjoints() {
+        let tape = Tape::new();
+        let x = tape.var(3.0);
+        let y = tape.var(4.0);
+        let outputs = tape.custom(Arc::new(ProductAndSum), &[x, y]);
+        let (product, sum) = (outputs[0], outputs[1]);
+        // f = x*y + 2*(x + y) - x/y
+        let f = product + sum * 2.0 - x / y;
+
+        let grads = tape.gradient(f);
+        assert!((grads.wrt(x) - (4.0 + 2.0 - 1.0 / 4.0)).abs() < 1e-12);
+        assert!((grads.wrt(y) - (3.0 + 2.0 + 3.0 / 16.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn near_tie_comparisons_are_reported_after_sweep() {
+        let tape = Tape::new();
+        let x = tape.var(1.0);
+        let y = tape.var(1.0);
+        let far = tape.var(5.0);
+        assert!(!far.lt(x));
+        let product = x * y;
+        let f = if x.gt(y) { product + x.sin() } else { product };
+
+        let grads = tape.gradient(f);
+        let ties = grads.near_ties();
+        assert_eq!(ties.len(), 1);
+        assert_eq!((ties[0].lhs, ties[0].rhs), (x.index(), y.index()));
+        assert!(!ties[0].result);
+        assert!(ties[0].margin.abs() < f64::EPSILON);
+        assert_eq!(tape.comparisons().len(), 2);
+    }
+
+    #[test]
+    fn tie_tolerance_is_configurable() {
+        let tape = Tape::with_tie_tolerance(1e-3);
+        let x = tape.var(1.0);
+        let y = tape.var(1.0005);
+        let f = if x.le(y) { x * y } else { x + y };
+        assert_eq!(tape.gradient(f).near_ties().len(), 1);
+
+        let strict = Tape::with_tie_tolerance(0.0);
+        let x = strict.var(1.0);
+        let y = strict.var(1.0005);
+        let f = if x.le(y) { x * y } else { x + y };
+        assert!(strict.gradient(f).near_ties().is_empty());
+    }
+
+    #[test]
+    fn stats_count_ops_and_clear_keeps_capacity() {
+        let mut tape = Tape::with_capacity(64);
+        let capacity = tape.capacity();
+        assert!(capacity >= 64);
+
+        for _ in 0..3 {
+            tape.clear();
+            let x = tape.var(2.0);
+            let y = tape.var(3.0);
+            let f = (x * y).sin() + x * y;
+            let _ = tape.gradient(f);
+        }
+        let stats = tape.stats();
+        assert_eq!(tape.capacity(), capacity);
+        assert_eq!(stats.nodes, 6);
+        assert_eq!(stats.by_op["input"], 2);
+        assert_eq!(stats.by_op["mul"], 2);
+        assert_eq!(stats.by_op["sin"], 1);
+        assert_eq!(stats.reverse_sweeps, 3);
+        assert!(stats.bytes_used <= stats.bytes_reserved);
+
+        tape.clear();
+        let _ = tape.var(1.0);
+        let stats = tape.stats();
+        assert_eq!((stats.nodes, stats.peak_nodes), (1, 6));
+    }
+
+    /// Сумма квадратов невязок модели `a * sin(2π(t + c) / p) + b`, как `sse_loss`
+    /// в `regression`, на ленте с произвольным типом значений.
+    fn sse_loss<'t, T: Real>(tape: &'t Tape<T>, temps: &[T], params: [T; 4]) -> [Var<'t, T>; 5] {
+        let [a, b, p, c] = params.map(|value| tape.var(value));
+        let loss = temps
+            .iter()
+            .enumerate()
+            .map(|(day, &temp)| {
+                let day = T::from_f64(f64::from(u32::try_from(day).unwrap()));
+                let arg = (c + day) * T::from_f64(2.0 * PI) / p;
+                let residual = a * arg.sin() + b - temp;
+                residual * residual
+            })
+            .reduce(|sum, term| sum + term)
+            .unwrap();
+        [a, b, p, c, loss]
+    }
+
+    /// Наибольшая относительная ошибка градиента `sse_loss` по отношению к `f64`.
+    fn relative_error(gradient: [f64; 4], reference: [f64; 4]) -> f64 {
+        gradient
+            .iter()
+            .zip(reference)
+            .map(|(value, exact)| (value - exact).abs() / exact.abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn f64_accumulation_reduces_f32_gradient_error() {
+        #[allow(clippy::cast_possible_truncation)]
+        let temps: Vec<f32> = (0..1068)
+            .map(|day| {
+                let t = f64::from(day);
+                ((2.0 * PI * t / 365.0).sin().mul_add(12.0, 5.0) + (t * 0.37).sin()) as f32
+            })
+            .collect();
+        let params = [10.0_f32, 4.0, 360.0, 3.0];
+
+        let exact_tape = Tape::new();
+        let exact_temps: Vec<f64> = temps.iter().map(|&temp| f64::from(temp)).collect();
+        let [a, b, p, c, loss] = sse_loss(&exact_tape, &exact_temps, params.map(f64::from));
+        let grads = exact_tape.gradient(loss);
+        let exact = [a, b, p, c].map(|var| grads.wrt(var));
+
+        let tape = Tape::<f32>::default();
+        let [a, b, p, c, loss] = sse_loss(&tape, &temps, params);
+        let single = tape.gradient(loss);
+        let single = [a, b, p, c].map(|var| f64::from(single.wrt(var)));
+        let mixed = tape.gradient_in::<f64>(loss);
+        let mixed = [a, b, p, c].map(|var| mixed.wrt(var));
+
+        let single_error = relative_error(single, exact);
+        let mixed_error = relative_error(mixed, exact);
+        assert!(single_error < 1e-4, "f32 error {single_error:e}");
+        assert!(
+            mixed_error < single_error,
+            "f32: {single_error:e}, f32 + f64 accumulation: {mixed_error:e}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "different tape")]
+    fn mixing_tapes_panics() {
+        let first = Tape::new();
+        let second = Tape::new();
+        let _ = first.var(1.0) + second.var(2.0);
+    }
+}
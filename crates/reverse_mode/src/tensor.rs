@@ -0,0 +1,691 @@
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Range, Sub};
+
+use faer::Mat;
+
+/// Операция тензорного узла. Векторы хранятся как матрицы-столбцы `n × 1`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TensorOp {
+    Input,
+    Const,
+    MatMul(usize, usize),
+    /// Поэлементные операции с broadcasting: размерность `1` растягивается.
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Neg(usize),
+    Scale(usize, f64),
+    Exp(usize),
+    Ln(usize),
+    Sin(usize),
+    Cos(usize),
+    Tanh(usize),
+    Transpose(usize),
+    Slice {
+        arg: usize,
+        rows: Range<usize>,
+        cols: Range<usize>,
+    },
+    /// Редукции всех элементов в матрицу `1 × 1`.
+    Sum(usize),
+    Mean(usize),
+    LogSumExp(usize),
+}
+
+#[derive(Clone, Debug)]
+struct TensorNode {
+    op: TensorOp,
+    value: Mat<f64>,
+}
+
+/// Лента reverse-mode, узлы которой хранят целые векторы и матрицы (`faer::Mat`).
+///
+/// Один узел — одна матричная операция, поэтому лента на порядки короче
+/// поэлементной [`crate::tape::Tape`], а сопряжённые правила сами являются
+/// матричными операциями (например, для `C = A B`: `Ā = C̄ Bᵀ`, `B̄ = Aᵀ C̄`).
+#[derive(Debug, Default)]
+pub struct TensorTape {
+    nodes: RefCell<Vec<TensorNode>>,
+}
+
+impl TensorTape {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Матрица-переменная.
+    pub fn input(&self, value: Mat<f64>) -> Tensor<'_> {
+        self.push(TensorOp::Input, value)
+    }
+
+    /// Вектор-переменная (столбец).
+    pub fn vector(&self, values: &[f64]) -> Tensor<'_> {
+        self.input(Mat::from_fn(values.len(), 1, |row, _| values[row]))
+    }
+
+    pub fn constant(&self, value: Mat<f64>) -> Tensor<'_> {
+        self.push(TensorOp::Const, value)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    /// Обратный проход от скалярного (`1 × 1`) выхода.
+    ///
+    /// # Panics
+    /// Если выход не `1 × 1` или принадлежит другой ленте.
+    #[must_use]
+    pub fn gradient(&self, output: Tensor<'_>) -> TensorGradients {
+        self.assert_owns(output);
+        let nodes = self.nodes.borrow();
+        assert_eq!(
+            (
+                nodes[output.index].value.nrows(),
+                nodes[output.index].value.ncols()
+            ),
+            (1, 1),
+            "Gradient requires a scalar (1 x 1) output."
+        );
+
+        let mut adjoints: Vec<Mat<f64>> = nodes
+            .iter()
+            .map(|node| Mat::zeros(node.value.nrows(), node.value.ncols()))
+            .collect();
+        adjoints[output.index][(0, 0)] = 1.0;
+
+        for index in (0..=output.index).rev() {
+            let adjoint = std::mem::replace(&mut adjoints[index], Mat::zeros(0, 0));
+            backward(&nodes, index, &adjoint, &mut adjoints);
+            adjoints[index] = adjoint;
+        }
+
+        TensorGradients { adjoints }
+    }
+
+    fn push(&self, op: TensorOp, value: Mat<f64>) -> Tensor<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(TensorNode { op, value });
+        Tensor {
+            tape: self,
+            index: nodes.len() - 1,
+        }
+    }
+
+    fn assert_owns(&self, tensor: Tensor<'_>) {
+        assert!(
+            std::ptr::eq(self, tensor.tape),
+            "Tensor {} belongs to a different tape.",
+            tensor.index
+        );
+    }
+}
+
+/// Сопряжённое правило узла `index`: добавляет вклад в сопряжённые аргументов.
+fn backward(nodes: &[TensorNode], index: usize, adjoint: &Mat<f64>, adjoints: &mut [Mat<f64>]) {
+    let node = &nodes[index];
+    let value_of = |arg: usize| &nodes[arg].value;
+
+    match node.op {
+        TensorOp::Input | TensorOp::Const => {}
+        TensorOp::MatMul(a, b) => {
+            let da = adjoint * value_of(b).transpose();
+            let db = value_of(a).transpose() * adjoint;
+            accumulate(&mut adjoints[a], &da);
+            accumulate(&mut adjoints[b], &db);
+        }
+        TensorOp::Add(a, b) => {
+            accumulate(&mut adjoints[a], &unbroadcast(adjoint, value_of(a)));
+            accumulate(&mut adjoints[b], &unbroadcast(adjoint, value_of(b)));
+        }
+        TensorOp::Sub(a, b) => {
+            accumulate(&mut adjoints[a], &unbroadcast(adjoint, value_of(a)));
+            accumulate(&mut adjoints[b], &-unbroadcast(adjoint, value_of(b)));
+        }
+        TensorOp::Mul(a, b) => {
+            let da = zip_broadcast(adjoint, value_of(b), |g, y| g * y);
+            let db = zip_broadcast(adjoint, value_of(a), |g, x| g * x);
+            accumulate(&mut adjoints[a], &unbroadcast(&da, value_of(a)));
+            accumulate(&mut adjoints[b], &unbroadcast(&db, value_of(b)));
+        }
+        TensorOp::Div(a, b) => {
+            let da = zip_broadcast(adjoint, value_of(b), |g, y| g / y);
+            let quotient = zip_broadcast(adjoint, &node.value, |g, z| g * z);
+            let db = zip_broadcast(&quotient, value_of(b), |gz, y| -gz / y);
+            accumulate(&mut adjoints[a], &unbroadcast(&da, value_of(a)));
+            accumulate(&mut adjoints[b], &unbroadcast(&db, value_of(b)));
+        }
+        TensorOp::Neg(a) => accumulate(&mut adjoints[a], &-adjoint),
+        TensorOp::Scale(a, factor) => accumulate(&mut adjoints[a], &map(adjoint, |g| g * factor)),
+        TensorOp::Exp(a) => {
+            accumulate(
+                &mut adjoints[a],
+                &zip_broadcast(adjoint, &node.value, |g, z| g * z),
+            );
+        }
+        TensorOp::Ln(a) => {
+            accumulate(
+                &mut adjoints[a],
+                &zip_broadcast(adjoint, value_of(a), |g, x| g / x),
+            );
+        }
+        TensorOp::Sin(a) => {
+            let da = zip_broadcast(adjoint, value_of(a), |g, x| g * x.cos());
+            accumulate(&mut adjoints[a], &da);
+        }
+        TensorOp::Cos(a) => {
+            let da = zip_broadcast(adjoint, value_of(a), |g, x| -g * x.sin());
+            accumulate(&mut adjoints[a], &da);
+        }
+        TensorOp::Tanh(a) => {
+            let da = zip_broadcast(adjoint, &node.value, |g, z| g * z.mul_add(-z, 1.0));
+            accumulate(&mut adjoints[a], &da);
+        }
+        TensorOp::Transpose(a) => accumulate(&mut adjoints[a], &adjoint.transpose().to_owned()),
+        TensorOp::Slice {
+            arg,
+            ref rows,
+            ref cols,
+        } => {
+            for (row, target_row) in rows.clone().enumerate() {
+                for (col, target_col) in cols.clone().enumerate() {
+                    adjoints[arg][(target_row, target_col)] += adjoint[(row, col)];
+                }
+            }
+        }
+        TensorOp::Sum(a) => {
+            let g = adjoint[(0, 0)];
+            accumulate(&mut adjoints[a], &map(value_of(a), |_| g));
+        }
+        TensorOp::Mean(a) => {
+            let g = adjoint[(0, 0)] / element_count(value_of(a));
+            accumulate(&mut adjoints[a], &map(value_of(a), |_| g));
+        }
+        TensorOp::LogSumExp(a) => {
+            // ∂ logsumexp / ∂x = softmax(x).
+            let (g, lse) = (adjoint[(0, 0)], node.value[(0, 0)]);
+            accumulate(&mut adjoints[a], &map(value_of(a), |x| g * (x - lse).exp()));
+        }
+    }
+}
+
+/// Результат обратного прохода по [`TensorTape`].
+#[derive(Clone, Debug)]
+pub struct TensorGradients {
+    adjoints: Vec<Mat<f64>>,
+}
+
+impl TensorGradients {
+    /// Градиент выхода по тензору `tensor` (той же формы, что и сам тензор).
+    #[must_use]
+    pub fn wrt(&self, tensor: Tensor<'_>) -> &Mat<f64> {
+        &self.adjoints[tensor.index]
+    }
+}
+
+/// Тензор на ленте: индекс узла. Значение читается через [`Tensor::value`].
+#[derive(Clone, Copy)]
+pub struct Tensor<'t> {
+    tape: &'t TensorTape,
+    index: usize,
+}
+
+impl fmt::Debug for Tensor<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rows, cols) = self.shape();
+        formatter
+            .debug_struct("Tensor")
+            .field("index", &self.index)
+            .field("shape", &(rows, cols))
+            .finish()
+    }
+}
+
+impl<'t> Tensor<'t> {
+    #[must_use]
+    pub fn value(&self) -> Ref<'t, Mat<f64>> {
+        Ref::map(self.tape.nodes.borrow(), |nodes| &nodes[self.index].value)
+    }
+
+    /// Значение тензора `1 × 1` как число.
+    #[must_use]
+    pub fn scalar(&self) -> f64 {
+        self.value()[(0, 0)]
+    }
+
+    #[must_use]
+    pub fn shape(&self) -> (usize, usize) {
+        let value = self.value();
+        (value.nrows(), value.ncols())
+    }
+
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Матричное произведение `self * rhs`.
+    ///
+    /// # Panics
+    /// Если внутренние размерности не совпадают.
+    #[must_use]
+    pub fn matmul(self, rhs: Self) -> Self {
+        self.tape.assert_owns(rhs);
+        let value = {
+            let (lhs_value, rhs_value) = (self.value(), rhs.value());
+            assert_eq!(
+                lhs_value.ncols(),
+                rhs_value.nrows(),
+                "matmul: inner dimensions do not match."
+            );
+            &*lhs_value * &*rhs_value
+        };
+        self.tape
+            .push(TensorOp::MatMul(self.index, rhs.index), value)
+    }
+
+    #[must_use]
+    pub fn transpose(self) -> Self {
+        let value = self.value().transpose().to_owned();
+        self.tape.push(TensorOp::Transpose(self.index), value)
+    }
+
+    /// Подматрица `rows × cols`.
+    ///
+    /// # Panics
+    /// Если диапазоны выходят за пределы тензора.
+    #[must_use]
+    pub fn slice(self, rows: Range<usize>, cols: Range<usize>) -> Self {
+        let value = {
+            let value = self.value();
+            assert!(
+                rows.end <= value.nrows() && cols.end <= value.ncols(),
+                "slice is out of bounds."
+            );
+            value
+                .as_ref()
+                .submatrix(rows.start, cols.start, rows.len(), cols.len())
+                .to_owned()
+        };
+        let op = TensorOp::Slice {
+            arg: self.index,
+            rows,
+            cols,
+        };
+        self.tape.push(op, value)
+    }
+
+    #[must_use]
+    pub fn exp(self) -> Self {
+        self.unary(TensorOp::Exp(self.index), f64::exp)
+    }
+
+    #[must_use]
+    pub fn ln(self) -> Self {
+        self.unary(TensorOp::Ln(self.index), f64::ln)
+    }
+
+    #[must_use]
+    pub fn sin(self) -> Self {
+        self.unary(TensorOp::Sin(self.index), f64::sin)
+    }
+
+    #[must_use]
+    pub fn cos(self) -> Self {
+        self.unary(TensorOp::Cos(self.index), f64::cos)
+    }
+
+    #[must_use]
+    pub fn tanh(self) -> Self {
+        self.unary(TensorOp::Tanh(self.index), f64::tanh)
+    }
+
+    #[must_use]
+    pub fn sum(self) -> Self {
+        let value = self.value().sum();
+        self.tape
+            .push(TensorOp::Sum(self.index), Mat::from_fn(1, 1, |_, _| value))
+    }
+
+    #[must_use]
+    pub fn mean(self) -> Self {
+        let value = {
+            let value = self.value();
+            value.sum() / element_count(&value)
+        };
+        self.tape
+            .push(TensorOp::Mean(self.index), Mat::from_fn(1, 1, |_, _| value))
+    }
+
+    /// Устойчивый `log Σ exp(x)`: максимум выносится за скобки.
+    #[must_use]
+    pub fn logsumexp(self) -> Self {
+        let value = {
+            let value = self.value();
+            let max = fold(&value, f64::NEG_INFINITY, f64::max);
+            if max.is_finite() {
+                max + fold(&value, 0.0, |sum, x| sum + (x - max).exp()).ln()
+            } else {
+                max
+            }
+        };
+        self.tape.push(
+            TensorOp::LogSumExp(self.index),
+            Mat::from_fn(1, 1, |_, _| value),
+        )
+    }
+
+    fn unary(self, op: TensorOp, f: impl Fn(f64) -> f64) -> Self {
+        let value = map(&self.value(), f);
+        self.tape.push(op, value)
+    }
+
+    fn elementwise(self, rhs: Self, op: TensorOp, f: impl Fn(f64, f64) -> f64) -> Self {
+        self.tape.assert_owns(rhs);
+        let value = zip_broadcast(&self.value(), &rhs.value(), f);
+        self.tape.push(op, value)
+    }
+}
+
+impl Add for Tensor<'_> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.elementwise(rhs, TensorOp::Add(self.index, rhs.index), |x, y| x + y)
+    }
+}
+
+impl Sub for Tensor<'_> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.elementwise(rhs, TensorOp::Sub(self.index, rhs.index), |x, y| x - y)
+    }
+}
+
+impl Mul for Tensor<'_> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.elementwise(rhs, TensorOp::Mul(self.index, rhs.index), |x, y| x * y)
+    }
+}
+
+impl Div for Tensor<'_> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self.elementwise(rhs, TensorOp::Div(self.index, rhs.index), |x, y| x / y)
+    }
+}
+
+impl Mul<f64> for Tensor<'_> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        self.unary(TensorOp::Scale(self.index, rhs), |x| x * rhs)
+    }
+}
+
+impl Neg for Tensor<'_> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.unary(TensorOp::Neg(self.index), |x| -x)
+    }
+}
+
+fn element_count(value: &Mat<f64>) -> f64 {
+    let count = value.nrows() * value.ncols();
+    f64::from(u32::try_from(count).expect("tensor is too large"))
+}
+
+fn map(value: &Mat<f64>, f: impl Fn(f64) -> f64) -> Mat<f64> {
+    Mat::from_fn(
+        value.nrows(),
+        value.ncols(),
+        |row, col| f(value[(row, col)]),
+    )
+}
+
+fn fold(value: &Mat<f64>, init: f64, f: impl Fn(f64, f64) -> f64) -> f64 {
+    let mut acc = init;
+    for col in 0..value.ncols() {
+        for row in 0..value.nrows() {
+            acc = f(acc, value[(row, col)]);
+        }
+    }
+    acc
+}
+
+/// Размер по одной оси после broadcasting.
+fn broadcast_dim(lhs: usize, rhs: usize) -> usize {
+    match (lhs, rhs) {
+        _ if lhs == rhs => lhs,
+        (1, _) => rhs,
+        (_, 1) => lhs,
+        _ => panic!("Shapes cannot be broadcast: {lhs} vs {rhs}."),
+    }
+}
+
+/// Элемент `value` в позиции `(row, col)` результата broadcasting.
+fn broadcast_at(value: &Mat<f64>, row: usize, col: usize) -> f64 {
+    let row = if value.nrows() == 1 { 0 } else { row };
+    let col = if value.ncols() == 1 { 0 } else { col };
+    value[(row, col)]
+}
+
+fn zip_broadcast(lhs: &Mat<f64>, rhs: &Mat<f64>, f: impl Fn(f64, f64) -> f64) -> Mat<f64> {
+    let rows = broadcast_dim(lhs.nrows(), rhs.nrows());
+    let cols = broadcast_dim(lhs.ncols(), rhs.ncols());
+    Mat::from_fn(rows, cols, |row, col| {
+        f(broadcast_at(lhs, row, col), broadcast_at(rhs, row, col))
+    })
+}
+
+/// Суммирует сопряжённое по осям, которые были растянуты при broadcasting,
+/// возвращая его к форме `target`.
+fn unbroadcast(adjoint: &Mat<f64>, target: &Mat<f64>) -> Mat<f64> {
+    if adjoint.nrows() == target.nrows() && adjoint.ncols() == target.ncols() {
+        return adjoint.clone();
+    }
+    let mut reduced = Mat::zeros(target.nrows(), target.ncols());
+    for col in 0..adjoint.ncols() {
+        for row in 0..adjoint.nrows() {
+            let target_row = if target.nrows() == 1 { 0 } else { row };
+            let target_col = if target.ncols() == 1 { 0 } else { col };
+            reduced[(target_row, target_col)] += adjoint[(row, col)];
+        }
+    }
+    reduced
+}
+
+fn accumulate(target: &mut Mat<f64>, delta: &Mat<f64>) {
+    *target += delta;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tensor, TensorTape, map};
+    use faer::Mat;
+
+    fn matrix(rows: usize, cols: usize, seed: f64) -> Mat<f64> {
+        Mat::from_fn(rows, cols, |row, col| {
+            0.7f64
+                .mul_add(f64::from(u32::try_from(row * cols + col).unwrap()), seed)
+                .sin()
+        })
+    }
+
+    /// Значения в `[1, 3]`: подходят для `ln` и знаменателя `Div`.
+    fn positive(rows: usize, cols: usize, seed: f64) -> Mat<f64> {
+        map(&matrix(rows, cols, seed), |x| x + 2.0)
+    }
+
+    /// Тензорная функция входов для проверки конечными разностями.
+    type Function = for<'t> fn(&[Tensor<'t>]) -> Tensor<'t>;
+
+    /// `Σ f(inputs) ⊙ W` с фиксированными весами `W`, чтобы сопряжённое на
+    /// выходе не было одинаковым во всех элементах, и градиенты по входам.
+    fn weighted(inputs: &[Mat<f64>], f: Function) -> (f64, Vec<Mat<f64>>) {
+        let tape = TensorTape::new();
+        let vars: Vec<Tensor<'_>> = inputs.iter().map(|m| tape.input(m.clone())).collect();
+        let out = f(&vars);
+        let (rows, cols) = out.shape();
+        let loss = (out * tape.constant(matrix(rows, cols, 0.4))).sum();
+        let grads = tape.gradient(loss);
+        let gradients = vars.iter().map(|&var| grads.wrt(var).clone()).collect();
+        (loss.scalar(), gradients)
+    }
+
+    /// Сравнивает градиенты `f` по всем элементам всех входов с центральными
+    /// разностями.
+    fn assert_matches_finite_differences(name: &str, inputs: &[Mat<f64>], f: Function) {
+        let (_, gradients) = weighted(inputs, f);
+        let h = 1e-6;
+        for (which, input) in inputs.iter().enumerate() {
+            assert_eq!(
+                (gradients[which].nrows(), gradients[which].ncols()),
+                (input.nrows(), input.ncols()),
+                "{name}: gradient of input {which} has the wrong shape"
+            );
+            for col in 0..input.ncols() {
+                for row in 0..input.nrows() {
+                    let shifted = |delta: f64| {
+                        let mut inputs = inputs.to_vec();
+                        inputs[which][(row, col)] += delta;
+                        weighted(&inputs, f).0
+                    };
+                    let numeric = (shifted(h) - shifted(-h)) / (2.0 * h);
+                    let analytic = gradients[which][(row, col)];
+                    assert!(
+                        (analytic - numeric).abs() < 1e-7 * numeric.abs().max(1.0),
+                        "{name}: input {which} at ({row}, {col}): {analytic} vs {numeric}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn elementwise_binary_ops_match_finite_differences_with_broadcasting() {
+        let ops: [(&str, Function); 4] = [
+            ("add", |v| v[0] + v[1]),
+            ("sub", |v| v[0] - v[1]),
+            ("mul", |v| v[0] * v[1]),
+            ("div", |v| v[0] / v[1]),
+        ];
+        // Каждый операнд по очереди растягивается по строкам, столбцам и целиком.
+        let shapes = [
+            ((3, 2), (3, 2)),
+            ((3, 2), (1, 2)),
+            ((3, 2), (3, 1)),
+            ((3, 2), (1, 1)),
+            ((1, 2), (3, 2)),
+            ((3, 1), (3, 2)),
+            ((1, 1), (3, 2)),
+            ((3, 1), (1, 2)),
+        ];
+        for (name, op) in ops {
+            for ((lhs_rows, lhs_cols), (rhs_rows, rhs_cols)) in shapes {
+                let inputs = [
+                    positive(lhs_rows, lhs_cols, 0.3),
+                    positive(rhs_rows, rhs_cols, 1.7),
+                ];
+                let name = format!("{name} {lhs_rows}x{lhs_cols} by {rhs_rows}x{rhs_cols}");
+                assert_matches_finite_differences(&name, &inputs, op);
+            }
+        }
+    }
+
+    #[test]
+    fn unary_ops_and_sum_match_finite_differences() {
+        let ops: [(&str, Function); 8] = [
+            ("neg", |v| -v[0]),
+            ("scale", |v| v[0] * -1.5),
+            ("exp", |v| v[0].exp()),
+            ("ln", |v| v[0].ln()),
+            ("sin", |v| v[0].sin()),
+            ("cos", |v| v[0].cos()),
+            ("tanh", |v| v[0].tanh()),
+            ("sum", |v| v[0].sum()),
+        ];
+        for (name, op) in ops {
+            assert_matches_finite_differences(name, &[positive(3, 2, 0.9)], op);
+        }
+    }
+
+    /// `L = logsumexp(tanh(X W + b))` для проверки matmul, broadcasting и редукций.
+    fn loss(x: &Mat<f64>, w: &Mat<f64>, b: &Mat<f64>) -> f64 {
+        let tape = TensorTape::new();
+        let x = tape.constant(x.clone());
+        let w = tape.input(w.clone());
+        let b = tape.input(b.clone());
+        (x.matmul(w) + b).tanh().logsumexp().scalar()
+    }
+
+    #[test]
+    fn dense_layer_gradient_matches_finite_differences() {
+        let (x0, w0, b0) = (matrix(4, 3, 0.1), matrix(3, 2, 1.3), matrix(1, 2, 2.9));
+        let tape = TensorTape::new();
+        let x = tape.constant(x0.clone());
+        let w = tape.input(w0.clone());
+        let b = tape.input(b0.clone());
+        let out = (x.matmul(w) + b).tanh().logsumexp();
+        let grads = tape.gradient(out);
+
+        let h = 1e-6;
+        for row in 0..3 {
+            for col in 0..2 {
+                let (mut plus, mut minus) = (w0.clone(), w0.clone());
+                plus[(row, col)] += h;
+                minus[(row, col)] -= h;
+                let numeric = (loss(&x0, &plus, &b0) - loss(&x0, &minus, &b0)) / (2.0 * h);
+                assert!((grads.wrt(w)[(row, col)] - numeric).abs() < 1e-8);
+            }
+        }
+        for col in 0..2 {
+            let (mut plus, mut minus) = (b0.clone(), b0.clone());
+            plus[(0, col)] += h;
+            minus[(0, col)] -= h;
+            let numeric = (loss(&x0, &w0, &plus) - loss(&x0, &w0, &minus)) / (2.0 * h);
+            assert!((grads.wrt(b)[(0, col)] - numeric).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn slice_transpose_and_mean_route_adjoints() {
+        let tape = TensorTape::new();
+        let a = tape.input(matrix(3, 3, 0.5));
+        // mean(A[0..2, 1..3]ᵀ * 2): каждому элементу среза достаётся 2 / 4.
+        let out = (a.slice(0..2, 1..3).transpose() * 2.0).mean();
+        let grads = tape.gradient(out);
+        let da = grads.wrt(a);
+
+        for row in 0..3 {
+            for col in 0..3 {
+                let expected = if row < 2 && col >= 1 { 0.5 } else { 0.0 };
+                assert!((da[(row, col)] - expected).abs() < 1e-15);
+            }
+        }
+    }
+
+    #[test]
+    fn logsumexp_is_stable_for_large_inputs() {
+        let tape = TensorTape::new();
+        let v = tape.vector(&[1000.0, 1000.0]);
+        let out = v.logsumexp();
+        let grads = tape.gradient(out);
+        assert!((out.scalar() - (1000.0 + 2.0_f64.ln())).abs() < 1e-12);
+        assert!((grads.wrt(v)[(0, 0)] - 0.5).abs() < 1e-12);
+    }
+}